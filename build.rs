@@ -0,0 +1,8 @@
+//the target triple is only known to build scripts, `--version` reports it
+fn main() {
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+}
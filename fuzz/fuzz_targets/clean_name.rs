@@ -0,0 +1,30 @@
+#![no_main]
+//only the cleanup of asset names is used
+#![allow(dead_code, unused_imports)]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../src/naming.rs"]
+mod naming;
+#[path = "../../src/parser/mod.rs"]
+mod parser;
+
+use naming::NameRules;
+
+//an asset name and the settings of the cleanup
+fuzz_target!(|input: (&str, bool, bool, Option<u8>, Vec<String>)| {
+    let (asset, ascii, strip_boilerplate, max_asset_len, stop_tokens) = input;
+    let rules = NameRules {
+        ascii,
+        strip_boilerplate,
+        max_asset_len: max_asset_len.map(usize::from),
+        stop_tokens,
+        ..NameRules::default()
+    };
+
+    let name = rules.clean_name(asset);
+    if let Some(max_len) = rules.max_asset_len {
+        assert!(name.chars().count() <= max_len);
+    }
+    let _ = rules.clean_stem(name);
+});
@@ -0,0 +1,21 @@
+#![no_main]
+//only the date extraction of the parser is used
+#![allow(dead_code, unused_imports)]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../src/parser/mod.rs"]
+mod parser;
+
+//the text extracted from a PDF, a first line ending in .pdf is the name of the file
+fuzz_target!(|input: &[u8]| {
+    let Ok(input) = std::str::from_utf8(input) else {
+        return;
+    };
+    let (file_name, text) = match input.split_once('\n') {
+        Some((file_name, text)) if file_name.ends_with(".pdf") => (Some(file_name), text),
+        _ => (None, input),
+    };
+
+    let _ = parser::fuzzing::extract_date(text, file_name);
+});
@@ -0,0 +1,32 @@
+#![no_main]
+//only the parser and the template of the crate are used
+#![allow(dead_code, unused_imports)]
+
+use std::path::Path;
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../src/naming.rs"]
+mod naming;
+#[path = "../../src/parser/mod.rs"]
+mod parser;
+
+use naming::Template;
+use parser::DateSource;
+
+//the text extracted from a PDF, a first line ending in .pdf is the name of the file
+fuzz_target!(|input: &[u8]| {
+    let Ok(input) = std::str::from_utf8(input) else {
+        return;
+    };
+    let (file_name, text) = match input.split_once('\n') {
+        Some((file_name, text)) if file_name.ends_with(".pdf") => (Some(file_name), text),
+        _ => (None, input),
+    };
+
+    let Some(mut data) = parser::parse_pdf_data(text, file_name) else {
+        return;
+    };
+    data.prefer_date(&[DateSource::Value, DateSource::PeriodEnd, DateSource::Header]);
+    let _ = Template::default().render(&data, Path::new(file_name.unwrap_or("fuzz.pdf")));
+});
@@ -0,0 +1,53 @@
+use std::{io, path::Path};
+
+use crate::{
+    process::{self, format_size, Outcome, RunOptions},
+    timings::Timings,
+};
+
+/// Renames the PDF at `path`, or all PDFs in the directory at `path`, and moves them into
+/// `<root>/<year>/<type>/`. Every move is recorded in the journal of the archive, with
+/// `options.optimize` the total space saved is reported.
+pub fn run(path: &Path, root: &Path, options: &RunOptions) -> io::Result<()> {
+    let options = RunOptions {
+        archive_root: Some(root.to_path_buf()),
+        ..options.clone()
+    };
+
+    println!("Archiving {:?} to {:?}", path, root);
+    let summary = process::run(path, &options, &mut Timings::default())?;
+    println!(
+        "{} {}, {} skipped, {} filtered out, {} already archived",
+        summary.renamed(),
+        if options.dry_run {
+            "would be archived"
+        } else {
+            "archived"
+        },
+        summary.skipped(),
+        summary.filtered(),
+        summary.already_renamed()
+    );
+
+    let (before, after) = summary
+        .files
+        .iter()
+        .filter_map(|file| match &file.outcome {
+            Outcome::Renamed {
+                optimized: Some(optimized),
+                ..
+            } => Some((optimized.before, optimized.after)),
+            _ => None,
+        })
+        .fold((0, 0), |(before, after), (b, a)| (before + b, after + a));
+    if before > 0 {
+        println!(
+            "Optimized from {} to {}, {} saved",
+            format_size(before),
+            format_size(after),
+            format_size(before.saturating_sub(after))
+        );
+    }
+
+    Ok(())
+}
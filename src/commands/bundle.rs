@@ -0,0 +1,203 @@
+use std::{
+    ffi::OsStr,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use lopdf::{dictionary, text_string, Document, Object, ObjectId};
+
+use super::{pdf_files, pdf_files_recursive};
+use crate::{naming::Template, pdf};
+
+//page attributes a page may inherit from its parents, they are lost with the parents otherwise
+const INHERITABLE: &[&[u8]] = &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+const MAX_TREE_DEPTH: usize = 64;
+
+/// Combines the PDFs in `folder` named with `template` whose date is in `year` (and whose type
+/// is `doc_type`) into one PDF at `output`, ordered by date, with a bookmark per document.
+pub fn run(
+    folder: &Path,
+    year: i32,
+    doc_type: Option<&str>,
+    template: &Template,
+    recursive: bool,
+    output: &Path,
+) -> io::Result<()> {
+    let matcher = template.matcher();
+    let paths = if recursive {
+        pdf_files_recursive(folder)?
+    } else {
+        pdf_files(folder)?
+    };
+
+    let mut documents = Vec::new();
+    for path in paths {
+        let Some(stem) = path.file_stem().and_then(OsStr::to_str) else {
+            continue;
+        };
+        let Some(fields) = matcher.parse(stem) else {
+            continue;
+        };
+        if fields.year == Some(year)
+            && doc_type.is_none_or(|doc_type| fields.doc_type.as_deref() == Some(doc_type))
+        {
+            documents.push((fields.date, stem.to_string(), path));
+        }
+    }
+    documents.sort();
+
+    if documents.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no documents of {year} named with {template} found in {folder:?}"),
+        ));
+    }
+
+    let titled: Vec<(String, PathBuf)> = documents
+        .into_iter()
+        .map(|(_, stem, path)| (stem, path))
+        .collect();
+    let mut bundle = merge(&titled)?;
+    bundle
+        .save(output)
+        .map_err(|err| io::Error::other(format!("{}: {err}", output.display())))?;
+
+    println!("Bundled {} document(s) into {:?}", titled.len(), output);
+    Ok(())
+}
+
+//append the pages of all documents to a new one, with a bookmark titled like the document on
+//its first page
+fn merge(documents: &[(String, PathBuf)]) -> io::Result<Document> {
+    let mut bundle = Document::with_version("1.5");
+    let pages_id = bundle.new_object_id();
+    let mut next_id = bundle.max_id + 1;
+    let mut kids = Vec::new();
+    let mut bookmarks = Vec::new();
+
+    for (title, path) in documents {
+        let mut document = pdf::load(path)?;
+        document.renumber_objects_with(next_id);
+        next_id = document.max_id + 1;
+
+        let pages: Vec<ObjectId> = document.get_pages().into_values().collect();
+        let Some(&first_page) = pages.first() else {
+            eprintln!(
+                "File {:?} skipped: it has no pages",
+                path.file_name().unwrap_or_default()
+            );
+            continue;
+        };
+        for &page in &pages {
+            inherit_attributes(&mut document, page);
+        }
+
+        for (id, object) in document.objects {
+            match object.type_name().unwrap_or_default() {
+                //replaced by the catalog, page tree and outline of the bundle
+                b"Catalog" | b"Pages" | b"Outlines" => {}
+                b"Page" => {
+                    let mut page = object;
+                    if let Object::Dictionary(dict) = &mut page {
+                        dict.set("Parent", pages_id);
+                    }
+                    bundle.objects.insert(id, page);
+                }
+                _ => {
+                    bundle.objects.insert(id, object);
+                }
+            }
+        }
+        kids.extend(pages);
+        bookmarks.push((title.as_str(), first_page));
+    }
+
+    bundle.max_id = next_id - 1;
+    let count = kids.len() as i64;
+    bundle.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids.into_iter().map(Object::Reference).collect::<Vec<_>>(),
+            "Count" => count,
+        }),
+    );
+    let outlines_id = add_outline(&mut bundle, &bookmarks);
+    let catalog_id = bundle.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "Outlines" => outlines_id,
+        "PageMode" => "UseOutlines",
+    });
+    bundle.trailer.set("Root", catalog_id);
+
+    Ok(bundle)
+}
+
+//copy the attributes a page inherits from its page tree onto the page itself
+fn inherit_attributes(document: &mut Document, page: ObjectId) {
+    let mut inherited = Vec::new();
+    let mut node = page;
+    //page trees are shallow, the limit stops at cycles in broken files
+    for _ in 0..MAX_TREE_DEPTH {
+        let Ok(parent) = document
+            .get_dictionary(node)
+            .and_then(|dict| dict.get(b"Parent"))
+            .and_then(Object::as_reference)
+        else {
+            break;
+        };
+        if let Ok(dict) = document.get_dictionary(parent) {
+            for &key in INHERITABLE {
+                if let Ok(value) = dict.get(key) {
+                    inherited.push((key, value.clone()));
+                }
+            }
+        }
+        node = parent;
+    }
+
+    if let Ok(dict) = document.get_dictionary_mut(page) {
+        //the nearest parent comes first and wins
+        for (key, value) in inherited {
+            if !dict.has(key) {
+                dict.set(key, value);
+            }
+        }
+    }
+}
+
+//a flat outline with one item per bookmark, titles may contain umlauts
+fn add_outline(bundle: &mut Document, bookmarks: &[(&str, ObjectId)]) -> ObjectId {
+    let outlines_id = bundle.new_object_id();
+    let item_ids: Vec<ObjectId> = bookmarks.iter().map(|_| bundle.new_object_id()).collect();
+
+    for (i, ((title, page), &id)) in bookmarks.iter().zip(&item_ids).enumerate() {
+        let mut item = dictionary! {
+            "Title" => text_string(title),
+            "Parent" => outlines_id,
+            "Dest" => vec![Object::Reference(*page), "Fit".into()],
+        };
+        if i > 0 {
+            item.set("Prev", item_ids[i - 1]);
+        }
+        if let Some(&next) = item_ids.get(i + 1) {
+            item.set("Next", next);
+        }
+        bundle.objects.insert(id, Object::Dictionary(item));
+    }
+
+    let mut outlines = dictionary! {
+        "Type" => "Outlines",
+        "Count" => item_ids.len() as i64,
+    };
+    if let (Some(&first), Some(&last)) = (item_ids.first(), item_ids.last()) {
+        outlines.set("First", first);
+        outlines.set("Last", last);
+    }
+    bundle
+        .objects
+        .insert(outlines_id, Object::Dictionary(outlines));
+    outlines_id
+}
@@ -0,0 +1,295 @@
+use std::{env, fmt::Display, io, path::Path};
+
+use super::doctor::{error, ok, warning};
+use crate::{
+    config::Config, placeholder::Placeholders, preflight, process::RunOptions, retry::Retry,
+};
+
+/// Checks the config file `file` and the paths and commands a run on `path` with `options` would
+/// use, and prints the effective settings. Returns `false` if a check failed that would break the
+/// run.
+pub fn run(
+    file: Option<&Path>,
+    config: &Config,
+    path: Option<&Path>,
+    options: &RunOptions,
+) -> io::Result<bool> {
+    let mut passed = true;
+
+    match file {
+        Some(file) => ok(format!("config file {:?} is valid", file)),
+        None => ok("no config file, the defaults are used"),
+    }
+
+    match path {
+        Some(path) if !path.exists() => {
+            passed = false;
+            error(format!("{:?} does not exist", path));
+        }
+        Some(path) => match preflight::check(path, options, false) {
+            Ok(()) => ok(format!("{:?} can be processed", path)),
+            Err(err) => {
+                passed = false;
+                error(format!("{:?}: {err}", path));
+            }
+        },
+        None => warning("no path, pass one or set `folder` in the config file"),
+    }
+
+    if let Some(root) = &config.archive_root {
+        if root.is_dir() {
+            ok(format!("archive root {:?} exists", root));
+        } else if root.exists() {
+            passed = false;
+            error(format!("archive root {:?} is not a directory", root));
+        } else {
+            warning(format!(
+                "archive root {:?} does not exist yet, `archive` creates it",
+                root
+            ));
+        }
+    }
+
+    for (hook, command) in [
+        ("pre_rename", &options.hooks.pre_rename),
+        ("post_rename", &options.hooks.post_rename),
+    ] {
+        let Some(command) = command else {
+            continue;
+        };
+        match command.first() {
+            None => {
+                passed = false;
+                error(format!("[hooks] {hook} is an empty command"));
+            }
+            Some(program) if !is_program(program) => {
+                passed = false;
+                error(format!("[hooks] {hook}: program {program:?} not found"));
+            }
+            Some(program) => ok(format!("[hooks] {hook} runs {program:?}")),
+        }
+    }
+    if options.hooks.timeout == Some(0) {
+        passed = false;
+        error("[hooks] timeout is 0, every hook would be killed right away");
+    }
+
+    if let Some(webhook) = &config.webhook {
+        if webhook.url.starts_with("http://") || webhook.url.starts_with("https://") {
+            ok(format!("[webhook] posts to {}", webhook.url));
+        } else {
+            passed = false;
+            error(format!(
+                "[webhook] url {:?} is not an http(s) URL",
+                webhook.url
+            ));
+        }
+    }
+    if config.email.is_some() && !cfg!(feature = "email") {
+        warning("[email] is ignored, build with --features email");
+    }
+    if config.otlp_endpoint.is_some() && !cfg!(feature = "otlp") {
+        passed = false;
+        error("otlp_endpoint is set, but traces can only be exported with --features otlp");
+    }
+
+    println!("Effective settings:");
+    setting(
+        "config file",
+        file.map_or("none".to_string(), |file| format!("{:?}", file)),
+    );
+    setting(
+        "path",
+        path.map_or("none".to_string(), |path| format!("{:?}", path)),
+    );
+    setting("template", &options.template);
+    setting("separator", format!("{:?}", options.template.separator()));
+    setting(
+        "document type labels",
+        list(
+            options
+                .template
+                .doc_type_labels()
+                .iter()
+                .map(|(doc_type, label)| format!("{doc_type} = {label}")),
+        ),
+    );
+    setting(
+        "date preference",
+        if options.date_preference.is_empty() {
+            "the parser's choice".to_string()
+        } else {
+            list(options.date_preference.iter())
+        },
+    );
+    setting(
+        "renamed pattern",
+        options
+            .renamed_pattern
+            .as_ref()
+            .map_or("names built with the template".to_string(), |pattern| {
+                pattern.to_string()
+            }),
+    );
+    setting(
+        "organized patterns",
+        list(options.organized_patterns.iter()),
+    );
+    setting("abbreviations", options.name_rules.abbreviations.len());
+    setting("ascii", on_off(options.name_rules.ascii));
+    setting(
+        "strip boilerplate",
+        if options.name_rules.strip_boilerplate && !options.name_rules.stop_tokens.is_empty() {
+            format!("on, also {}", list(options.name_rules.stop_tokens.iter()))
+        } else {
+            on_off(options.name_rules.strip_boilerplate).to_string()
+        },
+    );
+    setting("max asset length", limit(options.name_rules.max_asset_len));
+    setting("max path length", limit(options.max_path_len));
+
+    let filter = &options.filter;
+    let mut filters = Vec::new();
+    if !filter.only_types.is_empty() {
+        filters.push(format!("only {}", filter.only_types.join(",")));
+    }
+    if !filter.skip_types.is_empty() {
+        filters.push(format!("skip {}", filter.skip_types.join(",")));
+    }
+    if !filter.isins.is_empty() {
+        filters.push(format!("ISIN {}", filter.isins.join(",")));
+    }
+    if let Some(cents) = filter.min_cents {
+        filters.push(format!("amount at least {}", amount(cents)));
+    }
+    if let Some(cents) = filter.max_cents {
+        filters.push(format!("amount at most {}", amount(cents)));
+    }
+    if let Some(max_size) = filter.max_size.filter(|&max_size| max_size > 0) {
+        filters.push(format!("at most {max_size} bytes"));
+    }
+    if let Some(modified_after) = filter.modified_after {
+        filters.push(format!(
+            "modified after {}",
+            chrono::DateTime::<chrono::Local>::from(modified_after).format("%Y-%m-%d %H:%M")
+        ));
+    }
+    setting("filter", list(filters.iter()));
+
+    setting(
+        "files",
+        if options.retry_unparsed {
+            "only the ones not parsed before".to_string()
+        } else if options.since_last_run {
+            "added since the last run".to_string()
+        } else if let Some(newer_than) = options.newer_than {
+            format!("added after {newer_than}")
+        } else {
+            "all".to_string()
+        },
+    );
+    setting(
+        "recursive",
+        match (options.recursive, options.follow_links) {
+            (true, true) => "on, following links",
+            (true, false) => "on",
+            (false, _) => "off",
+        },
+    );
+    setting(
+        "unparsed files",
+        match &options.unparsed_dir {
+            Some(dir) if options.copy_unparsed => format!("copied to {:?}", dir),
+            Some(dir) => format!("moved to {:?}", dir),
+            None => "left in place".to_string(),
+        },
+    );
+    setting("set pdf info", on_off(options.set_info));
+    setting("thumbnails", on_off(options.thumbnails));
+    setting("spotlight", on_off(options.spotlight));
+    setting("clear quarantine", on_off(options.clear_quarantine));
+    setting("provenance xattrs", on_off(options.provenance));
+    setting(
+        "placeholders",
+        match options.placeholders {
+            Placeholders::Hydrate => "hydrate",
+            Placeholders::Skip => "skip",
+            Placeholders::Local => "local",
+        },
+    );
+    setting("rename retries", retries(options.rename_retry));
+    setting("io retries", retries(options.io_retry));
+    setting(
+        "archive root",
+        config
+            .archive_root
+            .as_ref()
+            .map_or("none".to_string(), |root| format!("{:?}", root)),
+    );
+    setting(
+        "folder layout",
+        config.folder_layout.clone().unwrap_or_default(),
+    );
+    setting("dry run", on_off(options.dry_run));
+
+    Ok(passed)
+}
+
+/// Reports a config file that cannot be read or has invalid settings.
+pub fn invalid(err: &io::Error) {
+    error(err.to_string());
+}
+
+fn setting(name: &str, value: impl Display) {
+    println!("  {name:<22}{value}");
+}
+
+fn list(items: impl Iterator<Item = impl Display>) -> String {
+    let items: Vec<_> = items.map(|item| item.to_string()).collect();
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn limit(max: Option<usize>) -> String {
+    max.map_or("unlimited".to_string(), |max| max.to_string())
+}
+
+fn amount(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, (cents % 100).abs())
+}
+
+fn retries(retry: Retry) -> String {
+    format!(
+        "{} times, first after {} ms",
+        retry.retries,
+        retry.delay.as_millis()
+    )
+}
+
+//a program given with its path has to exist, others are looked up in PATH
+fn is_program(program: &str) -> bool {
+    let program = Path::new(program);
+    if program.components().count() > 1 {
+        return program.is_file();
+    }
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| {
+            let candidate = dir.join(program);
+            candidate.is_file()
+                || candidate
+                    .with_extension(env::consts::EXE_EXTENSION)
+                    .is_file()
+        })
+    })
+}
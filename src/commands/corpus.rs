@@ -0,0 +1,136 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{
+    parser::parse_pdf_data,
+    process::{self, RunOptions},
+};
+
+/// Parses every text fixture listed in the expectations of `dir` and compares the filename a run
+/// with `options` would give it with the expected one. The expectations are read from `expected.toml`
+/// (`"fixture.txt" = "expected filename.pdf"`) or `expected.csv` (`fixture,filename`); an
+/// empty expected filename means the fixture must not be parsed. Returns `false` if any
+/// fixture failed.
+pub fn run(dir: &Path, options: &RunOptions) -> io::Result<bool> {
+    let expectations = load_expectations(dir)?;
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    println!("Running {} fixture(s) in {:?}", expectations.len(), dir);
+
+    for (fixture, expected) in &expectations {
+        let text = match fs::read_to_string(dir.join(fixture)) {
+            Ok(text) => text,
+            Err(err) => {
+                failed += 1;
+                println!("  FAIL  {fixture}: {err}");
+                continue;
+            }
+        };
+
+        let path = dir.join(fixture);
+        let actual = parse_pdf_data(&text, None)
+            .map(|mut data| {
+                data.prefer_date(&options.date_preference);
+                format!("{}.pdf", process::file_stem(&mut data, &path, options))
+            })
+            .unwrap_or_default();
+
+        if &actual == expected {
+            passed += 1;
+            println!("  PASS  {fixture}");
+        } else {
+            failed += 1;
+            println!(
+                "  FAIL  {fixture}: expected {}, got {}",
+                describe(expected),
+                describe(&actual)
+            );
+        }
+    }
+
+    println!("{passed} passed, {failed} failed");
+
+    Ok(failed == 0)
+}
+
+fn describe(filename: &str) -> String {
+    if filename.is_empty() {
+        "no match".to_string()
+    } else {
+        format!("{filename:?}")
+    }
+}
+
+//read expected.toml or expected.csv, fixture names map to expected filenames
+fn load_expectations(dir: &Path) -> io::Result<BTreeMap<String, String>> {
+    let toml_path = dir.join("expected.toml");
+    let csv_path = dir.join("expected.csv");
+
+    if toml_path.is_file() {
+        let content = fs::read_to_string(&toml_path)?;
+        toml::from_str(&content).map_err(|err| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {err}", toml_path.display()),
+            )
+        })
+    } else if csv_path.is_file() {
+        let content = fs::read_to_string(&csv_path)?;
+        let mut expectations = BTreeMap::new();
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || (i == 0 && line.trim() == "fixture,filename") {
+                continue;
+            }
+            match split_csv_line(line).as_slice() {
+                [fixture, filename] => {
+                    expectations.insert(fixture.clone(), filename.clone());
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "{}:{}: expected two columns: fixture,filename",
+                            csv_path.display(),
+                            i + 1
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(expectations)
+    } else {
+        Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("neither expected.toml nor expected.csv found in {:?}", dir),
+        ))
+    }
+}
+
+//split a CSV line, fields may be quoted with "" as escaped quote
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
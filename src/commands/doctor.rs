@@ -0,0 +1,254 @@
+use std::{
+    env,
+    ffi::OsStr,
+    fs::{self, read_dir},
+    io,
+    path::Path,
+};
+
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Document, Object, Stream,
+};
+
+use crate::{
+    extract_text_from_mem,
+    naming::{RenamedDetector, Template},
+    process::RunOptions,
+    state::State,
+    thumbnail::{self, THUMBNAILS_SUPPORTED},
+};
+
+//documents are small, warn when less space than this is left
+const LOW_DISK_SPACE: u64 = 100 * 1024 * 1024;
+
+//the text of the PDF the extraction is probed with
+const PROBE_TEXT: &str = "TR_PDF_rename doctor";
+
+/// Checks the environment for a run on `folder` with the `options` read from the config file
+/// `config_file` and prints actionable diagnostics, counting the files the options take as
+/// renamed. Returns `false` if a check failed that would break the run.
+pub fn run(
+    folder: &Path,
+    config_file: Option<&Path>,
+    options: io::Result<RunOptions>,
+) -> io::Result<bool> {
+    let mut passed = true;
+
+    println!("Checking {:?}", folder);
+
+    let detector = match options {
+        Ok(options) => {
+            match config_file {
+                Some(file) => ok(format!("config file {:?} is valid", file)),
+                None => ok("no config file, the defaults are used"),
+            }
+            RenamedDetector::new(&options.template, options.renamed_pattern.as_ref())
+        }
+        Err(err) => {
+            passed = false;
+            error(format!(
+                "config file {:?} is invalid ({err}), the run would stop",
+                config_file.unwrap_or(Path::new(""))
+            ));
+            RenamedDetector::new(&Template::default(), None)
+        }
+    };
+
+    if !folder.is_dir() {
+        error(format!(
+            "{:?} is not a directory, pass the folder containing your PDF files",
+            folder
+        ));
+        return Ok(false);
+    }
+    ok("folder exists");
+
+    //try to actually create a file, permissions alone do not tell about ACLs or read-only mounts
+    let probe = folder.join(".tr_pdf_rename_doctor");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            fs::remove_file(&probe)?;
+            ok("folder is writable");
+        }
+        Err(err) => {
+            passed = false;
+            error(format!(
+                "cannot create files in the folder ({err}), renaming will fail; check the folder permissions"
+            ));
+        }
+    }
+
+    match available_space(folder) {
+        Some(space) if space < LOW_DISK_SPACE => warning(format!(
+            "only {} MB of disk space left, the state file cannot be written when the disk is full",
+            space / 1024 / 1024
+        )),
+        Some(space) => ok(format!(
+            "{} MB of disk space available",
+            space / 1024 / 1024
+        )),
+        None => warning("available disk space could not be determined"),
+    }
+
+    match probe_pdf().and_then(|pdf| extract_text_from_mem(&pdf)) {
+        Ok(text) if text.contains(PROBE_TEXT) => ok("text extraction: pdf-extract works"),
+        Ok(_) => {
+            passed = false;
+            error("text extraction: pdf-extract returned no text for a test document");
+        }
+        Err(err) => {
+            passed = false;
+            error(format!("text extraction: pdf-extract failed ({err})"));
+        }
+    }
+    if THUMBNAILS_SUPPORTED {
+        match thumbnail::probe() {
+            Ok(()) => ok("thumbnails: pdfium library loaded"),
+            Err(err) => {
+                passed = false;
+                error(format!("thumbnails: {err}"));
+            }
+        }
+    }
+
+    match State::load(folder) {
+        Ok(state) => match state.last_run {
+            Some(last_run) => ok(format!("state file is valid, last run {last_run}")),
+            None => ok("no previous run recorded"),
+        },
+        //only the options reading the state stop on it
+        Err(err) => warning(format!(
+            "state file is unreadable ({err}), --since-last-run and --retry-unparsed would fail; delete {:?} to start over",
+            folder.join(crate::state::STATE_FILE_NAME)
+        )),
+    }
+
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    match locale {
+        Some(locale) if is_utf8_locale(&locale) => ok(format!("locale {locale}")),
+        Some(locale) => warning(format!(
+            "locale {locale} is not UTF-8, names with umlauts may be displayed wrongly"
+        )),
+        None if cfg!(windows) => ok("locale: Windows, filenames are Unicode"),
+        None => warning("no locale set (LANG), names with umlauts may be displayed wrongly"),
+    }
+
+    let mut pdfs = 0;
+    let mut renamed = 0;
+    let mut non_utf8 = 0;
+    for entry in read_dir(folder)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(OsStr::to_str) != Some("pdf") {
+            continue;
+        }
+        pdfs += 1;
+        match path.file_name().and_then(OsStr::to_str) {
+            Some(name) if detector.is_renamed(name) => renamed += 1,
+            Some(_) => {}
+            None => non_utf8 += 1,
+        }
+    }
+    ok(format!(
+        "{pdfs} PDF file(s), {renamed} already renamed, {} to process",
+        pdfs - renamed - non_utf8
+    ));
+    if non_utf8 > 0 {
+        passed = false;
+        error(format!(
+            "{non_utf8} PDF file(s) have names that are not valid Unicode, rename them by hand first"
+        ));
+    }
+
+    Ok(passed)
+}
+
+pub(super) fn ok(message: impl AsRef<str>) {
+    println!("  ok       {}", message.as_ref());
+}
+
+pub(super) fn warning(message: impl AsRef<str>) {
+    println!("  warning  {}", message.as_ref());
+}
+
+pub(super) fn error(message: impl AsRef<str>) {
+    println!("  error    {}", message.as_ref());
+}
+
+//a one page PDF showing PROBE_TEXT
+fn probe_pdf() -> io::Result<Vec<u8>> {
+    let mut document = Document::with_version("1.5");
+    let pages_id = document.new_object_id();
+    let font_id = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+    });
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+            Operation::new("Td", vec![50.into(), 700.into()]),
+            Operation::new("Tj", vec![Object::string_literal(PROBE_TEXT)]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    let content_id = document.add_object(Stream::new(
+        dictionary! {},
+        content.encode().map_err(io::Error::other)?,
+    ));
+    let page_id = document.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+    });
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }),
+    );
+    let catalog_id = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog_id);
+
+    let mut pdf = Vec::new();
+    document.save_to(&mut pdf)?;
+    Ok(pdf)
+}
+
+fn is_utf8_locale(locale: &str) -> bool {
+    let locale = locale.to_ascii_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+//free space for unprivileged users on the filesystem of `folder`
+#[cfg(unix)]
+fn available_space(folder: &Path) -> Option<u64> {
+    use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(folder.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    //SAFETY: path is a valid C string and stat points to writable memory for one statvfs
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    //SAFETY: statvfs returned success, so it initialized stat
+    let stat = unsafe { stat.assume_init() };
+    //the field types differ between platforms
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+#[cfg(not(unix))]
+fn available_space(_folder: &Path) -> Option<u64> {
+    None
+}
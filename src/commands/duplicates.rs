@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use chrono::NaiveDate;
+
+use super::{delete_document, pdf_files};
+use crate::{
+    extract_text,
+    parser::{parse_pdf_data, Amount},
+};
+
+//documents are the same if all of these match, whatever their bytes are
+type Identity = (NaiveDate, String, Option<String>, Option<Amount>);
+
+/// Groups the PDFs in `folder` by date, document type, ISIN and amount and prints the groups
+/// with more than one file, e.g. settlements Trade Republic issued twice. With `delete` the files
+/// of a group that are byte-identical to the first are moved to the trash, or removed if
+/// `permanently` is set. Files that only share the data, like two savings plan executions on one
+/// day, are kept unless `different` is set as well.
+pub fn run(folder: &Path, delete: bool, different: bool, permanently: bool) -> io::Result<()> {
+    let mut groups: BTreeMap<Identity, Vec<_>> = BTreeMap::new();
+
+    for path in pdf_files(folder)? {
+        let Some(data) = extract_text(&path)
+            .ok()
+            .and_then(|text| parse_pdf_data(&text, path.file_name()?.to_str()))
+        else {
+            continue;
+        };
+        groups
+            .entry((data.date, data.doc_type, data.isin, data.amount))
+            .or_default()
+            .push(path);
+    }
+
+    let mut clusters = 0;
+    let mut deleted = 0;
+    let mut kept = 0;
+    let mut failed = 0;
+    for ((date, doc_type, isin, amount), paths) in &groups {
+        if paths.len() < 2 {
+            continue;
+        }
+        clusters += 1;
+
+        println!(
+            "{date} {doc_type} {} {}:",
+            isin.as_deref().unwrap_or("(no ISIN)"),
+            amount
+                .as_ref()
+                .map(Amount::to_string)
+                .unwrap_or_else(|| "(no amount)".to_string())
+        );
+
+        //byte-identical copies are worth telling apart from reissued documents
+        let first = fs::read(&paths[0])?;
+        for (i, path) in paths.iter().enumerate() {
+            let identical = i > 0 && fs::read(path)? == first;
+            println!(
+                "  {:?}{}",
+                path.file_name().unwrap_or_default(),
+                if identical {
+                    " (identical to the first file)"
+                } else {
+                    ""
+                }
+            );
+            //the first file of the group is the one kept
+            if !delete || i == 0 {
+                continue;
+            }
+            //the same data does not make the same document, only copies go without confirmation
+            if !identical && !different {
+                kept += 1;
+                println!("    kept, pass --delete-different to delete it as well");
+                continue;
+            }
+            match delete_document(path, permanently) {
+                Ok(()) => {
+                    deleted += 1;
+                    println!("    {}", if permanently { "deleted" } else { "trashed" });
+                }
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "Could not delete {:?}: {err}",
+                        path.file_name().unwrap_or_default()
+                    );
+                }
+            }
+        }
+    }
+
+    println!("{clusters} group(s) of duplicate documents found");
+    if delete && permanently {
+        println!("{deleted} deleted, {kept} kept as they differ, {failed} failed");
+    } else if delete {
+        println!("{deleted} moved to the trash, {kept} kept as they differ, {failed} failed");
+    }
+
+    Ok(())
+}
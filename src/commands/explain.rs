@@ -0,0 +1,62 @@
+use std::{ffi::OsStr, fmt::Write, io, path::Path};
+
+use crate::{
+    extract_text,
+    naming::Template,
+    parser::{parse_pdf_data_traced, Trace},
+    provenance,
+};
+
+/// Prints which heuristics fired while parsing `path` and the resulting filename.
+pub fn run(path: &Path, template: &Template) -> io::Result<()> {
+    let text = extract_text(path)?;
+
+    println!("Explaining {:?}", path);
+    if let Some(provenance) = provenance::read(path) {
+        println!(
+            "  {:<10}{:?}, parsed {} by version {}",
+            "renamed",
+            provenance.original_name,
+            provenance
+                .parsed_at
+                .map_or("at an unknown time".to_string(), |parsed_at| format!(
+                    "at {parsed_at}"
+                )),
+            provenance.version.as_deref().unwrap_or("unknown")
+        );
+        if let (Some(template), Some(rules)) = (&provenance.template, &provenance.rules) {
+            println!("  {:<10}{template}, parser rules {rules}", "scheme");
+        }
+    }
+    print!("{}", explanation(&text, path, template));
+
+    Ok(())
+}
+
+/// Describes the parser decisions for `text` and the filename the file at `path` would get, one
+/// step per line.
+pub fn explanation(text: &str, path: &Path, template: &Template) -> String {
+    let mut trace = Trace::enabled();
+    let data = parse_pdf_data_traced(text, path.file_name().and_then(OsStr::to_str), &mut trace);
+
+    let mut explanation = String::new();
+    for (topic, step) in &trace.steps {
+        let _ = writeln!(explanation, "  {topic:<10}{step}");
+    }
+
+    let _ = match data {
+        Some(data) => writeln!(
+            explanation,
+            "  {:<10}{}.pdf",
+            "filename",
+            template.render(&data, path)
+        ),
+        None => writeln!(
+            explanation,
+            "  {:<10}none, the document could not be parsed",
+            "filename"
+        ),
+    };
+
+    explanation
+}
@@ -0,0 +1,261 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Write as _,
+    io::{self, ErrorKind},
+    path::Path,
+    str::FromStr,
+};
+
+use super::write_output;
+use crate::{
+    commands::report::{decimal, parse_documents},
+    config::AccountsConfig,
+    parser::{PdfData, Side},
+};
+
+/// The plain text accounting tools `export ledger` writes for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Syntax {
+    #[default]
+    Beancount,
+    Ledger,
+}
+
+impl FromStr for Syntax {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Syntax, String> {
+        match name {
+            "beancount" => Ok(Syntax::Beancount),
+            "ledger" => Ok(Syntax::Ledger),
+            _ => Err(format!(
+                "unknown syntax {name:?}, known syntaxes: beancount, ledger"
+            )),
+        }
+    }
+}
+
+//what a document books
+enum Kind {
+    //shares bought with cash, or with the Saveback credited by Trade Republic
+    Buy { saveback: bool },
+    Sell,
+    Dividend,
+    Interest,
+}
+
+//one line of an entry, without an amount the tool balances the entry with it
+struct Posting<'a> {
+    account: &'a str,
+    amount: Option<String>,
+}
+
+/// Writes the buys, sells, dividends, interest and Saveback of the documents in `folder` as
+/// entries for beancount or ledger to `output` (stdout if not given), ordered by date, booked on
+/// the `accounts` of the config file.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    accounts: &AccountsConfig,
+    syntax: Syntax,
+    output: Option<&Path>,
+) -> io::Result<()> {
+    let mut entries = String::new();
+    let mut used = BTreeSet::new();
+    let mut first_date = None;
+    let mut written = 0;
+    for (path, data) in parse_documents(folder, recursive)? {
+        let Some(kind) = kind(&data) else {
+            continue;
+        };
+        let file = path.strip_prefix(folder).unwrap_or(&path).to_string_lossy();
+        let Some(postings) = postings(&kind, &data, accounts, syntax) else {
+            eprintln!("{file:?} left out, its amount or quantity is missing");
+            continue;
+        };
+
+        let narration = format!("{} {}", data.doc_type, data.asset);
+        match syntax {
+            Syntax::Beancount => {
+                let _ = writeln!(
+                    entries,
+                    "{} * \"{}\" \"{}\"\n  file: \"{}\"",
+                    data.date,
+                    quoted(data.broker),
+                    quoted(&narration),
+                    quoted(&file)
+                );
+            }
+            Syntax::Ledger => {
+                let _ = writeln!(entries, "{} * {narration}\n    ; file: {file}", data.date);
+            }
+        }
+        let indent = match syntax {
+            Syntax::Beancount => "  ",
+            Syntax::Ledger => "    ",
+        };
+        for posting in &postings {
+            used.insert(posting.account);
+            let _ = match &posting.amount {
+                Some(amount) => writeln!(entries, "{indent}{:<40}  {amount}", posting.account),
+                None => writeln!(entries, "{indent}{}", posting.account),
+            };
+        }
+        entries.push('\n');
+        first_date.get_or_insert(data.date);
+        written += 1;
+    }
+
+    let Some(first_date) = first_date else {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no buys, sells, dividends or interest found in {folder:?}"),
+        ));
+    };
+
+    //beancount needs the accounts opened, and a booking method for sales from several lots
+    let mut content = String::new();
+    if syntax == Syntax::Beancount {
+        content.push_str("option \"booking_method\" \"FIFO\"\n\n");
+        for account in used {
+            let _ = writeln!(content, "{first_date} open {account}");
+        }
+        content.push('\n');
+    }
+    content.push_str(&entries);
+    write_output(output, &content)?;
+
+    if let Some(output) = output {
+        eprintln!("{written} entries written to {:?}", output);
+    }
+    Ok(())
+}
+
+fn kind(data: &PdfData) -> Option<Kind> {
+    match (data.side, data.doc_type.as_str()) {
+        (Some(Side::Buy), doc_type) => Some(Kind::Buy {
+            saveback: doc_type == "Wertpapierabrechnung_Saveback",
+        }),
+        (Some(Side::Sell), _) => Some(Kind::Sell),
+        (None, "Dividende") => Some(Kind::Dividend),
+        (None, "Zinsen") => Some(Kind::Interest),
+        _ => None,
+    }
+}
+
+//the postings of a document, None if an amount or the number of shares is missing
+fn postings<'a>(
+    kind: &Kind,
+    data: &PdfData,
+    accounts: &'a AccountsConfig,
+    syntax: Syntax,
+) -> Option<Vec<Posting<'a>>> {
+    //the documents list debits as negative or positive amounts, the sign is given by the kind
+    let cash = data.amount.as_ref()?;
+    let currency = cash.currency.as_str();
+    let total = cash.cents.abs();
+    let fees = data
+        .fees
+        .as_ref()
+        .filter(|fees| fees.currency == currency)
+        .map_or(0, |fees| fees.cents.abs());
+    //taxes in another currency, like a foreign withholding tax, are part of the income
+    let taxes: i64 = [
+        &data.taxes.capital_gains,
+        &data.taxes.solidarity,
+        &data.taxes.church,
+        &data.taxes.withholding,
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|tax| tax.currency == currency)
+    .map(|tax| -tax.cents)
+    .sum();
+
+    let money = |cents: i64| Some(format!("{} {currency}", decimal(cents)));
+    let shares = |quantity: &str| match syntax {
+        Syntax::Beancount => format!("{quantity} {}", data.isin.as_deref().unwrap_or_default()),
+        Syntax::Ledger => format!(
+            "{quantity} \"{}\"",
+            data.isin.as_deref().unwrap_or_default()
+        ),
+    };
+    let posting = |account: &'a String, amount: Option<String>| Posting {
+        account: account.as_str(),
+        amount,
+    };
+
+    let mut postings = Vec::new();
+    match kind {
+        Kind::Buy { saveback } => {
+            let quantity = data.quantity.as_deref().filter(|_| data.isin.is_some())?;
+            let cost = format!("{} {currency}", decimal(total - fees));
+            postings.push(posting(
+                &accounts.securities,
+                Some(match syntax {
+                    Syntax::Beancount => format!("{} {{{{{cost}}}}}", shares(quantity)),
+                    Syntax::Ledger => format!("{} @@ {cost}", shares(quantity)),
+                }),
+            ));
+            if fees != 0 {
+                postings.push(posting(&accounts.fees, money(fees)));
+            }
+            let source = if *saveback {
+                &accounts.saveback
+            } else {
+                &accounts.cash
+            };
+            postings.push(posting(source, money(-total)));
+        }
+        Kind::Sell => {
+            let quantity = data.quantity.as_deref().filter(|_| data.isin.is_some())?;
+            postings.push(posting(&accounts.cash, money(total)));
+            if fees != 0 {
+                postings.push(posting(&accounts.fees, money(fees)));
+            }
+            if taxes != 0 {
+                postings.push(posting(&accounts.taxes, money(taxes)));
+            }
+            //beancount takes the cost from the lots and books the difference as gain, ledger
+            //gets the proceeds
+            match syntax {
+                Syntax::Beancount => {
+                    let price = data
+                        .price
+                        .as_ref()
+                        .map(|price| format!(" @ {price}"))
+                        .unwrap_or_default();
+                    postings.push(posting(
+                        &accounts.securities,
+                        Some(format!("-{} {{}}{price}", shares(quantity))),
+                    ));
+                    postings.push(posting(&accounts.gains, None));
+                }
+                Syntax::Ledger => {
+                    let proceeds = money(total + fees + taxes)?;
+                    postings.push(posting(
+                        &accounts.securities,
+                        Some(format!("-{} @@ {proceeds}", shares(quantity))),
+                    ));
+                }
+            }
+        }
+        Kind::Dividend | Kind::Interest => {
+            postings.push(posting(&accounts.cash, money(total)));
+            if taxes != 0 {
+                postings.push(posting(&accounts.taxes, money(taxes)));
+            }
+            let income = match kind {
+                Kind::Dividend => &accounts.dividends,
+                _ => &accounts.interest,
+            };
+            postings.push(posting(income, None));
+        }
+    }
+    Some(postings)
+}
+
+//a string between double quotes of beancount
+fn quoted(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
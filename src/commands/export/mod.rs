@@ -0,0 +1,38 @@
+pub mod ledger;
+pub mod tax;
+pub mod transactions;
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+};
+
+/// The file formats of the `export` command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Format, String> {
+        match name {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format {name:?}, known formats: csv, json")),
+        }
+    }
+}
+
+/// Writes `content` to the file at `output`, or to stdout if not given.
+pub fn write_output(output: Option<&Path>, content: &str) -> io::Result<()> {
+    match output {
+        Some(output) => fs::write(output, content),
+        None => io::stdout().lock().write_all(content.as_bytes()),
+    }
+}
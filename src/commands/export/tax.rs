@@ -0,0 +1,114 @@
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Datelike;
+
+use super::write_output;
+use crate::{
+    commands::report::{csv, decimal, parse_documents},
+    parser::{Amount, PdfData, Side},
+};
+
+const COLUMNS: [&str; 12] = [
+    "Datum",
+    "Art",
+    "ISIN",
+    "Wertpapier",
+    "Betrag",
+    "Währung",
+    "Kapitalertragsteuer",
+    "Solidaritätszuschlag",
+    "Kirchensteuer",
+    "Quellensteuer",
+    "Währung Quellensteuer",
+    "Datei",
+];
+
+/// Writes the dividends, interest and realized gains of `year` in `folder` to `output` (stdout if
+/// not given) as CSV the way German tax software imports it: separated by semicolons, with
+/// decimal commas and dates like `31.12.2024`. The taxes withheld are positive, refunds negative.
+pub fn run(folder: &Path, recursive: bool, year: i32, output: Option<&Path>) -> io::Result<()> {
+    let mut lines = vec![COLUMNS.iter().map(|column| column.to_string()).collect()];
+    let (mut dividends, mut interest, mut sales, mut without_gain) = (0, 0, 0, 0);
+    for (path, data) in parse_documents(folder, recursive)? {
+        if data.date.year() != year {
+            continue;
+        }
+        let (kind, amount) = if data.doc_type == "Dividende" {
+            dividends += 1;
+            ("Dividende", data.gross.clone().or(data.amount.clone()))
+        } else if data.side == Some(Side::Sell) {
+            if data.gain.is_none() {
+                without_gain += 1;
+                continue;
+            }
+            sales += 1;
+            ("Veräußerungsgewinn", data.gain.clone())
+        } else if let Some(paid) = data
+            .interest
+            .clone()
+            .or(data.amount.clone().filter(|_| data.doc_type == "Zinsen"))
+        {
+            interest += 1;
+            ("Zinsen", Some(paid))
+        } else {
+            continue;
+        };
+
+        let file = path.strip_prefix(folder).unwrap_or(&path);
+        lines.push(line(&data, kind, amount, &file.to_string_lossy()));
+    }
+
+    if lines.len() == 1 {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no dividends, interest or sales of {year} found in {folder:?}"),
+        ));
+    }
+    write_output(output, &csv(&lines, ';'))?;
+    eprintln!(
+        "{dividends} dividend(s), {interest} interest payment(s) and {sales} sale(s) of {year}"
+    );
+    if without_gain > 0 {
+        eprintln!("{without_gain} sale(s) without a Gewinn or Verlust line left out");
+    }
+    Ok(())
+}
+
+fn line(data: &PdfData, kind: &str, amount: Option<Amount>, file: &str) -> Vec<String> {
+    //the documents list withheld taxes as debits
+    let withheld = |tax: &Option<Amount>| {
+        tax.as_ref()
+            .map(|tax| decimal_comma(-tax.cents))
+            .unwrap_or_default()
+    };
+    let taxes = &data.taxes;
+    vec![
+        data.date.format("%d.%m.%Y").to_string(),
+        kind.to_string(),
+        data.isin.clone().unwrap_or_default(),
+        data.asset.clone(),
+        amount
+            .as_ref()
+            .map(|amount| decimal_comma(amount.cents))
+            .unwrap_or_default(),
+        amount.map(|amount| amount.currency).unwrap_or_default(),
+        withheld(&taxes.capital_gains),
+        withheld(&taxes.solidarity),
+        withheld(&taxes.church),
+        withheld(&taxes.withholding),
+        taxes
+            .withholding
+            .as_ref()
+            .map(|tax| tax.currency.clone())
+            .unwrap_or_default(),
+        file.to_string(),
+    ]
+}
+
+//a decimal number in German notation like "-1234,56", without thousands separators
+fn decimal_comma(cents: i64) -> String {
+    decimal(cents).replace('.', ",")
+}
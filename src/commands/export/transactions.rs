@@ -0,0 +1,155 @@
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use serde::Serialize;
+
+use super::{write_output, Format};
+use crate::{
+    commands::report::{csv, decimal, parse_documents},
+    parser::{Amount, PdfData, Side},
+};
+
+/// One buy or sell, amounts as decimal numbers in their currency.
+#[derive(Serialize)]
+struct Transaction {
+    date: String,
+    #[serde(rename = "type")]
+    doc_type: String,
+    side: Side,
+    isin: Option<String>,
+    asset: String,
+    quantity: Option<String>,
+    price: Option<f64>,
+    price_currency: Option<String>,
+    fees: Option<f64>,
+    fees_currency: Option<String>,
+    amount: Option<f64>,
+    currency: Option<String>,
+    file: String,
+}
+
+const COLUMNS: [&str; 13] = [
+    "date",
+    "type",
+    "side",
+    "isin",
+    "asset",
+    "quantity",
+    "price",
+    "price_currency",
+    "fees",
+    "fees_currency",
+    "amount",
+    "currency",
+    "file",
+];
+
+/// Writes the buys and sells of the settlements in `folder` to `output` (stdout if not given) as
+/// CSV or JSON, ordered by date. Fees are positive, the amount is signed as in the document.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    format: Format,
+    output: Option<&Path>,
+) -> io::Result<()> {
+    let documents = parse_documents(folder, recursive)?;
+    let mut rows = Vec::new();
+    for (path, data) in &documents {
+        if let Some(side) = data.side {
+            rows.push((path, data, side));
+        }
+    }
+    if rows.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no buys or sells found in {folder:?}"),
+        ));
+    }
+
+    let content = match format {
+        Format::Csv => {
+            let mut lines = vec![COLUMNS.iter().map(|column| column.to_string()).collect()];
+            for &(path, data, side) in &rows {
+                lines.push(csv_row(folder, path, data, side));
+            }
+            csv(&lines, ',')
+        }
+        Format::Json => {
+            let transactions: Vec<Transaction> = rows
+                .iter()
+                .map(|&(path, data, side)| transaction(folder, path, data, side))
+                .collect();
+            let mut json = serde_json::to_string_pretty(&transactions).map_err(io::Error::other)?;
+            json.push('\n');
+            json
+        }
+    };
+    write_output(output, &content)?;
+    if let Some(output) = output {
+        eprintln!("{} transaction(s) written to {:?}", rows.len(), output);
+    }
+    Ok(())
+}
+
+fn transaction(folder: &Path, path: &Path, data: &PdfData, side: Side) -> Transaction {
+    let value = |amount: &Option<Amount>| amount.as_ref().map(|a| a.cents as f64 / 100.0);
+    let currency = |amount: &Option<Amount>| amount.as_ref().map(|a| a.currency.clone());
+    let fees = fees_paid(data);
+    Transaction {
+        date: data.date.to_string(),
+        doc_type: data.doc_type.clone(),
+        side,
+        isin: data.isin.clone(),
+        asset: data.asset.clone(),
+        quantity: data.quantity.clone(),
+        price: value(&data.price),
+        price_currency: currency(&data.price),
+        fees: value(&fees),
+        fees_currency: currency(&fees),
+        amount: value(&data.amount),
+        currency: currency(&data.amount),
+        file: file_name(folder, path),
+    }
+}
+
+fn csv_row(folder: &Path, path: &Path, data: &PdfData, side: Side) -> Vec<String> {
+    let value = |amount: &Option<Amount>| amount.as_ref().map(|a| decimal(a.cents));
+    let currency = |amount: &Option<Amount>| amount.as_ref().map(|a| a.currency.clone());
+    let fees = fees_paid(data);
+    [
+        Some(data.date.to_string()),
+        Some(data.doc_type.clone()),
+        Some(side.to_string()),
+        data.isin.clone(),
+        Some(data.asset.clone()),
+        data.quantity.clone(),
+        value(&data.price),
+        currency(&data.price),
+        value(&fees),
+        currency(&fees),
+        value(&data.amount),
+        currency(&data.amount),
+        Some(file_name(folder, path)),
+    ]
+    .into_iter()
+    .map(Option::unwrap_or_default)
+    .collect()
+}
+
+//the documents list fees as debits
+fn fees_paid(data: &PdfData) -> Option<Amount> {
+    data.fees.as_ref().map(|fees| Amount {
+        cents: fees.cents.abs(),
+        currency: fees.currency.clone(),
+    })
+}
+
+//the path below the exported folder, e.g. "2024/Wertpapierabrechnung/..." of an archive
+fn file_name(folder: &Path, path: &Path) -> String {
+    path.strip_prefix(folder)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
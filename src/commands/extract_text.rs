@@ -0,0 +1,19 @@
+use std::{io, path::Path};
+
+use crate::extract_text;
+
+/// Prints the raw text extracted from `path`, optionally prefixed with line numbers.
+pub fn run(path: &Path, line_numbers: bool) -> io::Result<()> {
+    let text = extract_text(path)?;
+
+    if line_numbers {
+        let width = text.lines().count().to_string().len();
+        for (i, line) in text.lines().enumerate() {
+            println!("{:>width$}: {line}", i + 1);
+        }
+    } else {
+        print!("{text}");
+    }
+
+    Ok(())
+}
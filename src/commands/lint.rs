@@ -0,0 +1,110 @@
+use std::{collections::BTreeMap, ffi::OsStr, io, path::Path};
+
+use chrono::NaiveDate;
+
+use super::pdf_files;
+use crate::{
+    naming::{Fields, RenamedDetector},
+    parser::{has_isin_format, is_valid_isin, known_doc_types},
+};
+
+/// Checks the names of the PDFs in `folder` the `detector` takes as renamed for malformed dates,
+/// unknown document types and invalid ISINs without opening the files. Returns `false` if any
+/// problem was found. The `doc_type_labels` used instead of document types are accepted as well.
+pub fn run(
+    folder: &Path,
+    detector: &RenamedDetector,
+    doc_type_labels: &BTreeMap<String, String>,
+) -> io::Result<bool> {
+    let mut checked = 0;
+    let mut with_problems = 0;
+
+    println!("Linting {:?}", folder);
+
+    for path in pdf_files(folder)? {
+        let (Some(file_name), Some(stem)) = (
+            path.file_name().and_then(OsStr::to_str),
+            path.file_stem().and_then(OsStr::to_str),
+        ) else {
+            continue;
+        };
+        if !detector.is_renamed(file_name) {
+            continue;
+        }
+        checked += 1;
+
+        //names built with the template are read with it, the fields of other names are guessed
+        let problems = match detector.fields(file_name) {
+            Some(fields) => lint_fields(stem, &fields),
+            None => lint_name(stem, doc_type_labels),
+        };
+        if !problems.is_empty() {
+            with_problems += 1;
+            println!("  {file_name:?}: {}", problems.join(", "));
+        }
+    }
+
+    println!(
+        "Checked {checked} file(s): {} ok, {with_problems} with problems",
+        checked - with_problems
+    );
+
+    Ok(with_problems == 0)
+}
+
+//check the fields read from a file stem built with the template, its date is valid already
+fn lint_fields(stem: &str, fields: &Fields) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(doc_type) = &fields.doc_type {
+        if !known_doc_types().any(|known| known == doc_type) {
+            problems.push(format!("unknown document type {doc_type:?}"));
+        }
+    }
+    problems.extend(invalid_isins(stem));
+    problems
+}
+
+//check a file stem of the form yyyy_mm_dd_type_asset, with '_', '-' or ' ' as separator
+fn lint_name(stem: &str, doc_type_labels: &BTreeMap<String, String>) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    //the separator follows the year
+    let separator = stem
+        .get(4..5)
+        .filter(|separator| matches!(*separator, "-" | " "))
+        .unwrap_or("_");
+
+    let date = stem.get(..10).unwrap_or(stem);
+    if NaiveDate::parse_from_str(&date.replace(separator, "_"), "%Y_%m_%d").is_err() {
+        problems.push(format!("malformed date {date:?}"));
+    }
+
+    //prefer the longest label, Wertpapierabrechnung_Sparplan over Wertpapierabrechnung
+    let type_and_asset = stem.get(11..).unwrap_or_default();
+    let doc_type = known_doc_types()
+        .map(|doc_type| {
+            doc_type_labels
+                .get(doc_type)
+                .map_or(doc_type, String::as_str)
+        })
+        .filter(|doc_type| {
+            type_and_asset
+                .strip_prefix(&doc_type.replace('_', separator))
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(separator))
+        })
+        .max_by_key(|doc_type| doc_type.len());
+    if doc_type.is_none() {
+        let label = type_and_asset.split(separator).next().unwrap_or_default();
+        problems.push(format!("unknown document type {label:?}"));
+    }
+
+    problems.extend(invalid_isins(stem));
+
+    problems
+}
+
+fn invalid_isins(stem: &str) -> impl Iterator<Item = String> + '_ {
+    stem.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| has_isin_format(word) && !is_valid_isin(word))
+        .map(|word| format!("invalid ISIN check digit in {word:?}"))
+}
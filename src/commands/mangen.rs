@@ -0,0 +1,23 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use clap::Command;
+use clap_mangen::Man;
+
+/// Writes the man pages of `cmd` and its subcommands into `out_dir`, or the page of `cmd` alone
+/// to stdout without it.
+pub fn run(cmd: Command, out_dir: Option<&Path>) -> io::Result<()> {
+    let Some(out_dir) = out_dir else {
+        let mut stdout = io::stdout().lock();
+        Man::new(cmd).render(&mut stdout)?;
+        return stdout.flush();
+    };
+
+    fs::create_dir_all(out_dir)?;
+    clap_mangen::generate_to(cmd, out_dir)?;
+    println!("Man pages written to {:?}", out_dir);
+    Ok(())
+}
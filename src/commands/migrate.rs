@@ -0,0 +1,89 @@
+use std::{ffi::OsStr, fs, io, path::Path};
+
+use super::pdf_files;
+use crate::{
+    naming::Template,
+    process::{change_case, get_unique_filename, is_case_change},
+    provenance::{self, PROVENANCE_SUPPORTED},
+};
+
+/// Renames the PDFs in `folder` named with `from` to the names `to` builds from the same
+/// fields, without extracting the PDFs again. With `provenance` the new scheme is recorded in
+/// their extended attributes.
+pub fn run(
+    folder: &Path,
+    from: &Template,
+    to: &Template,
+    dry_run: bool,
+    provenance: bool,
+) -> io::Result<()> {
+    let matcher = from.matcher();
+    let mut migrated = 0;
+    let mut skipped = 0;
+
+    for path in pdf_files(folder)? {
+        let Some(stem) = path.file_stem().and_then(OsStr::to_str) else {
+            continue;
+        };
+        let file_name = path.file_name().unwrap_or_default();
+
+        let Some(fields) = matcher.parse(stem) else {
+            skipped += 1;
+            println!("File {:?} skipped: does not match {from}", file_name);
+            continue;
+        };
+        let new_stem = match to.render_fields(&fields) {
+            Ok(new_stem) => new_stem,
+            Err(token) => {
+                skipped += 1;
+                println!(
+                    "File {:?} skipped: {token} is not part of the name, it can only be read from the PDF",
+                    file_name
+                );
+                continue;
+            }
+        };
+        if new_stem == stem {
+            continue;
+        }
+
+        let mut new_path = path.with_file_name(format!("{new_stem}.pdf"));
+        let case_change = is_case_change(&path, &new_path);
+        if new_path.exists() && !case_change {
+            new_path = get_unique_filename(new_path, to.separator());
+        }
+
+        if dry_run {
+            println!(
+                "Would rename {:?} to {:?}",
+                file_name,
+                new_path.file_name().unwrap_or_default()
+            );
+        } else {
+            if case_change {
+                change_case(&path, &new_path)?;
+            } else {
+                fs::rename(&path, &new_path)?;
+            }
+            println!(
+                "Renamed {:?} to {:?}",
+                file_name,
+                new_path.file_name().unwrap_or_default()
+            );
+            if provenance && PROVENANCE_SUPPORTED {
+                if let Err(err) = provenance::record_scheme(&new_path, &to.scheme()) {
+                    eprintln!("Attributes of {:?} not set: {err}", new_path);
+                }
+            }
+        }
+        migrated += 1;
+    }
+
+    if dry_run {
+        println!("{migrated} file(s) would be migrated, {skipped} skipped");
+    } else {
+        println!("{migrated} file(s) migrated, {skipped} skipped");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+pub mod archive;
+pub mod bundle;
+pub mod config_check;
+pub mod corpus;
+pub mod doctor;
+pub mod duplicates;
+pub mod explain;
+pub mod export;
+pub mod extract_text;
+pub mod lint;
+pub mod mangen;
+pub mod migrate;
+pub mod outdated;
+pub mod prune;
+pub mod report;
+pub mod report_issue;
+pub mod self_update;
+pub mod stats;
+pub mod verify;
+
+use std::{
+    ffi::OsStr,
+    fs::{self, read_dir},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Lists the PDF files in `folder`, sorted by name.
+pub fn pdf_files(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in read_dir(folder)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(OsStr::to_str) == Some("pdf") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Moves the document at `path` to the trash of the system, or removes it if `permanently` is
+/// set, together with the preview written next to it with `--thumbnails`.
+pub fn delete_document(path: &Path, permanently: bool) -> Result<(), String> {
+    let remove = |path: &Path| {
+        if permanently {
+            fs::remove_file(path).map_err(|err| err.to_string())
+        } else {
+            trash::delete(path).map_err(|err| err.to_string())
+        }
+    };
+    remove(path)?;
+    let thumbnail = path.with_extension("png");
+    if thumbnail.exists() {
+        let _ = remove(&thumbnail);
+    }
+    Ok(())
+}
+
+/// Lists the PDF files in `folder` and all its subdirectories, sorted by path.
+pub fn pdf_files_recursive(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = pdf_files(folder)?;
+    for entry in read_dir(folder)? {
+        let entry = entry?;
+        //symlinked directories are not followed to avoid cycles
+        if entry.file_type()?.is_dir() {
+            paths.extend(pdf_files_recursive(&entry.path())?);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
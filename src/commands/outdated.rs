@@ -0,0 +1,160 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+
+use super::{pdf_files, pdf_files_recursive};
+use crate::{
+    journal::{self, Entry},
+    parser::RULES_VERSION,
+    process::{self, Processed, RunOptions},
+    provenance::{self, PROVENANCE_SUPPORTED},
+    timings::Timings,
+};
+
+//where the scheme of a file was recorded, files of an archive are migrated like `archive` does
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Record {
+    Attributes,
+    Journal,
+}
+
+/// Lists the PDFs in `folder` that were named with another template scheme or older parser
+/// rules than `options` use, going by their extended attributes or the journal of an archive.
+/// With `migrate` they are parsed and renamed again. Returns `false` if outdated files are left.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    migrate: bool,
+    options: &RunOptions,
+) -> io::Result<bool> {
+    let scheme = options.template.scheme();
+    let journal: HashMap<PathBuf, Entry> = journal::read(folder)?
+        .into_iter()
+        .map(|entry| (folder.join(&entry.to), entry))
+        .collect();
+
+    let files = if recursive {
+        pdf_files_recursive(folder)?
+    } else {
+        pdf_files(folder)?
+    };
+
+    let mut outdated = Vec::new();
+    let mut unknown = 0;
+    for path in files {
+        let recorded = provenance::read(&path)
+            .and_then(|provenance| {
+                Some((provenance.template?, provenance.rules?, Record::Attributes))
+            })
+            .or_else(|| {
+                let entry = journal.get(&path)?;
+                Some((
+                    entry.template.clone()?,
+                    entry.rules.clone()?,
+                    Record::Journal,
+                ))
+            });
+        let Some((template, rules, record)) = recorded else {
+            unknown += 1;
+            continue;
+        };
+
+        let mut reasons = Vec::new();
+        if template != scheme {
+            reasons.push(format!("named with {template}"));
+        }
+        //the rules are versioned by date, a file named by newer rules is not outdated
+        if rules.as_str() < RULES_VERSION {
+            reasons.push(format!("named by parser rules {rules}"));
+        }
+        if reasons.is_empty() {
+            continue;
+        }
+        println!(
+            "File {:?} outdated: {}",
+            path.file_name().unwrap_or_default(),
+            reasons.join(", ")
+        );
+        outdated.push((path, record));
+    }
+
+    println!(
+        "{} file(s) not named with {scheme} and parser rules {RULES_VERSION}",
+        outdated.len()
+    );
+    if unknown > 0 {
+        println!(
+            "{unknown} file(s) without a recorded scheme, renamed by an older version or on a system without extended attributes"
+        );
+    }
+    if outdated.is_empty() {
+        return Ok(true);
+    }
+    if !migrate {
+        println!("Pass --migrate to rename them with the current scheme");
+        return Ok(false);
+    }
+
+    let mut left = 0;
+    for (path, record) in outdated {
+        let options = match record {
+            Record::Attributes => options.clone(),
+            Record::Journal => RunOptions {
+                archive_root: Some(folder.to_path_buf()),
+                ..options.clone()
+            },
+        };
+        let file_name = path.file_name().unwrap_or_default();
+
+        match process::process_file(&path, &options, &mut Timings::default()) {
+            Ok(Processed::Renamed { new_path, .. }) => println!(
+                "Renamed {:?} to {:?}",
+                file_name,
+                new_path.strip_prefix(folder).unwrap_or(&new_path)
+            ),
+            //the name was right, only the record is outdated
+            Ok(Processed::Unchanged { .. }) => {
+                match record {
+                    Record::Attributes if options.provenance && PROVENANCE_SUPPORTED => {
+                        provenance::record_scheme(&path, &scheme)?
+                    }
+                    Record::Attributes => {}
+                    Record::Journal => journal::append(
+                        folder,
+                        &Entry {
+                            moved_at: Utc::now(),
+                            from: std::path::absolute(&path)?,
+                            to: path.strip_prefix(folder).unwrap_or(&path).to_path_buf(),
+                            template: Some(scheme.clone()),
+                            rules: Some(RULES_VERSION.to_string()),
+                        },
+                    )?,
+                }
+                println!("File {:?} keeps its name", file_name);
+            }
+            Ok(Processed::Archived { existing, .. }) => {
+                left += 1;
+                println!(
+                    "File {:?} left alone: the archive holds it as {:?} already",
+                    file_name,
+                    existing.file_name().unwrap_or_default()
+                );
+            }
+            Ok(Processed::Filtered { reason, .. } | Processed::Skipped { reason, .. }) => {
+                left += 1;
+                println!("File {:?} skipped: {reason}", file_name);
+            }
+            Err(err) if err.kind() == ErrorKind::InvalidData => {
+                left += 1;
+                println!("File {:?} skipped: {err}", file_name);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(left == 0)
+}
@@ -0,0 +1,89 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Local;
+
+use super::{delete_document, pdf_files, pdf_files_recursive};
+use crate::{config::Retention, naming::Template};
+
+/// Lists the PDFs in `folder` named with `template` whose date is further back than the
+/// `retention` of their document type. With `delete` they are moved to the trash, or removed
+/// if `permanently` is set.
+pub fn run(
+    folder: &Path,
+    template: &Template,
+    retention: &BTreeMap<String, Retention>,
+    recursive: bool,
+    delete: bool,
+    permanently: bool,
+) -> io::Result<()> {
+    if retention.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "no retention rules, add a [retention] section to the config file",
+        ));
+    }
+
+    let today = Local::now().date_naive();
+    let matcher = template.matcher();
+    let paths = if recursive {
+        pdf_files_recursive(folder)?
+    } else {
+        pdf_files(folder)?
+    };
+
+    let mut expired = 0;
+    let mut failed = 0;
+    for path in paths {
+        let Some(fields) = path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .and_then(|stem| matcher.parse(stem))
+        else {
+            continue;
+        };
+        let (Some(date), Some(doc_type)) = (fields.date, fields.doc_type) else {
+            continue;
+        };
+        let Some(kept) = retention.get(&doc_type) else {
+            continue;
+        };
+        if kept.cutoff(today).is_none_or(|cutoff| date >= cutoff) {
+            continue;
+        }
+
+        expired += 1;
+        let name = path.strip_prefix(folder).unwrap_or(&path);
+        if !delete {
+            println!("{:?}: {doc_type} of {date}, kept for {kept}", name);
+            continue;
+        }
+
+        match delete_document(&path, permanently) {
+            Ok(()) => {
+                println!(
+                    "{} {:?}: {doc_type} of {date}, kept for {kept}",
+                    if permanently { "Deleted" } else { "Trashed" },
+                    name
+                );
+            }
+            Err(err) => {
+                failed += 1;
+                eprintln!("Could not delete {:?}: {err}", name);
+            }
+        }
+    }
+
+    if !delete {
+        println!("{expired} document(s) older than their retention, delete them with --delete");
+    } else if permanently {
+        println!("{} deleted, {failed} failed", expired - failed);
+    } else {
+        println!("{} moved to the trash, {failed} failed", expired - failed);
+    }
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Datelike;
+
+use super::{decimal, parse_documents, write_csv, Totals};
+use crate::parser::PdfData;
+
+//the costs of one year
+#[derive(Default)]
+struct Row {
+    documents: usize,
+    product: Totals,
+    service: Totals,
+    inducements: Totals,
+    total: Totals,
+}
+
+/// Compares the product costs, service costs and inducements of the Ex-Post Kosteninformation
+/// documents in `folder` across the years they cover. The table is printed and written to `csv`
+/// if given.
+pub fn run(folder: &Path, recursive: bool, csv: Option<&Path>) -> io::Result<()> {
+    let mut rows: BTreeMap<i32, Row> = BTreeMap::new();
+    for (_, data) in parse_documents(folder, recursive)? {
+        if data.doc_type != "Kosteninformation" || data.costs.is_empty() {
+            continue;
+        }
+        let costs = &data.costs;
+        let row = rows.entry(covered_year(&data)).or_default();
+        row.documents += 1;
+        for (totals, cost) in [
+            (&mut row.product, &costs.product),
+            (&mut row.service, &costs.service),
+            (&mut row.inducements, &costs.inducements),
+        ] {
+            if let Some(cost) = cost {
+                totals.add(cost);
+            }
+        }
+        //without a total line the costs are the product and service costs
+        match (&costs.total, &costs.product, &costs.service) {
+            (Some(total), _, _) => row.total.add(total),
+            (None, product, service) => {
+                for cost in [product, service].into_iter().flatten() {
+                    row.total.add(cost);
+                }
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no Ex-Post Kosteninformation found in {folder:?}"),
+        ));
+    }
+
+    println!("Costs");
+    println!(
+        "{:<6}  {:>9}  {:>16}  {:>16}  {:>16}  {:>16}  {:>8}",
+        "Year", "Documents", "Product", "Service", "Inducements", "Total", "Change"
+    );
+    let mut previous: Option<&Totals> = None;
+    for (year, row) in &rows {
+        println!(
+            "{year:<6}  {:>9}  {:>16}  {:>16}  {:>16}  {:>16}  {:>8}",
+            row.documents,
+            row.product.to_string(),
+            row.service.to_string(),
+            row.inducements.to_string(),
+            row.total.to_string(),
+            previous
+                .and_then(|previous| change(previous, &row.total))
+                .unwrap_or_else(|| "-".to_string())
+        );
+        previous = Some(&row.total);
+    }
+
+    if let Some(csv) = csv {
+        let mut lines = vec![vec![
+            "year".to_string(),
+            "currency".to_string(),
+            "documents".to_string(),
+            "product".to_string(),
+            "service".to_string(),
+            "inducements".to_string(),
+            "total".to_string(),
+        ]];
+        for (year, row) in &rows {
+            let columns = [&row.product, &row.service, &row.inducements, &row.total];
+            let currencies: BTreeSet<&str> = columns
+                .iter()
+                .flat_map(|totals| totals.currencies())
+                .collect();
+            for currency in currencies {
+                let mut line = vec![
+                    year.to_string(),
+                    currency.to_string(),
+                    row.documents.to_string(),
+                ];
+                line.extend(
+                    columns
+                        .iter()
+                        .map(|totals| totals.get(currency).map(decimal).unwrap_or_default()),
+                );
+                lines.push(line);
+            }
+        }
+        write_csv(csv, &lines)?;
+        println!("Written to {:?}", csv);
+    }
+
+    Ok(())
+}
+
+//the year the costs are listed for, a Kosteninformation is usually sent early in the next year
+fn covered_year(data: &PdfData) -> i32 {
+    data.tax_year
+        .or(data.period.map(|(_, end)| end.year()))
+        .unwrap_or(data.date.year() - 1)
+}
+
+//the change of the total costs in percent like "+12.5 %", if both years have them in one currency
+fn change(previous: &Totals, current: &Totals) -> Option<String> {
+    let mut currencies = previous.currencies();
+    let currency = currencies.next()?;
+    if currencies.next().is_some() || current.currencies().ne([currency]) {
+        return None;
+    }
+    let (before, after) = (previous.get(currency)?, current.get(currency)?);
+    if before == 0 {
+        return None;
+    }
+    let percent = (after - before) as f64 * 100.0 / before as f64;
+    Some(format!("{percent:+.1} %"))
+}
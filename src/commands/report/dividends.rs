@@ -0,0 +1,108 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Datelike;
+
+use super::{decimal, parse_documents, write_csv, Totals};
+
+//the dividends of one security
+#[derive(Default)]
+struct Row {
+    asset: String,
+    payments: usize,
+    gross: Totals,
+    net: Totals,
+}
+
+/// Sums the gross and net amounts of the dividends paid in `year` per ISIN and in total, from
+/// the Dividende documents in `folder`. The table is printed and written to `csv` if given.
+pub fn run(folder: &Path, recursive: bool, year: i32, csv: Option<&Path>) -> io::Result<()> {
+    let mut rows: BTreeMap<Option<String>, Row> = BTreeMap::new();
+    let mut without_gross = 0;
+    for (_, data) in parse_documents(folder, recursive)? {
+        if data.doc_type != "Dividende" || data.date.year() != year {
+            continue;
+        }
+        let row = rows.entry(data.isin).or_default();
+        if row.asset.is_empty() {
+            row.asset = data.asset;
+        }
+        row.payments += 1;
+        if let Some(amount) = &data.amount {
+            row.net.add(amount);
+        }
+        match &data.gross {
+            Some(gross) => row.gross.add(gross),
+            None => without_gross += 1,
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no dividends of {year} found in {folder:?}"),
+        ));
+    }
+
+    let mut total = Row {
+        asset: "Total".to_string(),
+        ..Row::default()
+    };
+    for row in rows.values() {
+        total.payments += row.payments;
+        total.gross.merge(&row.gross);
+        total.net.merge(&row.net);
+    }
+
+    println!("Dividends {year}");
+    println!(
+        "{:<12}  {:<30}  {:>8}  {:>16}  {:>16}",
+        "ISIN", "Asset", "Payments", "Gross", "Net"
+    );
+    for (isin, row) in rows.iter().chain([(&None, &total)]) {
+        println!(
+            "{:<12}  {:<30}  {:>8}  {:>16}  {:>16}",
+            isin.as_deref().unwrap_or(""),
+            row.asset.chars().take(30).collect::<String>(),
+            row.payments,
+            row.gross.to_string(),
+            row.net.to_string()
+        );
+    }
+    if without_gross > 0 {
+        println!("{without_gross} payment(s) without a gross amount, they are only in Net");
+    }
+
+    if let Some(csv) = csv {
+        let mut lines = vec![vec![
+            "isin".to_string(),
+            "asset".to_string(),
+            "currency".to_string(),
+            "payments".to_string(),
+            "gross".to_string(),
+            "net".to_string(),
+        ]];
+        //one line per currency, a foreign dividend may be paid out in euros
+        for (isin, row) in rows.iter().chain([(&None, &total)]) {
+            let currencies: BTreeSet<&str> =
+                row.gross.currencies().chain(row.net.currencies()).collect();
+            for currency in currencies {
+                lines.push(vec![
+                    isin.clone().unwrap_or_default(),
+                    row.asset.clone(),
+                    currency.to_string(),
+                    row.payments.to_string(),
+                    row.gross.get(currency).map(decimal).unwrap_or_default(),
+                    row.net.get(currency).map(decimal).unwrap_or_default(),
+                ]);
+            }
+        }
+        write_csv(csv, &lines)?;
+        println!("Written to {:?}", csv);
+    }
+
+    Ok(())
+}
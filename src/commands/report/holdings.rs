@@ -0,0 +1,102 @@
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::NaiveDate;
+
+use super::{decimal, parse_documents, write_csv, Totals};
+
+/// Prints the securities held at `as_of` (the latest statement if not given): the positions of
+/// the last Depotauszug in `folder` up to that date. The table is written to `csv` if given.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    as_of: Option<NaiveDate>,
+    csv: Option<&Path>,
+) -> io::Result<()> {
+    //a statement shows the holdings at the end of its period
+    let Some((path, date, data)) = parse_documents(folder, recursive)?
+        .into_iter()
+        .filter(|(_, data)| !data.positions.is_empty())
+        .map(|(path, data)| {
+            let date = data.period.map_or(data.date, |(_, end)| end);
+            (path, date, data)
+        })
+        .filter(|(_, date, _)| as_of.is_none_or(|as_of| *date <= as_of))
+        .max_by_key(|(path, date, _)| (*date, path.clone()))
+    else {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            match as_of {
+                Some(as_of) => format!("no Depotauszug up to {as_of} found in {folder:?}"),
+                None => format!("no Depotauszug found in {folder:?}"),
+            },
+        ));
+    };
+
+    let mut total = Totals::default();
+    println!(
+        "Holdings as of {date}, from {:?}",
+        path.strip_prefix(folder).unwrap_or(&path)
+    );
+    println!(
+        "{:<12}  {:<40}  {:>12}  {:>16}",
+        "ISIN", "Name", "Quantity", "Value"
+    );
+    for position in &data.positions {
+        if let Some(value) = &position.value {
+            total.add(value);
+        }
+        println!(
+            "{:<12}  {:<40}  {:>12}  {:>16}",
+            position.isin,
+            position.name.chars().take(40).collect::<String>(),
+            position.quantity,
+            position
+                .value
+                .as_ref()
+                .map_or("-".to_string(), ToString::to_string)
+        );
+    }
+    println!(
+        "{:<12}  {:<40}  {:>12}  {:>16}",
+        "Total",
+        format!("{} position(s)", data.positions.len()),
+        "",
+        total.to_string()
+    );
+
+    if let Some(csv) = csv {
+        let mut lines = vec![vec![
+            "date".to_string(),
+            "isin".to_string(),
+            "name".to_string(),
+            "quantity".to_string(),
+            "value".to_string(),
+            "currency".to_string(),
+        ]];
+        for position in &data.positions {
+            lines.push(vec![
+                date.to_string(),
+                position.isin.clone(),
+                position.name.clone(),
+                position.quantity.clone(),
+                position
+                    .value
+                    .as_ref()
+                    .map(|value| decimal(value.cents))
+                    .unwrap_or_default(),
+                position
+                    .value
+                    .as_ref()
+                    .map(|value| value.currency.clone())
+                    .unwrap_or_default(),
+            ]);
+        }
+        write_csv(csv, &lines)?;
+        println!("Written to {:?}", csv);
+    }
+
+    Ok(())
+}
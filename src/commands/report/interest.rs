@@ -0,0 +1,81 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Datelike;
+
+use super::{decimal, parse_documents, periods, write_csv, Totals};
+
+/// Sums the interest in `folder` per month and year (only of `year` if given): the amounts of the
+/// Zinsen documents and the interest lines of other documents, like the Cash Zinsen and Geldmarkt
+/// Dividende of a Sammelbeleg. The table is printed and written to `csv` if given.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    year: Option<i32>,
+    csv: Option<&Path>,
+) -> io::Result<()> {
+    let mut months: BTreeMap<(i32, u32), (usize, Totals)> = BTreeMap::new();
+    for (_, data) in parse_documents(folder, recursive)? {
+        if year.is_some_and(|year| data.date.year() != year) {
+            continue;
+        }
+        //the interest lines are more precise than the total of a document listing other items
+        let interest = data
+            .interest
+            .or(data.amount.filter(|_| data.doc_type == "Zinsen"));
+        if let Some(interest) = interest {
+            let (payments, totals) = months
+                .entry((data.date.year(), data.date.month()))
+                .or_default();
+            *payments += 1;
+            totals.add(&interest);
+        }
+    }
+
+    if months.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            match year {
+                Some(year) => format!("no interest of {year} found in {folder:?}"),
+                None => format!("no interest found in {folder:?}"),
+            },
+        ));
+    }
+
+    let rows = periods(&months, |(sum_payments, sum), (payments, totals)| {
+        *sum_payments += payments;
+        sum.merge(totals);
+    });
+
+    println!("Interest");
+    println!("{:<8}  {:>8}  {:>16}", "Period", "Payments", "Interest");
+    for (period, (payments, totals)) in &rows {
+        println!("{period:<8}  {payments:>8}  {:>16}", totals.to_string());
+    }
+
+    if let Some(csv) = csv {
+        let mut lines = vec![vec![
+            "period".to_string(),
+            "currency".to_string(),
+            "payments".to_string(),
+            "interest".to_string(),
+        ]];
+        for (period, (payments, totals)) in &rows {
+            for currency in totals.currencies() {
+                lines.push(vec![
+                    period.clone(),
+                    currency.to_string(),
+                    payments.to_string(),
+                    totals.get(currency).map(decimal).unwrap_or_default(),
+                ]);
+            }
+        }
+        write_csv(csv, &lines)?;
+        println!("Written to {:?}", csv);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+pub mod costs;
+pub mod dividends;
+pub mod holdings;
+pub mod interest;
+pub mod saveback;
+pub mod taxes;
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{pdf_files, pdf_files_recursive};
+use crate::{
+    extract_text,
+    parser::{parse_pdf_data, Amount, PdfData},
+};
+
+/// Parses the PDFs in `folder` (and its subdirectories if `recursive`), ordered by date. Files
+/// that cannot be read or parsed are counted on stderr.
+pub fn parse_documents(folder: &Path, recursive: bool) -> io::Result<Vec<(PathBuf, PdfData)>> {
+    let paths = if recursive {
+        pdf_files_recursive(folder)?
+    } else {
+        pdf_files(folder)?
+    };
+
+    let mut documents = Vec::new();
+    let mut unparsed = 0;
+    for path in paths {
+        let data = extract_text(&path)
+            .ok()
+            .and_then(|text| parse_pdf_data(&text, path.file_name().and_then(OsStr::to_str)));
+        match data {
+            Some(data) => documents.push((path, data)),
+            None => unparsed += 1,
+        }
+    }
+    if unparsed > 0 {
+        eprintln!(
+            "{unparsed} file(s) in {:?} could not be read or parsed",
+            folder
+        );
+    }
+
+    documents.sort_by(|(a_path, a), (b_path, b)| (a.date, a_path).cmp(&(b.date, b_path)));
+    Ok(documents)
+}
+
+/// Sums of amounts, kept apart by currency.
+#[derive(Debug, Default, Clone)]
+pub struct Totals(BTreeMap<String, i64>);
+
+impl Totals {
+    pub fn add(&mut self, amount: &Amount) {
+        *self.0.entry(amount.currency.clone()).or_default() += amount.cents;
+    }
+
+    pub fn merge(&mut self, other: &Totals) {
+        for (currency, cents) in &other.0 {
+            *self.0.entry(currency.clone()).or_default() += cents;
+        }
+    }
+
+    /// The sum in `currency`, if there was an amount in it.
+    pub fn get(&self, currency: &str) -> Option<i64> {
+        self.0.get(currency).copied()
+    }
+
+    pub fn currencies(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
+impl fmt::Display for Totals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("-");
+        }
+        for (i, (currency, &cents)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" + ")?;
+            }
+            write!(
+                f,
+                "{}",
+                Amount {
+                    cents,
+                    currency: currency.clone(),
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The rows of a table by month: the months of every year followed by the year, then the grand
+/// total if there are several years. The sums are built with `merge`.
+pub fn periods<T: Clone + Default>(
+    months: &BTreeMap<(i32, u32), T>,
+    merge: impl Fn(&mut T, &T),
+) -> Vec<(String, T)> {
+    let mut years: BTreeMap<i32, T> = BTreeMap::new();
+    for (&(year, _), month) in months {
+        merge(years.entry(year).or_default(), month);
+    }
+
+    let mut rows = Vec::new();
+    let mut total = T::default();
+    for (&year, sum) in &years {
+        for (&(_, month), row) in months.range((year, 1)..=(year, 12)) {
+            rows.push((format!("{year}-{month:02}"), row.clone()));
+        }
+        rows.push((year.to_string(), sum.clone()));
+        merge(&mut total, sum);
+    }
+    if years.len() > 1 {
+        rows.push(("Total".to_string(), total));
+    }
+    rows
+}
+
+/// `cents` as a decimal number like `-1234.56`, for CSV files.
+pub fn decimal(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Writes `rows` to the CSV file at `path`, the first row being the header.
+pub fn write_csv(path: &Path, rows: &[Vec<String>]) -> io::Result<()> {
+    fs::write(path, csv(rows, ','))
+}
+
+/// `rows` as CSV with fields separated by `delimiter`, the first row being the header. Fields
+/// are quoted if needed.
+pub fn csv(rows: &[Vec<String>], delimiter: char) -> String {
+    let mut csv = String::new();
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|field| csv_field(field, delimiter))
+            .collect();
+        csv.push_str(&fields.join(&delimiter.to_string()));
+        csv.push('\n');
+    }
+    csv
+}
+
+//quote a field containing the delimiter, quotes or line breaks, quotes are doubled
+fn csv_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Datelike;
+
+use super::{decimal, parse_documents, periods, write_csv, Totals};
+use crate::parser::Amount;
+
+//the executions of one period
+#[derive(Default, Clone)]
+struct Row {
+    saveback: (usize, Totals),
+    round_up: (usize, Totals),
+}
+
+/// Sums the Saveback and round-up executions in `folder` per month and year (only of `year` if
+/// given), the amounts invested being what was received. The table is printed and written to
+/// `csv` if given.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    year: Option<i32>,
+    csv: Option<&Path>,
+) -> io::Result<()> {
+    let mut months: BTreeMap<(i32, u32), Row> = BTreeMap::new();
+    for (_, data) in parse_documents(folder, recursive)? {
+        if year.is_some_and(|year| data.date.year() != year) {
+            continue;
+        }
+        let saveback = match data.doc_type.as_str() {
+            "Wertpapierabrechnung_Saveback" => true,
+            "Wertpapierabrechnung_Roundup" => false,
+            _ => continue,
+        };
+        let Some(amount) = &data.amount else {
+            continue;
+        };
+        let row = months
+            .entry((data.date.year(), data.date.month()))
+            .or_default();
+        let (executions, totals) = if saveback {
+            &mut row.saveback
+        } else {
+            &mut row.round_up
+        };
+        *executions += 1;
+        //some documents list the amount invested as a debit
+        totals.add(&Amount {
+            cents: amount.cents.abs(),
+            currency: amount.currency.clone(),
+        });
+    }
+
+    if months.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            match year {
+                Some(year) => format!("no Saveback or round-up of {year} found in {folder:?}"),
+                None => format!("no Saveback or round-up found in {folder:?}"),
+            },
+        ));
+    }
+
+    let rows = periods(&months, |sum, row| {
+        sum.saveback.0 += row.saveback.0;
+        sum.saveback.1.merge(&row.saveback.1);
+        sum.round_up.0 += row.round_up.0;
+        sum.round_up.1.merge(&row.round_up.1);
+    });
+
+    println!("Saveback and round-up");
+    println!(
+        "{:<8}  {:>8}  {:>16}  {:>8}  {:>16}",
+        "Period", "Saveback", "Amount", "Round-up", "Amount"
+    );
+    for (period, row) in &rows {
+        println!(
+            "{period:<8}  {:>8}  {:>16}  {:>8}  {:>16}",
+            row.saveback.0,
+            row.saveback.1.to_string(),
+            row.round_up.0,
+            row.round_up.1.to_string()
+        );
+    }
+
+    if let Some(csv) = csv {
+        let mut lines = vec![vec![
+            "period".to_string(),
+            "currency".to_string(),
+            "saveback_executions".to_string(),
+            "saveback".to_string(),
+            "round_up_executions".to_string(),
+            "round_up".to_string(),
+        ]];
+        for (period, row) in &rows {
+            let currencies: BTreeSet<&str> = row
+                .saveback
+                .1
+                .currencies()
+                .chain(row.round_up.1.currencies())
+                .collect();
+            for currency in currencies {
+                lines.push(vec![
+                    period.clone(),
+                    currency.to_string(),
+                    row.saveback.0.to_string(),
+                    row.saveback
+                        .1
+                        .get(currency)
+                        .map(decimal)
+                        .unwrap_or_default(),
+                    row.round_up.0.to_string(),
+                    row.round_up
+                        .1
+                        .get(currency)
+                        .map(decimal)
+                        .unwrap_or_default(),
+                ]);
+            }
+        }
+        write_csv(csv, &lines)?;
+        println!("Written to {:?}", csv);
+    }
+
+    Ok(())
+}
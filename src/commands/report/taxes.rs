@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::Datelike;
+
+use super::{decimal, parse_documents, write_csv, Totals};
+use crate::parser::Amount;
+
+//the taxes of one year, as withheld: refunds are negative
+#[derive(Default)]
+struct Row {
+    documents: usize,
+    capital_gains: Totals,
+    solidarity: Totals,
+    church: Totals,
+    withholding: Totals,
+}
+
+/// Sums the Kapitalertragsteuer, Solidaritätszuschlag, Kirchensteuer and Quellensteuer withheld
+/// per year (only `year` if given) across the documents in `folder`, refunds are subtracted.
+/// The table is printed and written to `csv` if given.
+pub fn run(
+    folder: &Path,
+    recursive: bool,
+    year: Option<i32>,
+    csv: Option<&Path>,
+) -> io::Result<()> {
+    let mut rows: BTreeMap<i32, Row> = BTreeMap::new();
+    for (_, data) in parse_documents(folder, recursive)? {
+        if year.is_some_and(|year| data.date.year() != year) {
+            continue;
+        }
+        let taxes = &data.taxes;
+        if [
+            &taxes.capital_gains,
+            &taxes.solidarity,
+            &taxes.church,
+            &taxes.withholding,
+        ]
+        .iter()
+        .all(|tax| tax.is_none())
+        {
+            continue;
+        }
+
+        let row = rows.entry(data.date.year()).or_default();
+        row.documents += 1;
+        for (totals, tax) in [
+            (&mut row.capital_gains, &taxes.capital_gains),
+            (&mut row.solidarity, &taxes.solidarity),
+            (&mut row.church, &taxes.church),
+            (&mut row.withholding, &taxes.withholding),
+        ] {
+            if let Some(tax) = tax {
+                //the documents list withheld taxes as debits
+                totals.add(&Amount {
+                    cents: -tax.cents,
+                    currency: tax.currency.clone(),
+                });
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            match year {
+                Some(year) => format!("no taxes of {year} found in {folder:?}"),
+                None => format!("no taxes found in {folder:?}"),
+            },
+        ));
+    }
+
+    println!("Taxes withheld");
+    println!(
+        "{:<6}  {:>9}  {:>20}  {:>20}  {:>16}  {:>16}",
+        "Year",
+        "Documents",
+        "Kapitalertragsteuer",
+        "Solidaritätszuschlag",
+        "Kirchensteuer",
+        "Quellensteuer"
+    );
+    for (year, row) in &rows {
+        println!(
+            "{year:<6}  {:>9}  {:>20}  {:>20}  {:>16}  {:>16}",
+            row.documents,
+            row.capital_gains.to_string(),
+            row.solidarity.to_string(),
+            row.church.to_string(),
+            row.withholding.to_string()
+        );
+    }
+
+    if let Some(csv) = csv {
+        let mut lines = vec![vec![
+            "year".to_string(),
+            "currency".to_string(),
+            "documents".to_string(),
+            "kapitalertragsteuer".to_string(),
+            "solidaritaetszuschlag".to_string(),
+            "kirchensteuer".to_string(),
+            "quellensteuer".to_string(),
+        ]];
+        for (year, row) in &rows {
+            let columns = [
+                &row.capital_gains,
+                &row.solidarity,
+                &row.church,
+                &row.withholding,
+            ];
+            let currencies: BTreeSet<&str> = columns
+                .iter()
+                .flat_map(|totals| totals.currencies())
+                .collect();
+            for currency in currencies {
+                let mut line = vec![
+                    year.to_string(),
+                    currency.to_string(),
+                    row.documents.to_string(),
+                ];
+                line.extend(
+                    columns
+                        .iter()
+                        .map(|totals| totals.get(currency).map(decimal).unwrap_or_default()),
+                );
+                lines.push(line);
+            }
+        }
+        write_csv(csv, &lines)?;
+        println!("Written to {:?}", csv);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::{collections::BTreeSet, fs, io, path::Path, sync::LazyLock};
+
+use regex::Regex;
+
+use super::explain::explanation;
+use crate::{
+    extract_text,
+    naming::Template,
+    parser::{parse_pdf_data, RULES_VERSION},
+};
+
+//IBAN, optionally written in groups of four; shorter matches like ISINs are kept
+static IBAN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,4})?\b").unwrap()
+});
+
+//the shortest IBANs (Norway) have 15 characters
+const MIN_IBAN_LEN: usize = 15;
+
+//depot, account and customer numbers: long digit sequences that are not amounts or dates
+static ACCOUNT_NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d{6,}\b").unwrap());
+
+static EMAIL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[\w.+-]+@[\w-]+\.[\w.-]+\b").unwrap());
+
+//street and house number, e.g. "Musterstraße 12a" or "Am Weg 3"
+static STREET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(.*?)([\p{L}.-]*(?:straße|strasse|str\.|weg|allee|platz|gasse|ring|damm|ufer|chaussee)) +\d+ ?[a-z]?\b").unwrap()
+});
+
+//German postal code and city
+static POSTAL_CODE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{5} +\p{Lu}[\p{L} .-]*").unwrap());
+
+/// Writes a redacted text fixture of `path` and the parse result into `out_dir`, so the
+/// document can be attached to a bug report without personal data. `extra` lists further
+/// strings to remove, e.g. the own name if it was not detected.
+pub fn run(path: &Path, out_dir: &Path, extra: &[String]) -> io::Result<()> {
+    let text = extract_text(path)?;
+    let redacted = redact(&text, extra);
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "document".to_string());
+    let fixture_path = out_dir.join(format!("{stem}.redacted.txt"));
+    let result_path = out_dir.join(format!("{stem}.result.txt"));
+
+    let mut result = format!(
+        "{} {} (parser rules {})\n{}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        RULES_VERSION,
+        explanation(&redacted, path, &Template::default())
+    );
+    if parse_pdf_data(&text, None) != parse_pdf_data(&redacted, None) {
+        result.push_str(
+            "\nNote: the redaction changed the parse result, the original text parses differently.\n",
+        );
+    }
+
+    fs::create_dir_all(out_dir)?;
+    fs::write(&fixture_path, &redacted)?;
+    fs::write(&result_path, &result)?;
+
+    println!("Wrote {:?} and {:?}", fixture_path, result_path);
+    println!(
+        "Please check both files for remaining personal data before attaching them to an issue."
+    );
+
+    Ok(())
+}
+
+/// Replaces IBANs, account numbers, e-mail addresses, the recipient address block and the
+/// strings in `extra` with placeholders, keeping the line structure the parser relies on.
+fn redact(text: &str, extra: &[String]) -> String {
+    //names are found in the line above a street, they are removed everywhere in the text
+    let lines: Vec<&str> = text.lines().collect();
+    let mut names: BTreeSet<String> = extra
+        .iter()
+        .filter(|name| !name.trim().is_empty())
+        .cloned()
+        .collect();
+    for (i, line) in lines.iter().enumerate() {
+        if STREET_RE.is_match(line) {
+            if let Some(name) = lines[..i].iter().rev().find(|line| !line.trim().is_empty()) {
+                if !name.contains("Trade Republic") {
+                    names.insert(name.trim().to_string());
+                }
+            }
+        }
+    }
+
+    let mut redacted: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            //the address of Trade Republic itself is no personal data
+            if line.contains("Trade Republic") {
+                return line.to_string();
+            }
+            let line = IBAN_RE.replace_all(line, |caps: &regex::Captures| {
+                let iban = &caps[0];
+                if iban.chars().filter(|c| !c.is_whitespace()).count() < MIN_IBAN_LEN {
+                    iban.to_string()
+                } else {
+                    "XX00REDACTEDIBAN".to_string()
+                }
+            });
+            let line = EMAIL_RE.replace_all(&line, "redacted@example.com");
+            let line = STREET_RE.replace(&line, "${1}Musterstraße 1");
+            let line = POSTAL_CODE_RE.replace_all(&line, "00000 Musterstadt");
+            ACCOUNT_NUMBER_RE
+                .replace_all(&line, |caps: &regex::Captures| "0".repeat(caps[0].len()))
+                .into_owned()
+        })
+        .collect();
+
+    //longest names first, so a full name is replaced before a part of it
+    let mut names: Vec<String> = names.into_iter().collect();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+    for line in &mut redacted {
+        for name in &names {
+            if line.contains(name.as_str()) {
+                *line = line.replace(name.as_str(), "Max Mustermann");
+            }
+        }
+    }
+
+    let mut redacted = redacted.join("\n");
+    if text.ends_with('\n') {
+        redacted.push('\n');
+    }
+    redacted
+}
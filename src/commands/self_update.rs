@@ -0,0 +1,145 @@
+use std::{
+    env, fs,
+    io::{self, ErrorKind, Read},
+    time::Duration,
+};
+
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+const LATEST_RELEASE: &str =
+    "https://api.github.com/repos/ArdentEmpiricist/TR_PDF_Rename/releases/latest";
+//the release asset with the SHA-256 of every binary, in the format of sha256sum. It comes from
+//the same release as the binary, so it only catches broken downloads, not replaced assets
+const CHECKSUMS: &str = "SHA256SUMS";
+const TIMEOUT: Duration = Duration::from_secs(60);
+//the binaries are a few MB, anything far bigger is not one of them
+const MAX_DOWNLOAD: u64 = 200 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Replaces the running binary with the one built for this platform of the latest GitHub
+/// release, if that is newer. With `check_only` it only reports if there is an update.
+pub fn run(check_only: bool) -> io::Result<()> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build();
+
+    let release: Release = serde_json::from_reader(
+        agent
+            .get(LATEST_RELEASE)
+            .call()
+            .map_err(|err| io::Error::other(err.to_string()))?
+            .into_reader(),
+    )?;
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).expect("the version is semver");
+    let latest = Version::parse(release.tag_name.trim_start_matches('v')).map_err(|err| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("release {:?}: {err}", release.tag_name),
+        )
+    })?;
+    if latest <= current {
+        println!("TR_PDF_rename {current} is up to date");
+        return Ok(());
+    }
+    if check_only {
+        println!("TR_PDF_rename {latest} is available, this is {current}");
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let asset = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "release {} has no {name:?}, update with cargo install TR_PDF_rename",
+                        release.tag_name
+                    ),
+                )
+            })
+    };
+    let binary_asset = asset(&name)?;
+    let checksums_asset = asset(CHECKSUMS)?;
+
+    println!("Downloading TR_PDF_rename {latest} ({name})");
+    let checksums = String::from_utf8(download(&agent, checksums_asset)?)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+    let binary = download(&agent, binary_asset)?;
+
+    //a binary not listed or with another checksum is never installed, it was not fully downloaded
+    let expected = checksums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, file)| file.trim().trim_start_matches('*') == name)
+        .map(|(checksum, _)| checksum.to_ascii_lowercase())
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{CHECKSUMS} of release {} lists no {name:?}",
+                    release.tag_name
+                ),
+            )
+        })?;
+    let actual = format!("{:x}", Sha256::digest(&binary));
+    if actual != expected {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "checksum of {name:?} is {actual}, {CHECKSUMS} lists {expected}, not installed"
+            ),
+        ));
+    }
+
+    let download_path = env::temp_dir().join(format!(".{name}.{}", std::process::id()));
+    fs::write(&download_path, &binary)?;
+    let replaced = self_replace::self_replace(&download_path);
+    fs::remove_file(&download_path)?;
+    replaced?;
+
+    println!("Updated TR_PDF_rename from {current} to {latest}");
+    Ok(())
+}
+
+//the binaries of a release are named after the target they are built for
+fn asset_name() -> String {
+    format!(
+        "TR_PDF_rename-{}{}",
+        env!("TARGET"),
+        env::consts::EXE_SUFFIX
+    )
+}
+
+fn download(agent: &ureq::Agent, asset: &Asset) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    agent
+        .get(&asset.browser_download_url)
+        .call()
+        .map_err(|err| io::Error::other(err.to_string()))?
+        .into_reader()
+        .take(MAX_DOWNLOAD)
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
@@ -0,0 +1,64 @@
+use std::{collections::BTreeMap, ffi::OsStr, io, path::Path};
+
+use chrono::Datelike;
+
+use super::pdf_files;
+use crate::{extract_text, parser::parse_pdf_data};
+
+/// Parses all PDFs in `folder` and prints counts per document type, year and ISIN.
+pub fn run(folder: &Path) -> io::Result<()> {
+    let mut per_type: BTreeMap<String, usize> = BTreeMap::new();
+    let mut per_year: BTreeMap<i32, usize> = BTreeMap::new();
+    let mut per_isin: BTreeMap<String, usize> = BTreeMap::new();
+    let mut total = 0;
+    let mut without_isin = 0;
+    let mut unknown = 0;
+    let mut unreadable = 0;
+
+    for path in pdf_files(folder)? {
+        total += 1;
+
+        let text = match extract_text(&path) {
+            Ok(text) => text,
+            Err(_) => {
+                unreadable += 1;
+                continue;
+            }
+        };
+        let Some(data) = parse_pdf_data(&text, path.file_name().and_then(OsStr::to_str)) else {
+            unknown += 1;
+            continue;
+        };
+
+        *per_type.entry(data.doc_type).or_default() += 1;
+        *per_year.entry(data.date.year()).or_default() += 1;
+        match data.isin {
+            Some(isin) => *per_isin.entry(isin).or_default() += 1,
+            None => without_isin += 1,
+        }
+    }
+
+    println!("{total} PDF file(s) in {:?}", folder);
+
+    println!("Per document type:");
+    print_counts(&per_type);
+    println!("Per year:");
+    print_counts(&per_year);
+    println!("Per ISIN:");
+    print_counts(&per_isin);
+    if without_isin > 0 {
+        println!("  {:<40}{without_isin:>6}", "(no ISIN)");
+    }
+
+    println!("Not classified:");
+    println!("  {:<40}{unknown:>6}", "unknown document type or date");
+    println!("  {:<40}{unreadable:>6}", "text extraction failed");
+
+    Ok(())
+}
+
+fn print_counts<K: ToString>(counts: &BTreeMap<K, usize>) {
+    for (key, count) in counts {
+        println!("  {:<40}{count:>6}", key.to_string());
+    }
+}
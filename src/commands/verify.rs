@@ -0,0 +1,150 @@
+use std::{ffi::OsStr, io, path::Path};
+
+use super::pdf_files;
+use crate::{
+    extract_text,
+    naming::{matches_generated, Fields, RenamedDetector, Template},
+    parser::parse_pdf_data,
+};
+
+/// Re-parses the already renamed PDFs in `folder` and reports files whose name no longer
+/// matches their content when named with `template`. Returns `false` if any mismatch was found.
+pub fn run(folder: &Path, template: &Template) -> io::Result<bool> {
+    let matcher = template.matcher();
+    let detector = RenamedDetector::Template(matcher.clone());
+    let mut checked = 0;
+    let mut mismatched = 0;
+    let mut unparsed = 0;
+
+    println!("Verifying {:?}", folder);
+
+    for path in pdf_files(folder)? {
+        let (Some(file_name), Some(stem)) = (
+            path.file_name().and_then(OsStr::to_str),
+            path.file_stem().and_then(OsStr::to_str),
+        ) else {
+            continue;
+        };
+        if !detector.is_renamed(file_name) {
+            continue;
+        }
+        checked += 1;
+
+        let data = match extract_text(&path) {
+            //the date of the name must not be used to check the name
+            Ok(text) => parse_pdf_data(&text, None),
+            Err(err) => {
+                unparsed += 1;
+                println!("  UNPARSED  {file_name:?}: {err}");
+                continue;
+            }
+        };
+        let Some(data) = data else {
+            unparsed += 1;
+            println!("  UNPARSED  {file_name:?}: no Trade Republic date and document type found");
+            continue;
+        };
+
+        //the counter and the original name cannot be read from the content, take them from the name
+        let name = matcher.parse(stem);
+        let expected = template
+            .render_fields(&Fields {
+                counter: name.as_ref().and_then(|name| name.counter).or(Some(1)),
+                original: name.as_ref().and_then(|name| name.original.clone()),
+                ..Fields::from(&data)
+            })
+            .ok();
+        if expected
+            .as_deref()
+            .is_some_and(|expected| matches_generated(stem, expected, template.separator()))
+        {
+            continue;
+        }
+        mismatched += 1;
+
+        //name the fields that differ by reading them back from the name
+        let differences = match name {
+            Some(name) => differences(&name, &Fields::from(&data), template.separator()),
+            None => vec![format!("name does not follow the template {template}")],
+        };
+
+        println!(
+            "  MISMATCH  {file_name:?}: {}, expected {}",
+            differences.join(", "),
+            expected
+                .map(|expected| format!("\"{expected}.pdf\""))
+                .unwrap_or_else(|| format!("a name following {template}"))
+        );
+    }
+
+    println!(
+        "Checked {checked} file(s): {} ok, {mismatched} mismatched, {unparsed} unparsed",
+        checked - mismatched - unparsed
+    );
+
+    Ok(mismatched == 0)
+}
+
+//describe the fields of the name that differ from the content
+fn differences(name: &Fields, content: &Fields, separator: char) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    if name.date.is_some() && name.date != content.date
+        || name.year.is_some() && name.year != content.year
+    {
+        differences.push(format!(
+            "date {} in the content",
+            content
+                .date
+                .map(|date| date.to_string())
+                .unwrap_or_default()
+        ));
+    }
+    if name.doc_type.is_some() && name.doc_type != content.doc_type {
+        differences.push(format!(
+            "document type {} in the content",
+            content.doc_type.as_deref().unwrap_or_default()
+        ));
+    }
+    //the name may end with the counter added for duplicate names
+    if let (Some(name_asset), Some(asset)) = (&name.asset, &content.asset) {
+        let generated = asset.replace('_', &separator.to_string());
+        if !matches_generated(name_asset, &generated, separator) {
+            differences.push(format!("asset {asset:?} in the content"));
+        }
+    }
+    if name.isin.is_some() && name.isin != content.isin {
+        differences.push(format!(
+            "ISIN {} in the content",
+            content.isin.as_deref().unwrap_or_default()
+        ));
+    }
+    if name.amount.is_some() && name.amount != content.amount {
+        differences.push(format!(
+            "amount {} in the content",
+            content.amount.as_deref().unwrap_or_default()
+        ));
+    }
+    if name.quantity.is_some() && name.quantity != content.quantity {
+        differences.push(format!(
+            "quantity {} in the content",
+            content.quantity.as_deref().unwrap_or_default()
+        ));
+    }
+    if name.tax_year.is_some() && name.tax_year != content.tax_year {
+        differences.push(format!(
+            "tax year {} in the content",
+            content.tax_year.as_deref().unwrap_or_default()
+        ));
+    }
+    if name.period_start.is_some() && name.period_start != content.period_start
+        || name.period_end.is_some() && name.period_end != content.period_end
+    {
+        differences.push("period in the content differs".to_string());
+    }
+    if differences.is_empty() {
+        differences.push("name differs from the content".to_string());
+    }
+
+    differences
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use chrono::{Days, Months, NaiveDate};
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    filter::parse_doc_type,
+    i18n::Lang,
+    naming::{FolderLayout, Separator, Template},
+    parser::DateSource,
+    placeholder::Placeholders,
+};
+
+/// Settings read from the TOML config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Directory processed when no path is given on the command line.
+    pub folder: Option<PathBuf>,
+    /// Directory the `archive` command moves the documents into, used unless `--to` is given.
+    pub archive_root: Option<PathBuf>,
+    /// Directories below the archive root, used unless `--layout` is given.
+    pub folder_layout: Option<FolderLayout>,
+    /// Directory files that cannot be parsed are moved into, used unless `--unparsed-dir` is
+    /// given.
+    pub unparsed_dir: Option<PathBuf>,
+    /// Naming template used unless `--template` is given.
+    pub template: Option<Template>,
+    /// Character between the parts of the names, used unless `--separator` is given.
+    pub separator: Option<Separator>,
+    /// Regex of filenames that are renamed already, used unless `--renamed-pattern` is given.
+    pub renamed_pattern: Option<String>,
+    /// Regexes of filenames organized by another tool, added to `--organized-pattern`.
+    #[serde(default)]
+    pub organized_patterns: Vec<String>,
+    /// Order in which the dates of a document are preferred, used unless `--date-preference` is
+    /// given.
+    pub date_preference: Option<Vec<DateSource>>,
+    /// Labels used in the filenames instead of the document types, by document type.
+    #[serde(default)]
+    pub doc_types: BTreeMap<String, String>,
+    /// Short forms of long asset names used in the filenames.
+    #[serde(default)]
+    pub abbreviations: BTreeMap<String, String>,
+    /// Maximum number of characters of the asset name, used unless `--max-asset-len` is given.
+    pub max_asset_length: Option<usize>,
+    /// Maximum number of characters of the paths of renamed files, used unless `--max-path-len`
+    /// is given.
+    pub max_path_length: Option<usize>,
+    /// Remove boilerplate from asset names, like `--strip-boilerplate`.
+    #[serde(default)]
+    pub strip_boilerplate: bool,
+    /// Further words or phrases removed from asset names with `strip_boilerplate`.
+    #[serde(default)]
+    pub stop_tokens: Vec<String>,
+    /// Retries of a rename blocked by another program, used unless `--rename-retries` is given.
+    pub rename_retries: Option<u32>,
+    /// Milliseconds before the first retry of a blocked rename, doubled for every further one.
+    pub rename_retry_delay_ms: Option<u64>,
+    /// Retries of reads and renames failing with a network error, used unless `--io-retries` is
+    /// given.
+    pub io_retries: Option<u32>,
+    /// Milliseconds before the first retry after a network error, doubled for every further one.
+    pub io_retry_delay_ms: Option<u64>,
+    /// Set the title and subject of the renamed PDFs, like `--set-pdf-info`.
+    #[serde(default)]
+    pub set_pdf_info: bool,
+    /// Make the renamed PDFs findable in Spotlight, like `--spotlight`.
+    #[serde(default)]
+    pub spotlight: bool,
+    /// Remove the quarantine attribute of the renamed PDFs, like `--clear-quarantine`.
+    #[serde(default)]
+    pub clear_quarantine: bool,
+    /// Record where the renamed PDFs came from in extended attributes, on by default.
+    pub provenance_xattrs: Option<bool>,
+    /// What to do with online-only files of sync clients, used unless `--placeholders` is given.
+    pub placeholders: Option<Placeholders>,
+    /// Base URL of the OpenTelemetry collector the traces are sent to, used unless
+    /// `--otlp-endpoint` is given.
+    pub otlp_endpoint: Option<String>,
+    /// Language of the messages of a run, `en` or `de`, used unless `--lang` is given.
+    pub lang: Option<Lang>,
+    /// Where to send the run summary.
+    pub webhook: Option<WebhookConfig>,
+    /// Whom to e-mail the run summary.
+    pub email: Option<EmailConfig>,
+    /// External commands run while renaming.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// How long documents are kept by the `prune` command, by document type.
+    #[serde(default)]
+    pub retention: BTreeMap<String, Retention>,
+    /// Accounts of the entries written by `export ledger`.
+    #[serde(default)]
+    pub accounts: AccountsConfig,
+}
+
+//the document types of `[doc_types]` spelled like the parser does, the labels usable in names
+fn check_doc_types(
+    doc_types: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    doc_types
+        .into_iter()
+        .map(|(doc_type, label)| {
+            let doc_type = parse_doc_type(&doc_type)
+                .map_err(|err| format!("[doc_types] {doc_type:?}: {err}"))?;
+            if label.trim().is_empty() || label.contains(['/', '\\']) {
+                return Err(format!(
+                    "[doc_types] {doc_type:?}: label {label:?} is empty or contains '/' or '\\'"
+                ));
+            }
+            Ok((doc_type, label))
+        })
+        .collect()
+}
+
+/// `[webhook]` section of the config file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// URL the summary is POSTed to as JSON.
+    pub url: String,
+    /// Also send the outcome of every file, not only the counts.
+    #[serde(default)]
+    pub include_files: bool,
+}
+
+/// `[email]` section of the config file. The SMTP credentials are read from the environment
+/// variables `TR_PDF_RENAME_SMTP_USERNAME` and `TR_PDF_RENAME_SMTP_PASSWORD`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+pub struct EmailConfig {
+    /// Host name of the SMTP server.
+    pub server: String,
+    /// Port of the SMTP server, the default depends on `tls`.
+    pub port: Option<u16>,
+    /// How the connection is encrypted.
+    #[serde(default)]
+    pub tls: SmtpTls,
+    /// Sender address.
+    pub from: String,
+    /// Recipient addresses.
+    pub to: Vec<String>,
+}
+
+/// Encryption of the SMTP connection.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade the connection with STARTTLS, port 587.
+    #[default]
+    Starttls,
+    /// Encrypted from the start, port 465.
+    Tls,
+    /// Unencrypted, port 25, only for servers on the local machine.
+    None,
+}
+
+/// `[hooks]` section of the config file. Commands are given as the program followed by its
+/// arguments, e.g. `["backup.sh", "--quiet"]`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Command deciding about the new name of every file before it is renamed.
+    pub pre_rename: Option<Vec<String>>,
+    /// Command run for every renamed file.
+    pub post_rename: Option<Vec<String>>,
+    /// Seconds a hook may run before it is killed, 30 by default.
+    pub timeout: Option<u64>,
+}
+
+impl HooksConfig {
+    /// How long a hook may run before it is killed.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(30))
+    }
+}
+
+/// `[accounts]` section of the config file, the accounts of the bookkeeping entries. Missing
+/// accounts keep their default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountsConfig {
+    /// Cash account of the broker.
+    pub cash: String,
+    /// Account holding the securities, in units of their ISIN.
+    pub securities: String,
+    /// Income account of dividends.
+    pub dividends: String,
+    /// Income account of interest.
+    pub interest: String,
+    /// Income account of the Saveback credited by Trade Republic.
+    pub saveback: String,
+    /// Income account of realized gains.
+    pub gains: String,
+    /// Expense account of fees.
+    pub fees: String,
+    /// Expense account of the taxes withheld.
+    pub taxes: String,
+}
+
+impl Default for AccountsConfig {
+    fn default() -> AccountsConfig {
+        AccountsConfig {
+            cash: "Assets:TradeRepublic:Cash".to_string(),
+            securities: "Assets:TradeRepublic:Depot".to_string(),
+            dividends: "Income:Dividends".to_string(),
+            interest: "Income:Interest".to_string(),
+            saveback: "Income:Saveback".to_string(),
+            gains: "Income:CapitalGains".to_string(),
+            fees: "Expenses:Fees".to_string(),
+            taxes: "Expenses:Taxes".to_string(),
+        }
+    }
+}
+
+/// How long a document is kept after its date, like `10y`, `18m`, `6w` or `90d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    Months(u32),
+    Days(u64),
+}
+
+impl Retention {
+    /// The first date of documents still kept on `today`, None if every date is kept.
+    pub fn cutoff(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            Retention::Months(months) => today.checked_sub_months(Months::new(months)),
+            Retention::Days(days) => today.checked_sub_days(Days::new(days)),
+        }
+    }
+}
+
+impl FromStr for Retention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Retention, String> {
+        let invalid = || format!("{s:?} is not a retention period like 10y, 18m, 6w or 90d");
+
+        let s = s.trim();
+        let (number, unit) = s.split_at(s.len() - s.chars().last().map_or(0, char::len_utf8));
+        let number: u32 = number.parse().map_err(|_| invalid())?;
+        match unit {
+            "y" => number.checked_mul(12).map(Retention::Months),
+            "m" => Some(Retention::Months(number)),
+            "w" => Some(Retention::Days(u64::from(number) * 7)),
+            "d" => Some(Retention::Days(u64::from(number))),
+            _ => None,
+        }
+        .ok_or_else(invalid)
+    }
+}
+
+impl<'de> Deserialize<'de> for Retention {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Retention, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Retention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Retention::Months(months) if months % 12 == 0 => write!(f, "{}y", months / 12),
+            Retention::Months(months) => write!(f, "{months}m"),
+            Retention::Days(days) => write!(f, "{days}d"),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file at `path`, or the one at the [default path](Config::default_path)
+    /// if it exists. Returns the defaults if there is no config file.
+    pub fn load(path: Option<&Path>) -> io::Result<Config> {
+        let Some(path) = Config::file(path) else {
+            return Ok(Config::default());
+        };
+
+        let content = fs::read_to_string(&path)?;
+        let invalid = |err: String| {
+            io::Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display()))
+        };
+        let mut config: Config =
+            toml::from_str(&content).map_err(|err| invalid(err.to_string()))?;
+        config.doc_types = check_doc_types(config.doc_types).map_err(invalid)?;
+        config.retention = config
+            .retention
+            .into_iter()
+            .map(|(doc_type, retention)| {
+                parse_doc_type(&doc_type)
+                    .map(|doc_type| (doc_type, retention))
+                    .map_err(|err| format!("[retention] {doc_type:?}: {err}"))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(invalid)?;
+        Ok(config)
+    }
+
+    /// The config file [read](Config::load) with `path`, None if there is none.
+    pub fn file(path: Option<&Path>) -> Option<PathBuf> {
+        path.map(Path::to_path_buf)
+            .or(Config::default_path().filter(|path| path.exists()))
+    }
+
+    /// The config file read without `--config`: `tr_pdf_rename/config.toml` in the user's config
+    /// directory (`$XDG_CONFIG_HOME` or `~/.config` on Linux, `%APPDATA%` on Windows).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tr_pdf_rename").join("config.toml"))
+    }
+
+    /// The directory parser plugins are loaded from without `--plugins`: `tr_pdf_rename/plugins`
+    /// in the user's config directory.
+    pub fn default_plugin_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tr_pdf_rename").join("plugins"))
+    }
+}
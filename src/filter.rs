@@ -0,0 +1,363 @@
+use std::{
+    fs::Metadata,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    parser::{is_valid_isin, known_doc_types, PdfData},
+    reason::Reason,
+};
+
+/// Conditions a parsed document has to meet to be renamed.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Only documents of these types, all types if empty.
+    pub only_types: Vec<String>,
+    /// Never documents of these types.
+    pub skip_types: Vec<String>,
+    /// Only documents about these securities, all documents if empty.
+    pub isins: Vec<String>,
+    /// Only documents whose total amount is at least this many cents, ignoring the sign.
+    pub min_cents: Option<i64>,
+    /// Only documents whose total amount is at most this many cents, ignoring the sign.
+    pub max_cents: Option<i64>,
+    /// Only files of at most this many bytes, checked before they are read. `0` means unlimited.
+    pub max_size: Option<u64>,
+    /// Only files modified after this time, checked before they are read.
+    pub modified_after: Option<SystemTime>,
+}
+
+impl Filter {
+    /// Returns why a file with `metadata` is not even read, `None` if it is.
+    pub fn rejects_file(&self, metadata: &Metadata) -> Option<(Reason, String)> {
+        let size = metadata.len();
+        if let Some(max_size) = self
+            .max_size
+            .filter(|&max_size| max_size > 0 && size > max_size)
+        {
+            return Some((
+                Reason::TooLarge,
+                format!("{size} bytes exceed the maximum size of {max_size}"),
+            ));
+        }
+        if let Some(modified_after) = self.modified_after {
+            //without a modification time the file cannot be too old
+            if metadata
+                .modified()
+                .is_ok_and(|modified| modified <= modified_after)
+            {
+                return Some((
+                    Reason::NotModifiedRecently,
+                    "not modified recently".to_string(),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Returns why `data` does not pass the filter, `None` if it does.
+    pub fn rejects(&self, data: &PdfData) -> Option<(Reason, String)> {
+        if !self.only_types.is_empty() && !self.only_types.contains(&data.doc_type) {
+            return Some((
+                Reason::TypeNotSelected,
+                format!("document type {} not selected", data.doc_type),
+            ));
+        }
+        if self.skip_types.contains(&data.doc_type) {
+            return Some((
+                Reason::TypeNotSelected,
+                format!("document type {} excluded", data.doc_type),
+            ));
+        }
+        if !self.isins.is_empty() {
+            let reason = match &data.isin {
+                Some(isin) if self.isins.contains(isin) => None,
+                Some(isin) => Some(format!("ISIN {isin} not selected")),
+                None => Some("document has no ISIN".to_string()),
+            };
+            if let Some(reason) = reason {
+                return Some((Reason::IsinNotSelected, reason));
+            }
+        }
+        if self.min_cents.is_some() || self.max_cents.is_some() {
+            let Some(amount) = &data.amount else {
+                return Some((
+                    Reason::AmountOutOfRange,
+                    "document has no total amount".to_string(),
+                ));
+            };
+            let cents = amount.cents.abs();
+            if self.min_cents.is_some_and(|min| cents < min) {
+                return Some((
+                    Reason::AmountOutOfRange,
+                    format!("amount {amount} below the minimum"),
+                ));
+            }
+            if self.max_cents.is_some_and(|max| cents > max) {
+                return Some((
+                    Reason::AmountOutOfRange,
+                    format!("amount {amount} above the maximum"),
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Parses a document type given on the command line, ignoring case.
+pub fn parse_doc_type(arg: &str) -> Result<String, String> {
+    known_doc_types()
+        .find(|doc_type| doc_type.eq_ignore_ascii_case(arg))
+        .map(str::to_string)
+        .ok_or_else(|| {
+            let known: Vec<&str> = known_doc_types().collect();
+            format!("unknown document type, known types: {}", known.join(", "))
+        })
+}
+
+/// Parses an ISIN given on the command line, ignoring case.
+pub fn parse_isin(arg: &str) -> Result<String, String> {
+    let isin = arg.trim().to_ascii_uppercase();
+    if is_valid_isin(&isin) {
+        Ok(isin)
+    } else {
+        Err("not a valid ISIN".to_string())
+    }
+}
+
+/// Parses an amount given on the command line like `100`, `99.90` or `99,90` into cents.
+pub fn parse_cents(arg: &str) -> Result<i64, String> {
+    let invalid = || format!("{arg:?} is not an amount like 100 or 99.90");
+
+    let arg = arg.trim();
+    let (units, fraction) = arg.split_once(['.', ',']).unwrap_or((arg, ""));
+    if units.is_empty()
+        || fraction.len() > 2
+        || !units.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let units: i64 = units.parse().map_err(|_| invalid())?;
+    let cents: i64 = format!("{fraction:0<2}").parse().map_err(|_| invalid())?;
+    units
+        .checked_mul(100)
+        .and_then(|units| units.checked_add(cents))
+        .ok_or_else(invalid)
+}
+
+/// Parses a file size like `300M`, `1G`, `512K` or `1048576` into bytes, `0` means unlimited.
+pub fn parse_size(arg: &str) -> Result<u64, String> {
+    let invalid = || format!("{arg:?} is not a size like 300M, 1G or 512K");
+
+    let arg = arg.trim();
+    let (number, factor) = match arg.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&arg[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&arg[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&arg[..i], 1 << 30),
+        _ => (arg, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(factor))
+        .ok_or_else(invalid)
+}
+
+/// Parses an age like `30m`, `12h`, `7d` or `2w` (seconds without a unit).
+pub fn parse_age(arg: &str) -> Result<Duration, String> {
+    let invalid = || format!("{arg:?} is not an age like 30m, 12h, 7d or 2w");
+
+    let arg = arg.trim();
+    let (number, factor) = match arg.char_indices().last() {
+        Some((i, 's')) => (&arg[..i], 1),
+        Some((i, 'm')) => (&arg[..i], 60),
+        Some((i, 'h')) => (&arg[..i], 60 * 60),
+        Some((i, 'd')) => (&arg[..i], 24 * 60 * 60),
+        Some((i, 'w')) => (&arg[..i], 7 * 24 * 60 * 60),
+        _ => (arg, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(factor))
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::parser::{parse_pdf_data, Amount};
+
+    //a parsed dividend of Apple with the total amount `cents`
+    fn dividend(cents: Option<i64>) -> PdfData {
+        PdfData {
+            amount: cents.map(|cents| Amount {
+                cents,
+                currency: "EUR".to_string(),
+            }),
+            ..parse_pdf_data(
+                "Trade Republic\nDATUM 15.06.2024\nDIVIDENDE\nApple Inc.\nISIN: US0378331005",
+                None,
+            )
+            .unwrap()
+        }
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse_cents("100"), Ok(10000));
+        assert_eq!(parse_cents("99.90"), Ok(9990));
+        assert_eq!(parse_cents("99,90"), Ok(9990));
+        assert_eq!(parse_cents("99,9"), Ok(9990));
+        assert_eq!(parse_cents(" 0.05 "), Ok(5));
+        assert_eq!(parse_cents("100."), Ok(10000));
+        for invalid in [
+            "",
+            ".50",
+            "-5",
+            "+5",
+            "1.234",
+            "1.000,00",
+            "1e3",
+            "12 EUR",
+            "99999999999999999999",
+        ] {
+            assert!(parse_cents(invalid).is_err(), "{invalid}");
+        }
+        //the cents would overflow
+        assert!(parse_cents(&(i64::MAX / 100 + 1).to_string()).is_err());
+        assert_eq!(
+            parse_cents(&(i64::MAX / 100).to_string()),
+            Ok(i64::MAX / 100 * 100)
+        );
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("1048576"), Ok(1 << 20));
+        assert_eq!(parse_size("512K"), Ok(512 << 10));
+        assert_eq!(parse_size("300m"), Ok(300 << 20));
+        assert_eq!(parse_size(" 1G "), Ok(1 << 30));
+        assert_eq!(parse_size("0"), Ok(0));
+        for invalid in ["", "M", "1T", "1.5G", "-1", "99999999999G"] {
+            assert!(parse_size(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn ages() {
+        assert_eq!(parse_age("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(parse_age("7d"), Ok(Duration::from_secs(7 * 24 * 60 * 60)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * 24 * 60 * 60)));
+        for invalid in ["", "d", "1y", "1.5d", "-1d", "99999999999999999w"] {
+            assert!(parse_age(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn file_size_and_age() {
+        let metadata = fs::metadata(env::current_exe().unwrap()).unwrap();
+        let filter = |max_size, modified_after| Filter {
+            max_size,
+            modified_after,
+            ..Filter::default()
+        };
+
+        assert_eq!(filter(None, None).rejects_file(&metadata), None);
+        //0 means unlimited
+        assert_eq!(filter(Some(0), None).rejects_file(&metadata), None);
+        assert_eq!(
+            filter(Some(metadata.len()), None).rejects_file(&metadata),
+            None
+        );
+        assert!(matches!(
+            filter(Some(metadata.len() - 1), None).rejects_file(&metadata),
+            Some((Reason::TooLarge, _))
+        ));
+
+        assert_eq!(
+            filter(None, Some(SystemTime::UNIX_EPOCH)).rejects_file(&metadata),
+            None
+        );
+        let future = SystemTime::now() + Duration::from_secs(60 * 60);
+        assert!(matches!(
+            filter(None, Some(future)).rejects_file(&metadata),
+            Some((Reason::NotModifiedRecently, _))
+        ));
+    }
+
+    #[test]
+    fn amount_bounds_ignore_the_sign() {
+        let filter = Filter {
+            min_cents: Some(1000),
+            max_cents: Some(5000),
+            ..Filter::default()
+        };
+        for cents in [1000, 5000, -1000, -5000, 2500] {
+            assert_eq!(filter.rejects(&dividend(Some(cents))), None, "{cents}");
+        }
+        for cents in [999, 5001, -999, -5001, 0] {
+            assert!(
+                matches!(
+                    filter.rejects(&dividend(Some(cents))),
+                    Some((Reason::AmountOutOfRange, _))
+                ),
+                "{cents}"
+            );
+        }
+        assert!(matches!(
+            filter.rejects(&dividend(None)),
+            Some((Reason::AmountOutOfRange, _))
+        ));
+        //without bounds the amount does not matter
+        assert_eq!(Filter::default().rejects(&dividend(None)), None);
+    }
+
+    #[test]
+    fn types_and_isins() {
+        let data = dividend(Some(217));
+        let filter = |only: &[&str], skip: &[&str], isins: &[&str]| Filter {
+            only_types: only.iter().map(|s| s.to_string()).collect(),
+            skip_types: skip.iter().map(|s| s.to_string()).collect(),
+            isins: isins.iter().map(|s| s.to_string()).collect(),
+            ..Filter::default()
+        };
+
+        assert_eq!(filter(&["Dividende"], &[], &[]).rejects(&data), None);
+        assert!(matches!(
+            filter(&["Zinsen"], &[], &[]).rejects(&data),
+            Some((Reason::TypeNotSelected, _))
+        ));
+        assert!(matches!(
+            filter(&[], &["Dividende"], &[]).rejects(&data),
+            Some((Reason::TypeNotSelected, _))
+        ));
+        assert_eq!(filter(&[], &[], &["US0378331005"]).rejects(&data), None);
+        assert!(matches!(
+            filter(&[], &[], &["IE00B4L5Y983"]).rejects(&data),
+            Some((Reason::IsinNotSelected, _))
+        ));
+        let without_isin = PdfData { isin: None, ..data };
+        assert!(matches!(
+            filter(&[], &[], &["US0378331005"]).rejects(&without_isin),
+            Some((Reason::IsinNotSelected, _))
+        ));
+    }
+
+    #[test]
+    fn doc_types_and_isins_from_the_command_line() {
+        assert_eq!(parse_doc_type("dividende"), Ok("Dividende".to_string()));
+        assert!(parse_doc_type("Rechnung").is_err());
+        assert_eq!(parse_isin(" us0378331005 "), Ok("US0378331005".to_string()));
+        //wrong check digit
+        assert!(parse_isin("US0378331006").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    path::Path,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::PdfData;
+
+//how often a running hook is checked for having finished
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// What the pre-rename hook decided about a file.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Decision {
+    /// Use the proposed name.
+    Accept,
+    /// Use another name instead.
+    Rename { name: String },
+    /// Leave the file as it is.
+    Skip {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+//what the pre-rename hook gets on stdin
+#[derive(Serialize)]
+struct HookInput<'a> {
+    file: &'a Path,
+    proposed_name: &'a str,
+    date: String,
+    doc_type: &'a str,
+    asset: &'a str,
+    isin: Option<&'a str>,
+    amount: Option<String>,
+    quantity: Option<&'a str>,
+    tax_year: Option<i32>,
+}
+
+/// Runs the pre-rename `command` with the file, the proposed name and the parsed fields as JSON
+/// on stdin and reads its [`Decision`] as JSON from stdout. Empty output accepts the proposed
+/// name. A hook running longer than `timeout` is killed and fails with [`ErrorKind::TimedOut`].
+pub fn pre_rename(
+    command: &[String],
+    timeout: Duration,
+    path: &Path,
+    proposed_name: &str,
+    data: &PdfData,
+) -> io::Result<Decision> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "pre_rename hook is empty",
+        ));
+    };
+
+    let input = serde_json::to_vec(&HookInput {
+        file: path,
+        proposed_name,
+        date: data.date.format("%Y-%m-%d").to_string(),
+        doc_type: &data.doc_type,
+        asset: &data.asset,
+        isin: data.isin.as_deref(),
+        amount: data.amount.as_ref().map(ToString::to_string),
+        quantity: data.quantity.as_deref(),
+        tax_year: data.tax_year,
+    })
+    .map_err(io::Error::other)?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    //write and read from other threads so a hook printing before reading all input cannot
+    //deadlock, and a hung one can be killed
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+    let status = wait(&mut child, program, timeout)?;
+    //a hook that decides without reading its input closes the pipe early, that is fine
+    if let Ok(Err(err)) = writer.join() {
+        if err.kind() != ErrorKind::BrokenPipe {
+            return Err(err);
+        }
+    }
+    let output = reader
+        .join()
+        .map_err(|_| io::Error::other("reading the decision failed"))??;
+
+    if !status.success() {
+        return Err(io::Error::other(format!("{program} exited with {status}")));
+    }
+
+    let stdout = String::from_utf8_lossy(&output);
+    if stdout.trim().is_empty() {
+        return Ok(Decision::Accept);
+    }
+    serde_json::from_str(&stdout).map_err(|err| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid decision from {program}: {err}"),
+        )
+    })
+}
+
+/// Runs the post-rename `command` for a renamed file. The old and new path are appended as
+/// arguments, the parsed fields are passed as `TR_*` environment variables. A hook running longer
+/// than `timeout` is killed and fails with [`ErrorKind::TimedOut`].
+pub fn post_rename(
+    command: &[String],
+    timeout: Duration,
+    old: &Path,
+    new: &Path,
+    data: &PdfData,
+) -> io::Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "post_rename hook is empty",
+        ));
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(old)
+        .arg(new)
+        .env("TR_OLD_PATH", old)
+        .env("TR_NEW_PATH", new)
+        .envs(field_vars(data))
+        .spawn()?;
+    let status = wait(&mut child, program, timeout)?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{program} exited with {status}")))
+    }
+}
+
+//wait for `child` to exit, killing it after `timeout`
+fn wait(child: &mut Child, program: &str, timeout: Duration) -> io::Result<ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            //it may have exited in the meantime, then there is nothing to kill
+            let _ = child.kill();
+            child.wait()?;
+            return Err(io::Error::new(
+                ErrorKind::TimedOut,
+                format!(
+                    "{program} did not finish within {} s and was killed",
+                    timeout.as_secs()
+                ),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+//the parsed fields as environment variables, empty if the document has no value
+fn field_vars(data: &PdfData) -> [(&'static str, String); 7] {
+    [
+        ("TR_DATE", data.date.format("%Y-%m-%d").to_string()),
+        ("TR_TYPE", data.doc_type.clone()),
+        ("TR_ASSET", data.asset.clone()),
+        ("TR_ISIN", data.isin.clone().unwrap_or_default()),
+        (
+            "TR_AMOUNT",
+            data.amount
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        ),
+        ("TR_QUANTITY", data.quantity.clone().unwrap_or_default()),
+        (
+            "TR_TAX_YEAR",
+            data.tax_year
+                .map(|tax_year| tax_year.to_string())
+                .unwrap_or_default(),
+        ),
+    ]
+}
@@ -0,0 +1,27 @@
+# Meldungen eines Laufs, die Dateinamen sind bereits in Anführungszeichen.
+
+path-or-file = Pfad oder Datei: { $path }
+watching = Überwache { $path } auf neue Dateien, Strg+C zum Beenden
+sampling = Stichprobe von { $count } aus { $total } PDF-Datei(en)
+sample-summary = { $renamed } würden umbenannt, { $skipped } übersprungen, { $filtered } ausgefiltert, { $already } bereits benannt
+check-summary = { $count } Datei(en) würden umbenannt
+not-renamed = Nicht umbenannt: { $reasons }
+
+renamed = { $from } umbenannt in { $to }
+archived = { $from } archiviert als { $to }
+optimized = Optimiert von { $before } auf { $after }
+low-confidence-date = Datum { $date } entnommen aus: { $source }, unsicher, bitte prüfen
+asset-shortened = Name { $asset } von { $file } auf { $shortened } gekürzt, damit der Pfad höchstens { $max } Zeichen lang ist
+
+filtered = Datei { $file } ausgefiltert ({ $code }): { $reason }
+skipped = Datei { $file } übersprungen ({ $code }): { $reason }
+unchanged = Datei { $file } hat bereits den richtigen Namen.
+in-archive = Datei { $file } übersprungen, bereits im Archiv als { $existing }.
+already-renamed = Datei { $file } ignoriert, sie scheint bereits umbenannt zu sein.
+organized = Datei { $file } ignoriert, sie passt auf das Muster { $pattern }.
+directory-visited = Verzeichnis { $dir } übersprungen: es führt zu { $target }, das bereits verarbeitet wird
+
+thumbnail-failed = Vorschau von { $file } fehlgeschlagen: { $error }
+attributes-failed = Attribute von { $file } nicht gesetzt: { $error }
+post-rename-failed = Post-rename-Hook für { $file } fehlgeschlagen: { $error }
+state-ignored = Statusdatei von { $dir } ignoriert: { $error }
@@ -0,0 +1,27 @@
+# Messages of a rename run, the file names are quoted already.
+
+path-or-file = path or file: { $path }
+watching = Watching { $path } for new files, press Ctrl+C to stop
+sampling = Sampling { $count } of { $total } PDF file(s)
+sample-summary = { $renamed } would be renamed, { $skipped } skipped, { $filtered } filtered out, { $already } already named
+check-summary = { $count } file(s) would be renamed
+not-renamed = Not renamed: { $reasons }
+
+renamed = Renamed { $from } to { $to }
+archived = Archived { $from } to { $to }
+optimized = Optimized from { $before } to { $after }
+low-confidence-date = Date { $date } taken from the { $source }, low confidence, please check
+asset-shortened = Asset { $asset } of { $file } shortened to { $shortened } to keep the path within { $max } characters
+
+filtered = File { $file } filtered out ({ $code }): { $reason }
+skipped = File { $file } skipped ({ $code }): { $reason }
+unchanged = File { $file } already has the right name.
+in-archive = File { $file } skipped, already in the archive as { $existing }.
+already-renamed = File { $file } ignored as it seems to have been renamed already.
+organized = File { $file } ignored as it matches the pattern { $pattern }.
+directory-visited = Directory { $dir } skipped: it leads to { $target }, which is processed already
+
+thumbnail-failed = Thumbnail of { $file } failed: { $error }
+attributes-failed = Attributes of { $file } not set: { $error }
+post-rename-failed = Post-rename hook for { $file } failed: { $error }
+state-ignored = State file of { $dir } ignored: { $error }
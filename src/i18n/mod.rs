@@ -0,0 +1,109 @@
+use std::{
+    env,
+    str::FromStr,
+    sync::{LazyLock, OnceLock},
+};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use serde::{Deserialize, Deserializer};
+use unic_langid::{langid, LanguageIdentifier};
+
+/// Language of the messages of a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    English,
+    German,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Lang, String> {
+        match name {
+            "en" => Ok(Lang::English),
+            "de" => Ok(Lang::German),
+            _ => Err(format!(
+                "unknown language {name:?}, known languages: en, de"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Lang {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Lang, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+static ENGLISH: LazyLock<FluentBundle<FluentResource>> =
+    LazyLock::new(|| bundle(langid!("en"), include_str!("en.ftl")));
+static GERMAN: LazyLock<FluentBundle<FluentResource>> =
+    LazyLock::new(|| bundle(langid!("de"), include_str!("de.ftl")));
+
+/// Uses `lang` for the messages, or the language of the user's locale without it.
+pub fn init(lang: Option<Lang>) {
+    LANG.get_or_init(|| lang.unwrap_or_else(detect));
+}
+
+//LC_ALL, LC_MESSAGES and LANG take precedence over the system setting, like for gettext
+fn detect() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+        .or_else(sys_locale::get_locale);
+    match locale {
+        Some(locale) if locale.to_ascii_lowercase().starts_with("de") => Lang::German,
+        _ => Lang::English,
+    }
+}
+
+fn bundle(lang: LanguageIdentifier, source: &str) -> FluentBundle<FluentResource> {
+    let resource = FluentResource::try_new(source.to_string()).expect("messages are valid Fluent");
+    let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+    //terminals don't need the marks isolating the arguments in bidirectional text
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("message ids are unique");
+    bundle
+}
+
+/// The message `id` with `args` in the language of the run, in English if it is not translated.
+pub fn message(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let bundles = match LANG.get_or_init(detect) {
+        Lang::English => [&*ENGLISH, &*ENGLISH],
+        Lang::German => [&*GERMAN, &*ENGLISH],
+    };
+    let args: FluentArgs = args.iter().cloned().collect();
+
+    bundles
+        .into_iter()
+        .find_map(|bundle| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = Vec::new();
+            Some(
+                bundle
+                    .format_pattern(pattern, Some(&args), &mut errors)
+                    .into_owned(),
+            )
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// The message with the id given first in the language of the run, with the arguments given as
+/// `name = value`.
+macro_rules! tr {
+    ($id:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::message(
+            $id,
+            &[$((stringify!($name), fluent_bundle::FluentValue::from($value))),*],
+        )
+    };
+}
+
+pub(crate) use tr;
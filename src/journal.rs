@@ -0,0 +1,56 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+//name of the journal kept in the archive root, one JSON object per line
+pub const JOURNAL_FILE_NAME: &str = ".tr_pdf_rename_journal.jsonl";
+
+/// A file moved into the archive.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// When the file was moved.
+    pub moved_at: DateTime<Utc>,
+    /// Absolute path of the file before it was moved.
+    pub from: PathBuf,
+    /// Path of the file in the archive, relative to the archive root.
+    pub to: PathBuf,
+    /// [Scheme](crate::naming::Template::scheme) of the template the file was named with,
+    /// missing in entries of older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Version of the parser rules the file was named by, missing in entries of older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+}
+
+/// Appends `entry` to the journal of the archive at `root`.
+pub fn append(root: &Path, entry: &Entry) -> io::Result<()> {
+    let mut line = serde_json::to_vec(entry).map_err(io::Error::other)?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(root.join(JOURNAL_FILE_NAME))?
+        .write_all(&line)
+}
+
+/// The entries of the journal of the archive at `root`, oldest first, none if it has no journal.
+pub fn read(root: &Path) -> io::Result<Vec<Entry>> {
+    let content = match fs::read_to_string(root.join(JOURNAL_FILE_NAME)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+        })
+        .collect()
+}
@@ -0,0 +1,77 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    sync::Arc,
+};
+
+use ignore::gitignore::Gitignore;
+use serde::Deserialize;
+
+use crate::naming::Template;
+
+/// Name of the file overriding settings for a directory and its subdirectories.
+pub const LOCAL_CONFIG_FILE_NAME: &str = ".trrename.toml";
+/// Name of the file with gitignore-style patterns of files and directories to leave alone.
+pub const IGNORE_FILE_NAME: &str = ".trignore";
+
+/// Settings of a `.trrename.toml`, they apply to its directory and all subdirectories unless
+/// overridden there again.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LocalConfig {
+    /// Naming template used instead of the one given on the command line.
+    pub template: Option<Template>,
+}
+
+impl LocalConfig {
+    /// Reads the `.trrename.toml` of `dir`, `None` if it has none.
+    pub fn load(dir: &Path) -> io::Result<Option<LocalConfig>> {
+        let path = dir.join(LOCAL_CONFIG_FILE_NAME);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        toml::from_str(&content).map(Some).map_err(|err| {
+            io::Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display()))
+        })
+    }
+}
+
+/// The `.trignore` files of a directory and its parents, the deepest file decides.
+#[derive(Debug, Clone, Default)]
+pub struct Ignores {
+    files: Vec<Arc<Gitignore>>,
+}
+
+impl Ignores {
+    /// Adds the `.trignore` of `dir`, if it has one.
+    pub fn with_dir(&self, dir: &Path) -> io::Result<Ignores> {
+        let path = dir.join(IGNORE_FILE_NAME);
+        if !path.is_file() {
+            return Ok(self.clone());
+        }
+
+        let (gitignore, err) = Gitignore::new(&path);
+        if let Some(err) = err {
+            return Err(io::Error::new(ErrorKind::InvalidData, err.to_string()));
+        }
+
+        let mut ignores = self.clone();
+        ignores.files.push(Arc::new(gitignore));
+        Ok(ignores)
+    }
+
+    /// Checks if `path` matches a pattern of the `.trignore` files that is not negated by a
+    /// later `!pattern`.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.files
+            .iter()
+            .rev()
+            .map(|gitignore| gitignore.matched(path, is_dir))
+            .find(|matched| !matched.is_none())
+            .is_some_and(|matched| matched.is_ignore())
+    }
+}
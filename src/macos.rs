@@ -0,0 +1,92 @@
+use std::{io, path::Path};
+
+use crate::xattr;
+
+/// Whether this platform has the quarantine attribute and Spotlight.
+pub const MACOS: bool = cfg!(target_os = "macos");
+
+//set by browsers and mail clients on downloads, Gatekeeper checks files that have it
+const QUARANTINE: &str = "com.apple.quarantine";
+//attributes Spotlight indexes, as binary property lists
+const TITLE: &str = "com.apple.metadata:kMDItemTitle";
+const KEYWORDS: &str = "com.apple.metadata:kMDItemKeywords";
+
+/// Gives `to` the quarantine attribute of `from`, which a rewritten copy would lose otherwise.
+pub fn copy_quarantine(from: &Path, to: &Path) -> io::Result<()> {
+    match xattr::get(from, QUARANTINE)? {
+        Some(value) => xattr::set(to, QUARANTINE, &value),
+        None => Ok(()),
+    }
+}
+
+/// Removes the quarantine attribute of `path`, so it opens without the downloaded file warning.
+pub fn clear_quarantine(path: &Path) -> io::Result<()> {
+    xattr::remove(path, QUARANTINE)
+}
+
+/// Sets the title and keywords Spotlight shows and finds `path` by.
+pub fn set_spotlight(path: &Path, title: &str, keywords: &[&str]) -> io::Result<()> {
+    xattr::set(path, TITLE, &plist(&[title], false))?;
+    xattr::set(path, KEYWORDS, &plist(keywords, true))
+}
+
+//a binary property list of one string, or of an array of strings with `array`
+fn plist(strings: &[&str], array: bool) -> Vec<u8> {
+    let mut objects = Vec::new();
+    if array {
+        let mut object = marker(0xA0, strings.len());
+        //the strings follow the array, refer to them by their index as two bytes
+        for index in 1..=strings.len() {
+            object.extend((index as u16).to_be_bytes());
+        }
+        objects.push(object);
+    }
+    for string in strings {
+        objects.push(if string.is_ascii() {
+            let mut object = marker(0x50, string.len());
+            object.extend(string.as_bytes());
+            object
+        } else {
+            let units: Vec<u16> = string.encode_utf16().collect();
+            let mut object = marker(0x60, units.len());
+            object.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+            object
+        });
+    }
+
+    let mut plist = b"bplist00".to_vec();
+    let mut offsets = Vec::new();
+    for object in &objects {
+        offsets.push(plist.len() as u64);
+        plist.extend(object);
+    }
+    let offset_table = plist.len() as u64;
+    for offset in offsets {
+        plist.extend(offset.to_be_bytes());
+    }
+    //trailer: sizes of offsets and references, number of objects, top object, offset table
+    plist.extend([0; 6]);
+    plist.extend([8, 2]);
+    plist.extend((objects.len() as u64).to_be_bytes());
+    plist.extend(0u64.to_be_bytes());
+    plist.extend(offset_table.to_be_bytes());
+    plist
+}
+
+//the type marker of an object with `len` elements, longer lengths follow as an integer object
+fn marker(kind: u8, len: usize) -> Vec<u8> {
+    if len < 15 {
+        return vec![kind | len as u8];
+    }
+    let mut marker = vec![kind | 0xF];
+    if let Ok(len) = u8::try_from(len) {
+        marker.extend([0x10, len]);
+    } else if let Ok(len) = u16::try_from(len) {
+        marker.push(0x11);
+        marker.extend(len.to_be_bytes());
+    } else {
+        marker.push(0x12);
+        marker.extend((len as u32).to_be_bytes());
+    }
+    marker
+}
@@ -1,210 +1,1385 @@
+mod commands;
+mod config;
+mod filter;
+mod hooks;
+mod i18n;
+mod journal;
+mod local_config;
+mod macos;
+mod metrics;
+mod naming;
+mod notify;
+mod parser;
+mod pdf;
+mod placeholder;
+mod preflight;
+mod preview;
+mod process;
+mod provenance;
+mod reason;
+mod retry;
+mod state;
+mod telemetry;
+mod thumbnail;
+mod timings;
+mod xattr;
+
 use std::{
-    env::args,
-    ffi::OsStr,
-    fs::{self, read_dir},
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, ErrorKind},
     path::{Path, PathBuf},
+    process::ExitCode,
+    time::{Duration, SystemTime},
 };
 
-fn main() -> std::io::Result<()> {
-    //get path or filename from args
-    let path = PathBuf::from(args().nth(1).expect("no file or directory provided"));
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use clap::{
+    error::ErrorKind as ClapErrorKind, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+};
+use regex::Regex;
 
-    //Alternatively filename can be specified here. Add // to line above and remove at line below + enter path
-    //let path = PathBuf::from(r"filename");
+use commands::export::{ledger::Syntax, Format};
+use config::Config;
+use filter::Filter;
+use i18n::{tr, Lang};
+use naming::{FolderLayout, NameRules, RenamedDetector, Separator, Template, DEFAULT_TEMPLATE};
+use parser::DateSource;
+use placeholder::Placeholders;
+use process::{Outcome, RunOptions, Summary};
+use reason::Reason;
+use timings::Timings;
 
-    //print path/file provided to stdout
-    println!("path or file: {:?}", path);
-
-    //check is path is file or directory
-    if path.is_file() && path.extension().and_then(OsStr::to_str) == Some("pdf") {
-        rename(&path)?;
-    } else if path.is_dir() {
-        //println!("Is dir: {:?}",&path);
-        for entry in read_dir(&path).expect("error parsing 'entry in read_dir(&path)'") {
-            let entry = entry.expect("error unwrapping entry");
-            let file_path = entry.path();
-            //check if path is file, is a pdf file and if the filename does not start with "20" (as this would indicate it already got renamed)
-            if file_path.is_file()
-                && file_path.extension().and_then(OsStr::to_str) == Some("pdf")
-                && !entry.file_name().to_str().unwrap().starts_with("20")
-            {
-                let name = rename(&file_path)?;
-                println!(
-                    "Renamed {:?} to {:?}",
-                    entry.file_name(),
-                    name.file_name().unwrap()
-                );
-            } else if file_path.is_file()
-                && file_path.extension().and_then(OsStr::to_str) == Some("pdf")
-                && entry.file_name().to_str().unwrap().starts_with("20")
-            {
-                println!(
-                    "File {:?} ignored as it seems to have been renamed already.",
-                    entry.file_name()
-                );
-            }
-        }
-    }
+/// Renames Trade Republic PDF statements as date_ordertype_name.
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// PDF file or directory containing the PDF files [default: `folder` from the config file]
+    #[arg(env = "TR_PDF_RENAME_PATH")]
+    path: Option<PathBuf>,
+
+    /// Only process files added since the previous run on this directory
+    #[arg(long, conflicts_with = "newer_than")]
+    since_last_run: bool,
+
+    /// Only process files added after this date or datetime (e.g. 2024-01-31 or "2024-01-31 18:00")
+    #[arg(long, value_name = "DATETIME", value_parser = parse_datetime)]
+    newer_than: Option<DateTime<Utc>>,
+
+    /// Only rename documents of these types (comma separated, e.g. Dividende,Wertpapierabrechnung)
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    only: Vec<String>,
+
+    /// Never rename documents of these types (comma separated)
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    skip: Vec<String>,
+
+    /// Only rename documents about this security (repeatable)
+    #[arg(long, value_name = "ISIN", value_parser = filter::parse_isin)]
+    isin: Vec<String>,
+
+    /// Only rename documents with a total amount of at least this much (e.g. 100 or 99.90)
+    #[arg(long, value_name = "AMOUNT", value_parser = filter::parse_cents)]
+    min_amount: Option<i64>,
+
+    /// Only rename documents with a total amount of at most this much
+    #[arg(long, value_name = "AMOUNT", value_parser = filter::parse_cents)]
+    max_amount: Option<i64>,
+
+    /// Do not open files larger than this, e.g. 300M or 1G [default: 0 = unlimited]
+    #[arg(long, value_name = "SIZE", value_parser = filter::parse_size)]
+    max_size: Option<u64>,
+
+    /// Only open files modified within this age, e.g. 12h, 7d or 2w
+    #[arg(long, value_name = "AGE", value_parser = filter::parse_age)]
+    mtime_newer_than: Option<Duration>,
+
+    /// Also process the subdirectories, honoring .trrename.toml and .trignore files
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// With --recursive, also process symlinked subdirectories and junctions, skipping links back to directories processed already
+    #[arg(long, requires = "recursive")]
+    follow_links: bool,
+
+    /// Only process the files that could not be parsed in previous runs, e.g. after an update
+    #[arg(long, conflicts_with_all = ["since_last_run", "newer_than"])]
+    retry_unparsed: bool,
+
+    /// Move files that cannot be read or parsed into this directory, each with a note why [default: `unparsed_dir` from the config file]
+    #[arg(long, value_name = "DIR")]
+    unparsed_dir: Option<PathBuf>,
+
+    /// Copy the files into --unparsed-dir instead of moving them
+    #[arg(long)]
+    copy_unparsed: bool,
+
+    /// Set the title of the renamed PDFs to their new name and the subject to broker and document type, e.g. "Trade Republic Dividende"
+    #[arg(long)]
+    set_pdf_info: bool,
+
+    /// How often to retry a rename while another program has the file open, e.g. a previewer or virus scanner on Windows [default: 5]
+    #[arg(long, value_name = "N")]
+    rename_retries: Option<u32>,
+
+    /// Milliseconds to wait before the first retry of a blocked rename, doubled for every further one [default: 200]
+    #[arg(long, value_name = "MS")]
+    rename_retry_delay: Option<u64>,
+
+    /// How often to retry reading or renaming a file after a network error like a timeout of a network share [default: 3]
+    #[arg(long, value_name = "N")]
+    io_retries: Option<u32>,
+
+    /// Milliseconds to wait before the first retry after a network error, doubled for every further one [default: 500]
+    #[arg(long, value_name = "MS")]
+    io_retry_delay: Option<u64>,
+
+    /// Set the Spotlight title and keywords (broker, document type, asset and ISIN) of the renamed PDFs, on macOS
+    #[arg(long)]
+    spotlight: bool,
+
+    /// Remove the quarantine attribute of the renamed PDFs, so macOS opens them without the downloaded file warning
+    #[arg(long)]
+    clear_quarantine: bool,
+
+    /// Don't record the original name, parse time and version in extended attributes of the renamed PDFs (done on Linux by default)
+    #[arg(long)]
+    no_provenance_xattrs: bool,
+
+    /// What to do with online-only files of OneDrive, Dropbox or iCloud Drive, which are downloaded when read: hydrate (read them anyway), skip (report them as skipped) or local (only process local files) [default: hydrate]
+    #[arg(long, value_name = "POLICY")]
+    placeholders: Option<Placeholders>,
+
+    /// Clear the read-only attribute of the PDFs before renaming them instead of stopping, on Windows
+    #[arg(long)]
+    chmod: bool,
+
+    /// Write a PNG preview of the first page next to each renamed PDF (needs a build with the thumbnails feature)
+    #[arg(long)]
+    thumbnails: bool,
+
+    /// Report the time spent in text extraction, parsing and filesystem operations
+    #[arg(long)]
+    timings: bool,
+
+    /// Number of slowest files listed by --timings
+    #[arg(long, value_name = "N", default_value_t = 5, requires = "timings")]
+    slowest: usize,
+
+    /// Naming template [default: {date}_{type}_{asset}], tokens: {date} {year} {month} {day} {type} {asset} {isin} {amount} {quantity} {tax_year} {period_start} {period_end} {counter} {orig} {orig_stem}
+    #[arg(long, env = "TR_PDF_RENAME_TEMPLATE")]
+    template: Option<Template>,
+
+    /// Character between the parts of the names: underscore, hyphen or space [default: underscore]
+    #[arg(long, global = true)]
+    separator: Option<Separator>,
+
+    /// Language of the messages: en or de [default: the language of the locale]
+    #[arg(long, global = true)]
+    lang: Option<Lang>,
+
+    /// Dates preferred for the names, in this order: execution, trade, booking, value, period (end), header (DATUM), any [default: execution,trade,booking,value,period,header,any]
+    #[arg(long, value_name = "DATES", value_delimiter = ',')]
+    date_preference: Vec<DateSource>,
+
+    /// Use only ASCII characters in the new names, umlauts become ae, oe, ue and ss
+    #[arg(long)]
+    ascii: bool,
+
+    /// Remove boilerplate like "UCITS ETF", "(Acc)", share class codes and issuer names from asset names
+    #[arg(long)]
+    strip_boilerplate: bool,
+
+    /// Shorten asset names to at most N characters at word boundaries, 0 means unlimited
+    #[arg(long, value_name = "N")]
+    max_asset_len: Option<usize>,
+
+    /// Shorten the asset name when the full path of a renamed PDF would be longer than N characters, 0 means unlimited [default: 259 on Windows, unlimited elsewhere]
+    #[arg(long, value_name = "N")]
+    max_path_len: Option<usize>,
+
+    /// Regex of filenames that are renamed already [default: names built with the template]
+    #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+    renamed_pattern: Option<Regex>,
+
+    /// Regex of filenames organized by another tool, these files are left alone (repeatable)
+    #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+    organized_pattern: Vec<Regex>,
+
+    /// Keep running and process new files in the directory as they arrive
+    #[arg(long)]
+    watch: bool,
+
+    /// Seconds between two checks for new files in --watch mode
+    #[arg(long, value_name = "SECONDS", default_value_t = 10, requires = "watch")]
+    interval: u64,
+
+    /// Show a desktop notification when files were renamed or could not be parsed in --watch mode
+    #[arg(long, requires = "watch")]
+    desktop_notifications: bool,
+
+    /// Serve Prometheus metrics at http://<ADDR>/metrics in --watch mode, e.g. 127.0.0.1:9464
+    #[arg(long, value_name = "ADDR", requires = "watch")]
+    metrics_addr: Option<String>,
+
+    /// Send a trace of every processed file to the OpenTelemetry collector at this base URL, e.g. http://localhost:4318 (needs a build with the otlp feature) [default: `otlp_endpoint` from the config file]
+    #[arg(long, value_name = "URL")]
+    otlp_endpoint: Option<String>,
+
+    /// Only report which files would be renamed and exit with a failure code if there are any
+    #[arg(long, conflicts_with = "watch")]
+    check: bool,
+
+    /// Show the names N randomly chosen files would get, without renaming anything
+    #[arg(long, value_name = "N", conflicts_with_all = ["watch", "check", "recursive", "retry_unparsed"])]
+    sample: Option<usize>,
+
+    /// Write the data parsed from every file, with its path and new name, to this JSON file
+    #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+    dump_parsed: Option<PathBuf>,
 
-    Ok(())
+    /// Print nothing if no file was renamed, otherwise only the renamed files (for cron jobs)
+    #[arg(long, conflicts_with = "watch")]
+    quiet_if_unchanged: bool,
+
+    /// TOML config file, e.g. with a [webhook] to notify when the run finished [default: tr_pdf_rename/config.toml in the user config directory]
+    #[arg(long, value_name = "FILE", env = "TR_PDF_RENAME_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// Directory with parser plugins (*.wasm) for further brokers, needs the plugins feature [default: tr_pdf_rename/plugins in the user config directory, if it exists]
+    #[arg(long, value_name = "DIR", global = true)]
+    plugins: Option<PathBuf>,
 }
 
-pub fn rename(path: &Path) -> std::io::Result<PathBuf> {
-    //prepare the new path to rename the file
-    let mut new_path = PathBuf::new();
+#[derive(Subcommand)]
+enum Command {
+    /// Rename PDFs and move them into directories like <root>/<year>/<type>/ of an archive
+    Archive {
+        /// PDF file or directory containing the PDF files [default: `folder` from the config file]
+        path: Option<PathBuf>,
 
-    //add parent path to new path and clone for further use
-    new_path.push(path.parent().unwrap());
+        /// Root directory of the archive [default: `archive_root` from the config file]
+        #[arg(long, value_name = "DIR")]
+        to: Option<PathBuf>,
 
-    let mut unique_path = new_path.clone();
+        /// Directories below the root built from the tokens of the naming template, e.g. {year}/{month} or {isin} [default: {year}/{type}]
+        #[arg(long, value_name = "LAYOUT")]
+        layout: Option<FolderLayout>,
 
-    //read pdf file
-    let bytes = std::fs::read(path).unwrap();
-    let out = pdf_extract::extract_text_from_mem(&bytes).unwrap();
+        /// Also process the subdirectories, except the archive itself
+        #[arg(short, long)]
+        recursive: bool,
 
-    //println!("Read: {}", out);
+        /// Encrypt the archived PDFs with AES-256, the password is read from TR_PDF_RENAME_PASSWORD or asked for
+        #[arg(long)]
+        encrypt: bool,
 
-    //find date of transaction and create string yyyy_mm_dd_
-    let position_date: usize = out.clone().find("DATUM").unwrap() + 5;
+        /// Recompress the archived PDFs, merging duplicate streams and dropping unused objects
+        #[arg(long)]
+        optimize: bool,
 
-    let mut date: String = String::new();
+        /// Write a PNG preview of the first page next to each archived PDF
+        #[arg(long)]
+        thumbnails: bool,
 
-    for i in 0..11 {
-        date.push(out.clone().chars().nth(position_date + i).unwrap())
-    }
+        /// Only print where the files would be moved
+        #[arg(long)]
+        dry_run: bool,
+    },
 
-    //println!("{:?}", date);
+    /// Combine the renamed PDFs of a year (and type) into one PDF with a bookmark per document
+    Bundle {
+        /// Directory containing the renamed PDF files
+        folder: PathBuf,
 
-    //trim whitespaces and split date
-    let vec_date: Vec<&str> = date.trim().split('.').collect();
+        /// Year of the documents
+        #[arg(long)]
+        year: i32,
 
-    //organise date to yyyy_mm_dd
-    let mut date_ordertype_name: String = String::new();
-    date_ordertype_name.push_str(vec_date[2]);
-    date_ordertype_name.push('_');
-    date_ordertype_name.push_str(vec_date[1]);
-    date_ordertype_name.push('_');
-    date_ordertype_name.push_str(vec_date[0]);
-    date_ordertype_name.push('_');
+        /// Only documents of this type, e.g. Dividende
+        #[arg(long = "type", value_name = "TYPE")]
+        doc_type: Option<String>,
 
-    //println!("date: {:?}", date_ordertype_name);
+        /// Also bundle the documents in the subdirectories, e.g. of an archive
+        #[arg(short, long)]
+        recursive: bool,
 
-    //find order type and name
-    let mut line_name: usize = 9999;
+        /// PDF file to write [default: <year>_<type>.pdf in the current directory]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
 
-    let mut name = String::new();
+        /// Naming template the files were renamed with
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE", default_value = DEFAULT_TEMPLATE)]
+        template: Template,
+    },
 
-    let mut order_type: String = String::new();
+    /// Check text fixtures against expected filenames (expected.toml or expected.csv)
+    Corpus {
+        /// Directory containing the fixtures and the expectations
+        dir: PathBuf,
 
-    //take inbto account the different formatting
-    if out.contains("DIVIDENDE") {
-        order_type = "Dividende".to_string();
-        for (i, line) in out.lines().enumerate() {
-            if line.starts_with("POSITION") {
-                line_name = i + 2;
-                //println!("Line with POSITION: {:?},{:?}, {:?}", line, i, line_name);
-            } else if i == line_name {
-                //println!("Line Name: {:?}", line);
-                name = line.to_string();
-                break;
-            }
+        /// Naming template the expected filenames are built with [default: the one of the config file or {date}_{type}_{asset}]
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE")]
+        template: Option<Template>,
+    },
+
+    /// Check the config file and print the settings a run would use
+    Config {
+        #[command(subcommand)]
+        config: ConfigCommand,
+    },
+
+    /// Check the environment (permissions, disk space, locale, state file) before a run
+    Doctor {
+        /// Directory containing the PDF files
+        folder: PathBuf,
+
+        /// Naming template of the renamed files [default: the one of the config file or {date}_{type}_{asset}]
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE")]
+        template: Option<Template>,
+    },
+
+    /// Report documents with the same date, type, ISIN and amount, even if their bytes differ
+    Duplicates {
+        /// Directory containing the PDF files
+        folder: PathBuf,
+
+        /// Keep the first file of each group and move the identical copies of it to the trash
+        #[arg(long)]
+        delete: bool,
+
+        /// Also delete the files of a group that are not identical to the first one
+        #[arg(long, requires = "delete")]
+        delete_different: bool,
+
+        /// Remove the files instead of moving them to the trash
+        #[arg(long, requires = "delete")]
+        permanent_delete: bool,
+    },
+
+    /// Show which heuristics fired while parsing a PDF and the filename it would get
+    Explain {
+        /// PDF file to explain
+        file: PathBuf,
+
+        /// Naming template the filename is built from
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE", default_value = DEFAULT_TEMPLATE)]
+        template: Template,
+    },
+
+    /// Export the data parsed from the renamed PDFs, e.g. for a spreadsheet
+    Export {
+        #[command(subcommand)]
+        export: ExportCommand,
+    },
+
+    /// Print the raw text extracted from a PDF, as seen by the parser
+    ExtractText {
+        /// PDF file to extract the text from
+        file: PathBuf,
+
+        /// Prefix every line with its line number
+        #[arg(short = 'n', long)]
+        line_numbers: bool,
+    },
+
+    /// Check the names of already renamed PDFs for malformed dates, unknown types and invalid ISINs
+    Lint {
+        /// Directory containing the renamed PDF files
+        folder: PathBuf,
+
+        /// Naming template the files were renamed with [default: the one of the config file or {date}_{type}_{asset}]
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE")]
+        template: Option<Template>,
+    },
+
+    /// Write man pages generated from the command line arguments, e.g. for distribution packages
+    Mangen {
+        /// Directory the pages of the command and every subcommand are written to, as <name>.1 [default: only the page of the command to stdout]
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+
+    /// Rename files named with one template to another template without re-reading the PDFs
+    Migrate {
+        /// Directory containing the renamed PDF files
+        folder: PathBuf,
+
+        /// Template the files are currently named with
+        #[arg(long, value_name = "TEMPLATE")]
+        from_template: Template,
+
+        /// Template the files should be named with
+        #[arg(long, value_name = "TEMPLATE")]
+        to_template: Template,
+
+        /// Only print the new names without renaming
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List renamed PDFs named with another template or older parser rules, and rename them anew
+    Outdated {
+        /// Directory containing the renamed PDF files, e.g. the archive root
+        folder: PathBuf,
+
+        /// Naming template the files should be named with [default: `template` from the config file, or {date}_{type}_{asset}]
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE")]
+        template: Option<Template>,
+
+        /// Also look into the subdirectories
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Parse the outdated files again and rename them with the current template and rules
+        #[arg(long)]
+        migrate: bool,
+    },
+
+    /// List (and delete) renamed PDFs older than the retention of their type in the config file
+    Prune {
+        /// Directory containing the renamed PDF files [default: `archive_root` or `folder` from the config file]
+        folder: Option<PathBuf>,
+
+        /// Also look into the subdirectories, e.g. of an archive
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Move the listed files to the trash
+        #[arg(long)]
+        delete: bool,
+
+        /// Remove the files instead of moving them to the trash
+        #[arg(long, requires = "delete", alias = "permanent-delete")]
+        permanently: bool,
+
+        /// Naming template the files were renamed with
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE", default_value = DEFAULT_TEMPLATE)]
+        template: Template,
+    },
+
+    /// Sum up the parsed documents, e.g. the dividends of a year
+    Report {
+        #[command(subcommand)]
+        report: ReportCommand,
+    },
+
+    /// Write a redacted text fixture and the parse result of a PDF for attaching to a bug report
+    ReportIssue {
+        /// PDF file that is parsed wrongly
+        file: PathBuf,
+
+        /// Directory to write the report files to
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out_dir: PathBuf,
+
+        /// Additional text to remove, e.g. your name if it is not detected (repeatable)
+        #[arg(long, value_name = "TEXT")]
+        redact: Vec<String>,
+    },
+
+    /// Replace this binary with the one of the latest GitHub release
+    SelfUpdate {
+        /// Only report if a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Count the PDFs of a directory per document type, year and ISIN
+    Stats {
+        /// Directory containing the PDF files
+        folder: PathBuf,
+    },
+
+    /// Re-parse already renamed PDFs and report files whose name does not match their content
+    Verify {
+        /// Directory containing the renamed PDF files
+        folder: PathBuf,
+
+        /// Naming template the files were renamed with
+        #[arg(long, env = "TR_PDF_RENAME_TEMPLATE", default_value = DEFAULT_TEMPLATE)]
+        template: Template,
+    },
+}
+
+/// The reports of the `report` command.
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Product costs, service costs and inducements per year, from the Ex-Post Kosteninformation
+    Costs {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Also write the table to this CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+
+    /// Gross and net dividends of a year per ISIN and in total
+    Dividends {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Year the dividends were paid in
+        #[arg(long)]
+        year: i32,
+
+        /// Also write the table to this CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+
+    /// Securities held at a date, from the position table of the last Depotauszug before it
+    Holdings {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Date of the holdings, e.g. 2024-06-30 [default: the latest Depotauszug]
+        #[arg(long, value_name = "DATE")]
+        as_of: Option<NaiveDate>,
+
+        /// Also write the table to this CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+
+    /// Interest per month and year, from the Zinsen documents and the interest lines of others
+    Interest {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Only the interest paid in this year
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// Also write the table to this CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+
+    /// Amounts received via Saveback and round-up per month and year
+    Saveback {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Only the executions of this year
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// Also write the table to this CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+
+    /// Kapitalertragsteuer, Solidaritätszuschlag, Kirchensteuer and Quellensteuer withheld per year
+    Taxes {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Only the taxes of this year
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// Also write the table to this CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<PathBuf>,
+    },
+}
+
+/// The checks of the `config` command.
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check the config file, the paths and the hooks, and print the settings of a run with ARGS
+    Check {
+        /// Arguments of the run, e.g. -- --template '{date}_{asset}' -r ~/Documents [default: none, only the config file and the environment]
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "ARGS"
+        )]
+        args: Vec<OsString>,
+    },
+}
+
+/// The exports of the `export` command.
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// Buys, sells, dividends, interest and Saveback as beancount or ledger entries
+    Ledger {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Syntax to write: beancount or ledger
+        #[arg(long, default_value = "beancount")]
+        syntax: Syntax,
+
+        /// File to write [default: stdout]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Dividends, interest and realized gains of a year as CSV for German tax software
+    Tax {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Year of the tax return
+        #[arg(long)]
+        year: i32,
+
+        /// File to write [default: stdout]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Buys and sells with date, ISIN, asset, quantity, price, fees and amount
+    Transactions {
+        #[command(flatten)]
+        documents: Documents,
+
+        /// Format to write: csv or json
+        #[arg(long, default_value = "csv")]
+        format: Format,
+
+        /// File to write [default: stdout]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Where the renamed documents of a report are.
+#[derive(Args)]
+struct Documents {
+    /// Directory containing the PDF files [default: `archive_root` or `folder` from the config file]
+    folder: Option<PathBuf>,
+
+    /// Also look into the subdirectories, e.g. of an archive
+    #[arg(short, long)]
+    recursive: bool,
+}
+
+fn main() -> io::Result<ExitCode> {
+    //get path or filename from args
+    let cli = Cli::command()
+        .long_version(long_version())
+        .try_get_matches()
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+        .unwrap_or_else(|err| err.exit());
+
+    //plugins are loaded first, the config file may label their document types
+    match &cli.plugins {
+        Some(dir) => {
+            parser::load_plugins(dir, false)?;
         }
-    } else if out.contains("SAVEBACK") {
-        order_type = "Wertpapierabrechnung_Saveback".to_string();
-        for (i, line) in out.lines().enumerate() {
-            if line.starts_with("POSITION") {
-                line_name = i + 2;
-                //println!("Line with POSITION: {:?},{:?}, {:?}", line, i, line_name);
-            } else if i == line_name {
-                //println!("Line Name: {:?}", line);
-                name = line.to_string();
+        //a broken plugin nobody asked for must not stop `doctor` or `config check` from telling
+        None => {
+            if let Some(dir) = Config::default_plugin_dir().filter(|dir| dir.is_dir()) {
+                if parser::PLUGINS_SUPPORTED {
+                    if let Err(err) = parser::load_plugins(&dir, true) {
+                        eprintln!("Plugins in {:?} not loaded: {err}", dir);
+                    }
+                }
             }
         }
-    } else if out.contains("SPARPLAN") {
-        order_type = "Wertpapierabrechnung_Sparplan".to_string();
-        for (i, line) in out.lines().enumerate() {
-            if line.starts_with("POSITION") {
-                line_name = i + 2;
-                //println!("Line with POSITION: {:?},{:?}, {:?}", line, i, line_name);
-            } else if i == line_name {
-                //println!("Line Name: {:?}", line);
-                name = line.to_string();
+    }
+
+    let mut config_error = None;
+    let config = match Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        //`config check` reports the mistake instead of failing on it
+        Err(err) if matches!(cli.command, Some(Command::Config { .. })) => {
+            commands::config_check::invalid(&err);
+            return Ok(ExitCode::FAILURE);
+        }
+        //`doctor` reports it among its other checks
+        Err(err) if matches!(cli.command, Some(Command::Doctor { .. })) => {
+            config_error = Some(err);
+            Config::default()
+        }
+        Err(err) => return Err(err),
+    };
+    i18n::init(cli.lang.or(config.lang));
+    let styled = |template: Template| styled_template(template, cli.separator, &config);
+
+    if let Some(command) = cli.command {
+        return match command {
+            Command::Archive {
+                path,
+                to,
+                layout,
+                recursive,
+                encrypt,
+                optimize,
+                thumbnails,
+                dry_run,
+            } => {
+                if thumbnails {
+                    require_thumbnails()?;
+                }
+                let Some(path) = path.or_else(|| config.folder.clone()) else {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "no path given, pass one or set `folder` in the config file",
+                    ));
+                };
+                let Some(root) = to.or_else(|| config.archive_root.clone()) else {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "no archive given, pass --to or set `archive_root` in the config file",
+                    ));
+                };
+                let defaults = RunOptions::from_config(
+                    &config,
+                    styled(config.template.clone().unwrap_or_default()),
+                )?;
+                let options = RunOptions {
+                    recursive,
+                    dry_run,
+                    folder_layout: layout.unwrap_or(defaults.folder_layout),
+                    encrypt_password: if encrypt && !dry_run {
+                        Some(encryption_password()?)
+                    } else {
+                        None
+                    },
+                    optimize,
+                    thumbnails,
+                    ..defaults
+                };
+                require_macos(&options)?;
+                commands::archive::run(&path, &root, &options).map(|()| ExitCode::SUCCESS)
+            }
+            Command::Bundle {
+                folder,
+                year,
+                doc_type,
+                recursive,
+                output,
+                template,
+            } => {
+                let doc_type = doc_type_args("--type", doc_type.as_slice()).pop();
+                let output = output.unwrap_or_else(|| {
+                    PathBuf::from(match &doc_type {
+                        Some(doc_type) => format!("{year}_{doc_type}.pdf"),
+                        None => format!("{year}.pdf"),
+                    })
+                });
+                commands::bundle::run(
+                    &folder,
+                    year,
+                    doc_type.as_deref(),
+                    &styled(template),
+                    recursive,
+                    &output,
+                )
+                .map(|()| ExitCode::SUCCESS)
+            }
+            Command::Corpus { dir, template } => {
+                let options = RunOptions::from_config(
+                    &config,
+                    styled(template.or(config.template.clone()).unwrap_or_default()),
+                )?;
+                commands::corpus::run(&dir, &options).map(exit_code)
+            }
+            Command::Config {
+                config: ConfigCommand::Check { args },
+            } => {
+                //the arguments are parsed like those of a run, the environment included
+                let run = Cli::try_parse_from(
+                    [OsString::from(env!("CARGO_PKG_NAME"))]
+                        .into_iter()
+                        .chain(args),
+                )
+                .unwrap_or_else(|err| err.exit());
+                let options = match run_options(&run, &config) {
+                    Ok(options) => options,
+                    Err(err) => {
+                        commands::config_check::invalid(&err);
+                        return Ok(ExitCode::FAILURE);
+                    }
+                };
+                commands::config_check::run(
+                    Config::file(cli.config.as_deref()).as_deref(),
+                    &config,
+                    run.path.or_else(|| config.folder.clone()).as_deref(),
+                    &options,
+                )
+                .map(exit_code)
+            }
+            Command::Doctor { folder, template } => {
+                let options = match config_error {
+                    Some(err) => Err(err),
+                    None => RunOptions::from_config(
+                        &config,
+                        styled(template.or(config.template.clone()).unwrap_or_default()),
+                    ),
+                };
+                commands::doctor::run(
+                    &folder,
+                    Config::file(cli.config.as_deref()).as_deref(),
+                    options,
+                )
+                .map(exit_code)
+            }
+            Command::Duplicates {
+                folder,
+                delete,
+                delete_different,
+                permanent_delete,
+            } => commands::duplicates::run(&folder, delete, delete_different, permanent_delete)
+                .map(|()| ExitCode::SUCCESS),
+            Command::Explain { file, template } => {
+                commands::explain::run(&file, &styled(template)).map(|()| ExitCode::SUCCESS)
+            }
+            Command::Export { export } => match export {
+                ExportCommand::Ledger {
+                    documents,
+                    syntax,
+                    output,
+                } => commands::export::ledger::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    &config.accounts,
+                    syntax,
+                    output.as_deref(),
+                ),
+                ExportCommand::Tax {
+                    documents,
+                    year,
+                    output,
+                } => commands::export::tax::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    year,
+                    output.as_deref(),
+                ),
+                ExportCommand::Transactions {
+                    documents,
+                    format,
+                    output,
+                } => commands::export::transactions::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    format,
+                    output.as_deref(),
+                ),
+            }
+            .map(|()| ExitCode::SUCCESS),
+            Command::ExtractText { file, line_numbers } => {
+                commands::extract_text::run(&file, line_numbers).map(|()| ExitCode::SUCCESS)
+            }
+            Command::Lint { folder, template } => {
+                let detector = renamed_detector(template, cli.separator, &config)?;
+                commands::lint::run(&folder, &detector, &config.doc_types).map(exit_code)
+            }
+            Command::Mangen { out_dir } => {
+                commands::mangen::run(Cli::command(), out_dir.as_deref())
+                    .map(|()| ExitCode::SUCCESS)
+            }
+            Command::Migrate {
+                folder,
+                from_template,
+                to_template,
+                dry_run,
+            } => commands::migrate::run(
+                &folder,
+                &styled(from_template),
+                &styled(to_template),
+                dry_run,
+                config.provenance_xattrs.unwrap_or(true),
+            )
+            .map(|()| ExitCode::SUCCESS),
+            Command::Outdated {
+                folder,
+                template,
+                recursive,
+                migrate,
+            } => {
+                let options = RunOptions::from_config(
+                    &config,
+                    styled(template.or(config.template.clone()).unwrap_or_default()),
+                )?;
+                commands::outdated::run(&folder, recursive, migrate, &options).map(exit_code)
             }
+            Command::Prune {
+                folder,
+                recursive,
+                delete,
+                permanently,
+                template,
+            } => {
+                let folder = documents_folder(folder, &config)?;
+                commands::prune::run(
+                    &folder,
+                    &styled(template),
+                    &config.retention,
+                    recursive,
+                    delete,
+                    permanently,
+                )
+                .map(|()| ExitCode::SUCCESS)
+            }
+            Command::Report { report } => match report {
+                ReportCommand::Costs { documents, csv } => commands::report::costs::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    csv.as_deref(),
+                ),
+                ReportCommand::Dividends {
+                    documents,
+                    year,
+                    csv,
+                } => commands::report::dividends::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    year,
+                    csv.as_deref(),
+                ),
+                ReportCommand::Holdings {
+                    documents,
+                    as_of,
+                    csv,
+                } => commands::report::holdings::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    as_of,
+                    csv.as_deref(),
+                ),
+                ReportCommand::Interest {
+                    documents,
+                    year,
+                    csv,
+                } => commands::report::interest::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    year,
+                    csv.as_deref(),
+                ),
+                ReportCommand::Saveback {
+                    documents,
+                    year,
+                    csv,
+                } => commands::report::saveback::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    year,
+                    csv.as_deref(),
+                ),
+                ReportCommand::Taxes {
+                    documents,
+                    year,
+                    csv,
+                } => commands::report::taxes::run(
+                    &documents_folder(documents.folder, &config)?,
+                    documents.recursive,
+                    year,
+                    csv.as_deref(),
+                ),
+            }
+            .map(|()| ExitCode::SUCCESS),
+            Command::ReportIssue {
+                file,
+                out_dir,
+                redact,
+            } => commands::report_issue::run(&file, &out_dir, &redact).map(|()| ExitCode::SUCCESS),
+            Command::SelfUpdate { check } => {
+                commands::self_update::run(check).map(|()| ExitCode::SUCCESS)
+            }
+            Command::Stats { folder } => commands::stats::run(&folder).map(|()| ExitCode::SUCCESS),
+            Command::Verify { folder, template } => {
+                commands::verify::run(&folder, &styled(template)).map(exit_code)
+            }
+        };
+    }
+
+    let Some(path) = cli.path.clone().or_else(|| config.folder.clone()) else {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "no path given, pass one or set `folder` in the config file",
+        ));
+    };
+
+    //print path/file provided to stdout
+    if !cli.quiet_if_unchanged {
+        println!("{}", tr!("path-or-file", path = format!("{:?}", path)));
+    }
+
+    if cli.thumbnails {
+        require_thumbnails()?;
+    }
+    let options = run_options(&cli, &config)?;
+    require_macos(&options)?;
+    //permission problems would otherwise only show up file by file
+    if !options.dry_run && cli.sample.is_none() {
+        preflight::check(&path, &options, cli.chmod)?;
+    }
+    //exports the remaining spans when dropped at the end of the run
+    let _telemetry = match cli.otlp_endpoint.as_ref().or(config.otlp_endpoint.as_ref()) {
+        Some(endpoint) => Some(telemetry::init(endpoint)?),
+        None => None,
+    };
+    if cli.watch {
+        if !path.is_dir() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "--watch needs a directory",
+            ));
         }
-    } else if out.contains("STEUERLICHE OPTIMIERUNG") {
-        order_type = "Steuerliche".to_string();
-        name = "Optimierung".to_string();
-    } else if out.contains("DEPOTAUSZUG") {
-        order_type = "Depot".to_string();
-        name = "Auszug".to_string();
-    } else if out.contains("WERTPAPIERABRECHNUNG") {
-        order_type = "Wertpapierabrechnung".to_string();
-        for (i, line) in out.lines().enumerate() {
-            if line.starts_with("POSITION") {
-                line_name = i + 2;
-                //println!("Line with POSITION: {:?},{:?}, {:?}", line, i, line_name);
-            } else if i == line_name {
-                //println!("Line Name: {:?}", line);
-                name = line.to_string();
+        if cli.desktop_notifications && !notify::DESKTOP_SUPPORTED {
+            eprintln!("Desktop notifications are not available, build with --features desktop-notifications");
+        }
+
+        let metrics = match &cli.metrics_addr {
+            Some(addr) => {
+                let metrics = metrics::serve(addr.as_str())?;
+                println!("Serving metrics at http://{addr}/metrics");
+                Some(metrics)
             }
+            None => None,
+        };
+
+        println!("{}", tr!("watching", path = format!("{:?}", path)));
+        process::watch(
+            &path,
+            &options,
+            Duration::from_secs(cli.interval),
+            |summary| {
+                if let Some(metrics) = &metrics {
+                    metrics.record(summary);
+                }
+                notify::run_finished(&config, &path, summary, cli.desktop_notifications)
+            },
+        )?;
+    }
+
+    let mut timings = Timings::default();
+
+    if let Some(count) = cli.sample {
+        if !path.is_dir() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "--sample needs a directory",
+            ));
+        }
+        let summary = process::sample(&path, count, &options, &mut timings)?;
+        if let Some(dump) = &cli.dump_parsed {
+            dump_parsed(dump, &summary)?;
+        }
+        println!(
+            "{}",
+            tr!(
+                "sample-summary",
+                renamed = summary.renamed(),
+                skipped = summary.skipped(),
+                filtered = summary.filtered(),
+                already = summary.already_renamed()
+            )
+        );
+        summary.print_reasons();
+        if cli.timings {
+            timings.print_report(cli.slowest);
         }
-    } else if out.contains("DEPOTTRANSFER") {
-        order_type = "Depottransfer".to_string();
-        for line in out.lines() {
-            if line.starts_with("1 Depottransfer") {
-                name = line
-                    .strip_prefix("1 Depottransfer eingegangen ")
-                    .unwrap()
-                    .to_string();
-                //println!("Line with POSITION: {:?},{:?}, {:?}", line, i, line_name);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let summary = process::run(&path, &options, &mut timings)?;
+    if let Some(dump) = &cli.dump_parsed {
+        dump_parsed(dump, &summary)?;
+    }
+
+    //only the changes, nothing at all if there were none
+    if cli.quiet_if_unchanged {
+        for file in &summary.files {
+            if let Outcome::Renamed { new_name, .. } = &file.outcome {
+                println!("{} -> {new_name}", file.file);
             }
         }
-    };
+    }
+
+    if !cli.quiet_if_unchanged {
+        summary.print_reasons();
+    }
+    if cli.timings {
+        timings.print_report(cli.slowest);
+    }
+
+    if cli.check {
+        //nothing was renamed, so there is nothing to notify about
+        if summary.renamed() > 0 {
+            println!("{}", tr!("check-summary", count = summary.renamed()));
+        }
+        return Ok(exit_code(summary.renamed() == 0));
+    }
+
+    notify::run_finished(&config, &path, &summary, false);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+//write the parsed data of the files of a run as a JSON array
+fn dump_parsed(path: &Path, summary: &Summary) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(&summary.parsed).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
 
-    //println!("name: {:?}", name);
+//the folder of a command reading the renamed documents, the archive or the folder of the config
+//file without one
+fn documents_folder(folder: Option<PathBuf>, config: &Config) -> io::Result<PathBuf> {
+    folder
+        .or_else(|| config.archive_root.clone())
+        .or_else(|| config.folder.clone())
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "no folder given, pass one or set `archive_root` in the config file",
+            )
+        })
+}
 
-    //finalize new filename as date_ordertype_name.pdf
-    date_ordertype_name.push_str(&order_type);
+//the options of a run, the arguments of `cli` taking precedence over the config file
+fn run_options(cli: &Cli, config: &Config) -> io::Result<RunOptions> {
+    let defaults = RunOptions::from_config(
+        config,
+        styled_template(
+            cli.template
+                .clone()
+                .or(config.template.clone())
+                .unwrap_or_default(),
+            cli.separator,
+            config,
+        ),
+    )?;
+    Ok(RunOptions {
+        name_rules: NameRules {
+            ascii: cli.ascii,
+            max_asset_len: cli
+                .max_asset_len
+                .or(config.max_asset_length)
+                .filter(|&max| max > 0),
+            strip_boilerplate: cli.strip_boilerplate || config.strip_boilerplate,
+            ..defaults.name_rules
+        },
+        renamed_pattern: cli.renamed_pattern.clone().or(defaults.renamed_pattern),
+        unparsed_dir: cli.unparsed_dir.clone().or(defaults.unparsed_dir),
+        copy_unparsed: cli.copy_unparsed,
+        thumbnails: cli.thumbnails,
+        set_info: cli.set_pdf_info || defaults.set_info,
+        rename_retry: defaults
+            .rename_retry
+            .with(cli.rename_retries, cli.rename_retry_delay),
+        io_retry: defaults.io_retry.with(cli.io_retries, cli.io_retry_delay),
+        spotlight: cli.spotlight || defaults.spotlight,
+        clear_quarantine: cli.clear_quarantine || defaults.clear_quarantine,
+        provenance: !cli.no_provenance_xattrs && defaults.provenance,
+        placeholders: cli.placeholders.unwrap_or(defaults.placeholders),
+        max_path_len: match cli.max_path_len {
+            Some(0) => None,
+            Some(max) => Some(max),
+            None => defaults.max_path_len,
+        },
+        organized_patterns: defaults
+            .organized_patterns
+            .into_iter()
+            .chain(cli.organized_pattern.iter().cloned())
+            .collect(),
+        since_last_run: cli.since_last_run,
+        newer_than: cli.newer_than,
+        retry_unparsed: cli.retry_unparsed,
+        quiet: cli.quiet_if_unchanged,
+        dry_run: cli.check,
+        recursive: cli.recursive,
+        follow_links: cli.follow_links,
+        filter: Filter {
+            only_types: doc_type_args("--only", &cli.only),
+            skip_types: doc_type_args("--skip", &cli.skip),
+            isins: cli.isin.clone(),
+            min_cents: cli.min_amount,
+            max_cents: cli.max_amount,
+            max_size: cli.max_size,
+            modified_after: cli
+                .mtime_newer_than
+                .and_then(|age| SystemTime::now().checked_sub(age)),
+        },
+        date_preference: if cli.date_preference.is_empty() {
+            defaults.date_preference
+        } else {
+            cli.date_preference.clone()
+        },
+        ..defaults
+    })
+}
 
-    date_ordertype_name.push('_');
+//the names use the separator and the document type labels of the config file
+//tells renamed files apart like a run with `template`, or the template of the config file, does
+fn renamed_detector(
+    template: Option<Template>,
+    separator: Option<Separator>,
+    config: &Config,
+) -> io::Result<RenamedDetector> {
+    let template = template.or(config.template.clone()).unwrap_or_default();
+    let options = RunOptions::from_config(config, styled_template(template, separator, config))?;
+    Ok(RenamedDetector::new(
+        &options.template,
+        options.renamed_pattern.as_ref(),
+    ))
+}
 
-    date_ordertype_name.push_str(&name);
+fn styled_template(template: Template, separator: Option<Separator>, config: &Config) -> Template {
+    template
+        .with_separator(separator.or(config.separator).unwrap_or_default().as_char())
+        .with_doc_type_labels(config.doc_types.clone())
+}
 
-    new_path.push(date_ordertype_name.clone());
+//what differs between two installations renaming the same document differently
+fn long_version() -> String {
+    let features: Vec<_> = [
+        (
+            "desktop-notifications",
+            cfg!(feature = "desktop-notifications"),
+        ),
+        ("email", cfg!(feature = "email")),
+        ("otlp", cfg!(feature = "otlp")),
+        ("plugins", cfg!(feature = "plugins")),
+        ("thumbnails", cfg!(feature = "thumbnails")),
+    ]
+    .into_iter()
+    .filter(|&(_, enabled)| enabled)
+    .map(|(feature, _)| feature)
+    .collect();
 
-    new_path.set_extension("pdf");
+    format!(
+        "{}\nparser rules: {}\nfeatures: {}\ntarget: {}",
+        env!("CARGO_PKG_VERSION"),
+        parser::RULES_VERSION,
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(", ")
+        },
+        env!("TARGET")
+    )
+}
 
-    //rename file
+//the document types given with `flag`, checked only once the plugins are loaded as they may add
+//further types
+fn doc_type_args(flag: &str, args: &[String]) -> Vec<String> {
+    args.iter()
+        .map(|arg| filter::parse_doc_type(arg))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|err| {
+            Cli::command()
+                .error(ClapErrorKind::InvalidValue, format!("{flag}: {err}"))
+                .exit()
+        })
+}
 
-    //check if file exists and add counter to filename to create unique filename
-    if new_path.exists() {
-        let unique_filename = get_unique_filename(new_path);
-        unique_path.push(&unique_filename);
-        fs::rename(path, &unique_path)?;
-        new_path = unique_path;
+//thumbnails are rendered by pdfium, which is only linked in with the thumbnails feature
+fn require_thumbnails() -> io::Result<()> {
+    if thumbnail::THUMBNAILS_SUPPORTED {
+        Ok(())
     } else {
-        fs::rename(path, &new_path)?;
+        Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "thumbnails are not available, build with --features thumbnails",
+        ))
     }
-    Ok(new_path)
 }
 
-fn get_unique_filename(mut path: PathBuf) -> PathBuf {
-    let mut counter = 1;
-    let original_path = path.clone();
+//Spotlight and the quarantine attribute only exist on macOS
+fn require_macos(options: &RunOptions) -> io::Result<()> {
+    if (options.spotlight || options.clear_quarantine) && !macos::MACOS {
+        Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "Spotlight metadata and the quarantine attribute are only available on macOS",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+//the password to encrypt the archive with, from the environment or typed twice on the terminal
+fn encryption_password() -> io::Result<String> {
+    if let Ok(password) = env::var("TR_PDF_RENAME_PASSWORD") {
+        if password.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "TR_PDF_RENAME_PASSWORD is empty",
+            ));
+        }
+        return Ok(password);
+    }
 
-    while path.exists() {
-        let mut new_path = original_path.clone();
-        new_path.set_file_name(format!(
-            "{}_{}.pdf",
-            original_path
-                .file_stem()
-                .and_then(|stem| stem.to_str())
-                .unwrap_or(""),
-            counter
+    let password =
+        rpassword::prompt_password("Password for the archived PDFs: ").map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("cannot ask for the password ({err}), set TR_PDF_RENAME_PASSWORD instead"),
+            )
+        })?;
+    if password.is_empty() {
+        return Err(io::Error::new(ErrorKind::InvalidInput, "empty password"));
+    }
+    if rpassword::prompt_password("Repeat the password: ")? != password {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "the passwords do not match",
         ));
-        path = new_path;
-        counter += 1;
     }
+    Ok(password)
+}
+
+//exit with a failure code if a check did not pass
+fn exit_code(passed: bool) -> ExitCode {
+    if passed {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+//parse a date ("2024-01-31") or a local datetime ("2024-01-31 18:00", "2024-01-31T18:00:00") or RFC 3339
+fn parse_datetime(arg: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(arg) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    let naive = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(arg, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(arg, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+    .ok_or_else(|| format!("invalid date or datetime: {arg:?}"))?;
+
+    naive
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .ok_or_else(|| format!("{arg:?} does not exist in the local timezone"))
+}
+
+/// Reads the PDF at `path` and extracts its text.
+pub fn extract_text(path: &Path) -> io::Result<String> {
+    extract_text_from_mem(&fs::read(path)?)
+}
 
-    path
+/// Extracts the text of a PDF, unreadable PDFs are reported as invalid data.
+pub fn extract_text_from_mem(bytes: &[u8]) -> io::Result<String> {
+    pdf_extract::extract_text_from_mem(bytes)
+        .map_err(|err| Reason::ExtractionFailed.error(format!("text extraction failed: {err}")))
 }
@@ -0,0 +1,192 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::process::{Outcome, Summary};
+
+//upper bounds of the processing latency histogram in seconds
+const LATENCY_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Counters of a long running process, exposed in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    inner: Mutex<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    documents: BTreeMap<&'static str, u64>,
+    renamed_by_type: BTreeMap<String, u64>,
+    failures_by_reason: BTreeMap<&'static str, u64>,
+    latency_buckets: Vec<u64>,
+    latency_sum: f64,
+    latency_count: u64,
+}
+
+impl Metrics {
+    /// Adds the outcomes of a finished run or batch.
+    pub fn record(&self, summary: &Summary) {
+        let mut counters = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        if counters.latency_buckets.is_empty() {
+            counters.latency_buckets = vec![0; LATENCY_BUCKETS.len()];
+        }
+
+        for file in &summary.files {
+            let outcome = match &file.outcome {
+                Outcome::Renamed { doc_type, .. } => {
+                    *counters
+                        .renamed_by_type
+                        .entry(doc_type.clone())
+                        .or_default() += 1;
+                    "renamed"
+                }
+                Outcome::Skipped { code, .. } => {
+                    *counters.failures_by_reason.entry(code.code()).or_default() += 1;
+                    "skipped"
+                }
+                Outcome::Filtered { .. } => "filtered",
+                Outcome::AlreadyRenamed { .. } => "already_renamed",
+            };
+            *counters.documents.entry(outcome).or_default() += 1;
+
+            //already renamed files are not opened, their latency would only skew the histogram
+            if !matches!(file.outcome, Outcome::AlreadyRenamed { .. }) {
+                let seconds = file.elapsed.as_secs_f64();
+                for (count, bound) in counters.latency_buckets.iter_mut().zip(LATENCY_BUCKETS) {
+                    if seconds <= *bound {
+                        *count += 1;
+                    }
+                }
+                counters.latency_sum += seconds;
+                counters.latency_count += 1;
+            }
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let counters = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP tr_pdf_rename_documents_total Documents looked at, by outcome.\n\
+             # TYPE tr_pdf_rename_documents_total counter"
+        );
+        for outcome in ["renamed", "skipped", "filtered", "already_renamed"] {
+            let count = counters.documents.get(outcome).copied().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "tr_pdf_rename_documents_total{{outcome=\"{outcome}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP tr_pdf_rename_renamed_total Renamed documents, by document type.\n\
+             # TYPE tr_pdf_rename_renamed_total counter"
+        );
+        for (doc_type, count) in &counters.renamed_by_type {
+            let _ = writeln!(
+                out,
+                "tr_pdf_rename_renamed_total{{doc_type=\"{}\"}} {count}",
+                escape_label(doc_type)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP tr_pdf_rename_parse_failures_total Skipped documents, by reason.\n\
+             # TYPE tr_pdf_rename_parse_failures_total counter"
+        );
+        for (reason, count) in &counters.failures_by_reason {
+            let _ = writeln!(
+                out,
+                "tr_pdf_rename_parse_failures_total{{reason=\"{reason}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP tr_pdf_rename_processing_seconds Time spent reading, parsing and renaming a document.\n\
+             # TYPE tr_pdf_rename_processing_seconds histogram"
+        );
+        for (i, bound) in LATENCY_BUCKETS.iter().enumerate() {
+            let count = counters.latency_buckets.get(i).copied().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "tr_pdf_rename_processing_seconds_bucket{{le=\"{bound}\"}} {count}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "tr_pdf_rename_processing_seconds_bucket{{le=\"+Inf\"}} {}\n\
+             tr_pdf_rename_processing_seconds_sum {}\n\
+             tr_pdf_rename_processing_seconds_count {}",
+            counters.latency_count, counters.latency_sum, counters.latency_count
+        );
+
+        out
+    }
+}
+
+/// Serves the metrics at `http://<addr>/metrics` from a background thread.
+pub fn serve(addr: impl ToSocketAddrs) -> io::Result<Arc<Metrics>> {
+    let listener = TcpListener::bind(addr)?;
+    let metrics = Arc::new(Metrics::default());
+
+    let served = Arc::clone(&metrics);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            //a broken scrape must not stop the server
+            if let Err(err) = respond(stream, &served) {
+                eprintln!("Serving metrics failed: {err}");
+            }
+        }
+    });
+
+    Ok(metrics)
+}
+
+fn respond(stream: TcpStream, metrics: &Metrics) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    //skip the headers, the request has no body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+        _ => ("404 Not Found", "not found, try /metrics\n".to_string()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,1174 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+use crate::parser::{known_doc_types, Amount, PdfData};
+
+/// Template the new filenames are built from unless another one is configured.
+pub const DEFAULT_TEMPLATE: &str = "{date}_{type}_{asset}";
+
+/// Folder layout of the archive unless another one is configured.
+pub const DEFAULT_FOLDER_LAYOUT: &str = "{year}/{type}";
+
+/// A value that can be used in a naming template as `{token}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    /// Date as `yyyy_mm_dd`.
+    Date,
+    /// Four digit year of the date.
+    Year,
+    /// Two digit month of the date.
+    Month,
+    /// Two digit day of the date.
+    Day,
+    /// Document type, e.g. `Dividende`.
+    Type,
+    /// Name of the security.
+    Asset,
+    /// ISIN of the security, empty if the document has none.
+    Isin,
+    /// Total amount like `25_00EUR`, empty if the document has none.
+    Amount,
+    /// Number of shares like `3_512Stk`, empty if the document has none.
+    Quantity,
+    /// Year a tax document refers to, empty if the document has none.
+    TaxYear,
+    /// First day of the period a statement covers as `yyyy_mm_dd`, empty if it has none.
+    PeriodStart,
+    /// Last day of the period a statement covers as `yyyy_mm_dd`, empty if it has none.
+    PeriodEnd,
+    /// `1`, `2`, ... for documents that would otherwise get the same name.
+    Counter,
+    /// Filename before renaming, e.g. `Abrechnung.pdf`.
+    Orig,
+    /// Filename before renaming without `.pdf`.
+    OrigStem,
+}
+
+const TOKENS: &[(&str, Token)] = &[
+    ("date", Token::Date),
+    ("year", Token::Year),
+    ("month", Token::Month),
+    ("day", Token::Day),
+    ("type", Token::Type),
+    ("asset", Token::Asset),
+    ("isin", Token::Isin),
+    ("amount", Token::Amount),
+    ("quantity", Token::Quantity),
+    ("tax_year", Token::TaxYear),
+    ("period_start", Token::PeriodStart),
+    ("period_end", Token::PeriodEnd),
+    ("counter", Token::Counter),
+    ("orig", Token::Orig),
+    ("orig_stem", Token::OrigStem),
+];
+
+/// Character between the parts of generated names, it replaces the underscores of the
+/// template and of values like the date.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Separator {
+    /// `2024_01_31_Dividende`
+    #[default]
+    Underscore,
+    /// `2024-01-31-Dividende`
+    Hyphen,
+    /// `2024 01 31 Dividende`
+    Space,
+}
+
+impl Separator {
+    /// The character itself.
+    pub fn as_char(self) -> char {
+        match self {
+            Separator::Underscore => '_',
+            Separator::Hyphen => '-',
+            Separator::Space => ' ',
+        }
+    }
+}
+
+impl FromStr for Separator {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Separator, String> {
+        match name {
+            "underscore" | "_" => Ok(Separator::Underscore),
+            "hyphen" | "-" => Ok(Separator::Hyphen),
+            "space" | " " => Ok(Separator::Space),
+            _ => Err(format!(
+                "unknown separator {name:?}, known separators: underscore, hyphen, space"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Separator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Separator, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Token(Token),
+}
+
+/// A naming template like `{date}_{type}_{asset}`, the new file stem (without `.pdf`) is built
+/// by replacing the tokens with the values of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    parts: Vec<Part>,
+    separator: char,
+    doc_type_labels: BTreeMap<String, String>,
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Template, String> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed '{{' in template {source:?}"))?;
+            let name = &rest[start + 1..start + end];
+            let token = TOKENS
+                .iter()
+                .find(|(token_name, _)| *token_name == name)
+                .map(|(_, token)| *token)
+                .ok_or_else(|| {
+                    let names: Vec<String> = TOKENS
+                        .iter()
+                        .map(|(name, _)| format!("{{{name}}}"))
+                        .collect();
+                    format!(
+                        "unknown token {{{name}}} in template {source:?}, known tokens: {}",
+                        names.join(", ")
+                    )
+                })?;
+            parts.push(Part::Token(token));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+
+        if parts.iter().any(|part| match part {
+            Part::Literal(literal) => literal.contains(['/', '\\', '}']),
+            Part::Token(_) => false,
+        }) {
+            return Err(format!(
+                "template {source:?} must not contain '/', '\\' or an unmatched '}}'"
+            ));
+        }
+        if !parts.iter().any(|part| matches!(part, Part::Token(_))) {
+            return Err(format!("template {source:?} contains no token"));
+        }
+
+        Ok(Template {
+            source: source.to_string(),
+            parts,
+            separator: '_',
+            doc_type_labels: BTreeMap::new(),
+        })
+    }
+}
+
+impl Default for Template {
+    fn default() -> Template {
+        DEFAULT_TEMPLATE.parse().expect("default template is valid")
+    }
+}
+
+impl<'de> Deserialize<'de> for Template {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Template, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Template {
+    /// Uses `separator` instead of the underscores of the template and the values.
+    pub fn with_separator(mut self, separator: char) -> Template {
+        self.separator = separator;
+        self
+    }
+
+    /// Character between the parts of the names.
+    pub fn separator(&self) -> char {
+        self.separator
+    }
+
+    /// The template with the separator instead of the underscores of its text, followed by
+    /// `|sep=` and the separator unless it is `_`, e.g. `{date}-{type}-{asset}|sep=-`. It is recorded
+    /// as the naming scheme of the renamed files, templates with the same scheme build the same
+    /// names.
+    pub fn scheme(&self) -> String {
+        let separator = self.separator.to_string();
+        let mut scheme: String = self
+            .parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(literal) => literal.replace('_', &separator),
+                Part::Token(token) => token.to_string(),
+            })
+            .collect();
+        //the separator is part of the values as well, e.g. of the date
+        if self.separator != '_' {
+            scheme.push_str("|sep=");
+            scheme.push(self.separator);
+        }
+        scheme
+    }
+
+    /// Uses the labels of `doc_type_labels` for `{type}` instead of the document types they are
+    /// keyed by.
+    pub fn with_doc_type_labels(mut self, doc_type_labels: BTreeMap<String, String>) -> Template {
+        self.doc_type_labels = doc_type_labels;
+        self
+    }
+
+    /// Labels used instead of document types.
+    pub fn doc_type_labels(&self) -> &BTreeMap<String, String> {
+        &self.doc_type_labels
+    }
+
+    /// The text `{type}` is replaced with for `doc_type`.
+    pub fn type_label(&self, doc_type: &str) -> String {
+        self.doc_type_labels
+            .get(doc_type)
+            .map_or(doc_type, String::as_str)
+            .replace('_', &self.separator.to_string())
+    }
+
+    /// Builds the file stem for `data` read from the file at `original`.
+    pub fn render(&self, data: &PdfData, original: &Path) -> String {
+        self.render_numbered(data, original, 1)
+    }
+
+    /// Like [`Template::render`] with `counter` as the value of `{counter}`.
+    pub fn render_numbered(&self, data: &PdfData, original: &Path, counter: u32) -> String {
+        let fields = Fields {
+            counter: Some(counter),
+            original: Some(
+                original
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            ..Fields::from(data)
+        };
+        self.render_fields(&fields)
+            .expect("all fields are known for parsed documents")
+    }
+
+    /// Checks if the template contains `token`.
+    pub fn contains(&self, token: Token) -> bool {
+        self.parts.contains(&Part::Token(token))
+    }
+
+    /// Builds the file stem from `fields`. Returns the first token without a value if the
+    /// fields are incomplete. Path separators in the values are replaced with the separator, so
+    /// e.g. the asset `A/B Holding` cannot put the file into another directory.
+    pub fn render_fields(&self, fields: &Fields) -> Result<String, Token> {
+        let separator = self.separator.to_string();
+        let mut stem = String::new();
+        for part in &self.parts {
+            let value = match part {
+                Part::Literal(literal) => {
+                    stem.push_str(&literal.replace('_', &separator));
+                    continue;
+                }
+                //the original name is kept as it is
+                Part::Token(token @ (Token::Orig | Token::OrigStem)) => {
+                    fields.value(*token).ok_or(*token)?
+                }
+                Part::Token(Token::Type) => {
+                    self.type_label(fields.doc_type.as_ref().ok_or(Token::Type)?)
+                }
+                Part::Token(token) => fields.value(*token).ok_or(*token)?.replace('_', &separator),
+            };
+            stem.push_str(&value.replace(['/', '\\'], &separator));
+        }
+        Ok(stem)
+    }
+
+    /// Creates a matcher that reads the fields back from names built with this template.
+    pub fn matcher(&self) -> NameMatcher {
+        //longest document types first, Wertpapierabrechnung_Sparplan before Wertpapierabrechnung
+        let mut doc_types: Vec<&str> = known_doc_types()
+            .map(|doc_type| {
+                self.doc_type_labels
+                    .get(doc_type)
+                    .map_or(doc_type, String::as_str)
+            })
+            .collect();
+        doc_types.sort_by_key(|doc_type| std::cmp::Reverse(doc_type.len()));
+        let separator = self.separator.to_string();
+        let doc_types: Vec<String> = doc_types
+            .into_iter()
+            .map(|doc_type| regex::escape(&doc_type.replace('_', &separator)))
+            .collect();
+        let sep = regex::escape(&separator);
+
+        let mut pattern = String::from("^");
+        let mut tokens = Vec::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => {
+                    pattern.push_str(&regex::escape(&literal.replace('_', &separator)))
+                }
+                Part::Token(token) => {
+                    tokens.push(*token);
+                    pattern.push('(');
+                    pattern.push_str(&match token {
+                        Token::Date => format!(r"\d{{4}}{sep}\d{{2}}{sep}\d{{2}}"),
+                        Token::Year => r"\d{4}".to_string(),
+                        Token::Month | Token::Day => r"\d{2}".to_string(),
+                        Token::Type => doc_types.join("|"),
+                        Token::Asset => ".*?".to_string(),
+                        Token::Isin => "[A-Z]{2}[A-Z0-9]{9}[0-9]|".to_string(),
+                        Token::Amount => format!(r"-?\d+{sep}\d{{2}}[A-Z]{{3}}|"),
+                        Token::Quantity => format!(r"\d+(?:{sep}\d+)?Stk|"),
+                        Token::TaxYear => r"\d{4}|".to_string(),
+                        Token::PeriodStart | Token::PeriodEnd => {
+                            format!(r"\d{{4}}{sep}\d{{2}}{sep}\d{{2}}|")
+                        }
+                        Token::Counter => r"\d+".to_string(),
+                        Token::Orig | Token::OrigStem => ".*?".to_string(),
+                    });
+                    pattern.push(')');
+                }
+            }
+        }
+        pattern.push('$');
+
+        NameMatcher {
+            regex: Regex::new(&pattern).expect("template pattern is valid"),
+            tokens,
+            separator: self.separator,
+            doc_type_labels: self.doc_type_labels.clone(),
+        }
+    }
+}
+
+/// Directories below the archive root a document is moved into, like `{year}/{type}`. Every
+/// directory is a [`Template`] of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderLayout {
+    source: String,
+    dirs: Vec<Template>,
+}
+
+impl FromStr for FolderLayout {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<FolderLayout, String> {
+        let dirs = source
+            .split(['/', '\\'])
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| {
+                dir.parse()
+                    .map_err(|err| format!("folder layout {source:?}: {err}"))
+            })
+            .collect::<Result<Vec<Template>, _>>()?;
+        if dirs.is_empty() {
+            return Err(format!("folder layout {source:?} contains no directory"));
+        }
+
+        Ok(FolderLayout {
+            source: source.to_string(),
+            dirs,
+        })
+    }
+}
+
+impl Default for FolderLayout {
+    fn default() -> FolderLayout {
+        DEFAULT_FOLDER_LAYOUT
+            .parse()
+            .expect("default folder layout is valid")
+    }
+}
+
+impl<'de> Deserialize<'de> for FolderLayout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FolderLayout, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for FolderLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FolderLayout {
+    /// Builds the relative directory for `data` read from the file at `original`, with the
+    /// separator and document type labels of `template`. Fails if a directory name would be
+    /// empty, `.` or `..` or contain a path separator, so a document cannot leave the archive.
+    pub fn render(
+        &self,
+        data: &PdfData,
+        original: &Path,
+        template: &Template,
+    ) -> Result<PathBuf, String> {
+        let mut path = PathBuf::new();
+        for dir in &self.dirs {
+            let name = dir
+                .clone()
+                .with_separator(template.separator())
+                .with_doc_type_labels(template.doc_type_labels().clone())
+                .render(data, original);
+            let trimmed = name.trim();
+            if trimmed.is_empty()
+                || trimmed == "."
+                || trimmed == ".."
+                || name.contains(['/', '\\', ':'])
+            {
+                return Err(format!(
+                    "invalid directory name {name:?} from the folder layout {}",
+                    self.source
+                ));
+            }
+            path.push(name);
+        }
+        Ok(path)
+    }
+}
+
+/// The values of the tokens, as read from a document or from an existing filename.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fields {
+    pub date: Option<NaiveDate>,
+    pub year: Option<i32>,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+    pub doc_type: Option<String>,
+    pub asset: Option<String>,
+    pub isin: Option<String>,
+    pub amount: Option<String>,
+    pub quantity: Option<String>,
+    /// Tax year, an empty string if the document has none.
+    pub tax_year: Option<String>,
+    /// First and last day of the period, empty strings if the document has none.
+    pub period_start: Option<String>,
+    pub period_end: Option<String>,
+    pub counter: Option<u32>,
+    /// Filename before renaming.
+    pub original: Option<String>,
+}
+
+impl From<&PdfData> for Fields {
+    fn from(data: &PdfData) -> Fields {
+        Fields {
+            date: Some(data.date),
+            year: Some(data.date.year()),
+            month: Some(data.date.month()),
+            day: Some(data.date.day()),
+            doc_type: Some(data.doc_type.clone()),
+            asset: Some(data.asset.clone()),
+            isin: Some(data.isin.clone().unwrap_or_default()),
+            amount: Some(data.amount.as_ref().map(amount_text).unwrap_or_default()),
+            quantity: Some(
+                data.quantity
+                    .as_ref()
+                    .map(|quantity| format!("{}Stk", quantity.replace('.', "_")))
+                    .unwrap_or_default(),
+            ),
+            tax_year: Some(
+                data.tax_year
+                    .map(|tax_year| format!("{tax_year:04}"))
+                    .unwrap_or_default(),
+            ),
+            period_start: Some(period_text(data.period.map(|(start, _)| start))),
+            period_end: Some(period_text(data.period.map(|(_, end)| end))),
+            counter: Some(1),
+            original: None,
+        }
+    }
+}
+
+impl Fields {
+    /// The text a token is replaced with, `None` if the value is unknown.
+    pub fn value(&self, token: Token) -> Option<String> {
+        match token {
+            Token::Date => self.date.map(|date| date.format("%Y_%m_%d").to_string()),
+            Token::Year => self.year.map(|year| format!("{year:04}")),
+            Token::Month => self.month.map(|month| format!("{month:02}")),
+            Token::Day => self.day.map(|day| format!("{day:02}")),
+            Token::Type => self.doc_type.clone(),
+            Token::Asset => self.asset.clone(),
+            Token::Isin => self.isin.clone(),
+            Token::Amount => self.amount.clone(),
+            Token::Quantity => self.quantity.clone(),
+            Token::TaxYear => self.tax_year.clone(),
+            Token::PeriodStart => self.period_start.clone(),
+            Token::PeriodEnd => self.period_end.clone(),
+            Token::Counter => self.counter.map(|counter| counter.to_string()),
+            Token::Orig => self.original.clone(),
+            Token::OrigStem => self.original.as_deref().map(|original| {
+                original
+                    .strip_suffix(".pdf")
+                    .unwrap_or(original)
+                    .to_string()
+            }),
+        }
+    }
+}
+
+//a day of a period like the date, empty if there is no period
+fn period_text(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.format("%Y_%m_%d").to_string())
+        .unwrap_or_default()
+}
+
+//amount without separators that differ between locales, e.g. 25_00EUR or -1234_56EUR
+fn amount_text(amount: &Amount) -> String {
+    let sign = if amount.cents < 0 { "-" } else { "" };
+    let cents = amount.cents.unsigned_abs();
+    format!(
+        "{sign}{}_{:02}{}",
+        cents / 100,
+        cents % 100,
+        amount.currency
+    )
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = TOKENS
+            .iter()
+            .find(|(_, token)| token == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        write!(f, "{{{name}}}")
+    }
+}
+
+/// Reads the fields back from file stems built with a [`Template`].
+#[derive(Debug, Clone)]
+pub struct NameMatcher {
+    regex: Regex,
+    tokens: Vec<Token>,
+    separator: char,
+    doc_type_labels: BTreeMap<String, String>,
+}
+
+impl NameMatcher {
+    /// Returns the fields of `stem`, or `None` if it was not built with the template.
+    pub fn parse(&self, stem: &str) -> Option<Fields> {
+        let captures = self.regex.captures(stem)?;
+        let mut fields = Fields::default();
+
+        for (token, capture) in self.tokens.iter().zip(captures.iter().skip(1)) {
+            let value = capture?.as_str();
+            //the fields hold the values with underscores, as read from the documents
+            let normalized = || value.replace(self.separator, "_");
+            match token {
+                Token::Date => {
+                    let date = NaiveDate::parse_from_str(&normalized(), "%Y_%m_%d").ok()?;
+                    fields.date = Some(date);
+                    fields.year = Some(date.year());
+                    fields.month = Some(date.month());
+                    fields.day = Some(date.day());
+                }
+                Token::Year => fields.year = Some(value.parse().ok()?),
+                Token::Month => fields.month = Some(value.parse().ok()?),
+                Token::Day => fields.day = Some(value.parse().ok()?),
+                Token::Type => {
+                    let label = normalized();
+                    let doc_type = self
+                        .doc_type_labels
+                        .iter()
+                        .find(|(_, doc_type_label)| **doc_type_label == label)
+                        .map_or(label.clone(), |(doc_type, _)| doc_type.clone());
+                    fields.doc_type = Some(doc_type);
+                }
+                Token::Asset => fields.asset = Some(value.to_string()),
+                Token::Isin => fields.isin = Some(value.to_string()),
+                //the sign is a minus whatever the separator is
+                Token::Amount => {
+                    fields.amount = Some(match value.strip_prefix('-') {
+                        Some(cents) => format!("-{}", cents.replace(self.separator, "_")),
+                        None => normalized(),
+                    })
+                }
+                Token::Quantity => fields.quantity = Some(normalized()),
+                Token::TaxYear => fields.tax_year = Some(value.to_string()),
+                Token::PeriodStart => fields.period_start = Some(normalized()),
+                Token::PeriodEnd => fields.period_end = Some(normalized()),
+                Token::Counter => fields.counter = Some(value.parse().ok()?),
+                Token::Orig => fields.original = Some(value.to_string()),
+                Token::OrigStem => fields.original = Some(format!("{value}.pdf")),
+            }
+        }
+
+        //a complete date can also be assembled from year, month and day
+        if fields.date.is_none() {
+            if let (Some(year), Some(month), Some(day)) = (fields.year, fields.month, fields.day) {
+                fields.date = Some(NaiveDate::from_ymd_opt(year, month, day)?);
+            }
+        }
+
+        Some(fields)
+    }
+}
+
+/// How the asset name of a document is cleaned up before it becomes part of a filename.
+#[derive(Debug, Clone, Default)]
+pub struct NameRules {
+    /// Short forms of long asset names, looked up ignoring case.
+    pub abbreviations: BTreeMap<String, String>,
+    /// Transliterate umlauts and drop other non-ASCII characters.
+    pub ascii: bool,
+    /// Maximum number of characters of the asset name, unlimited if `None`.
+    pub max_asset_len: Option<usize>,
+    /// Remove boilerplate like "UCITS ETF" or "(Acc)" from asset names.
+    pub strip_boilerplate: bool,
+    /// Further words or phrases removed from asset names with `strip_boilerplate`.
+    pub stop_tokens: Vec<String>,
+}
+
+//phrases removed from asset names with --strip-boilerplate, wherever they are
+const STOP_TOKENS: &[&str] = &[
+    "UCITS ETF",
+    "UCITS",
+    "ETF",
+    "(Acc)",
+    "(Dist)",
+    "Acc",
+    "Dist",
+    "Accumulating",
+    "Distributing",
+    "Registered Shares",
+    "Reg. Shs",
+    "Inhaber-Aktien",
+    "Namens-Aktien",
+    "o.N.",
+];
+
+//issuer names removed with --strip-boilerplate at the start of asset names
+const ISSUER_PREFIXES: &[&str] = &[
+    "iShares Core",
+    "iShares",
+    "Xtrackers",
+    "Vanguard",
+    "Amundi",
+    "SPDR",
+    "Lyxor",
+    "Invesco",
+    "HSBC",
+];
+
+//words of fund names that are dropped first when shortening an asset name
+const EXPENDABLE_WORDS: &[&str] = &["UCITS", "ETF"];
+
+impl NameRules {
+    /// Returns the asset name as used in filenames.
+    pub fn clean_name(&self, asset: &str) -> String {
+        let asset = asset.trim();
+        let asset = self
+            .abbreviations
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(asset))
+            .map(|(_, short)| short.clone())
+            .unwrap_or_else(|| {
+                if self.strip_boilerplate {
+                    self.without_boilerplate(asset)
+                } else {
+                    asset.to_string()
+                }
+            });
+
+        match self.max_asset_len {
+            Some(max_len) => truncate_asset(&asset, max_len),
+            None => asset,
+        }
+    }
+
+    //the asset without issuer prefix, stop tokens and share class codes like "1C", unchanged if
+    //nothing would be left
+    fn without_boilerplate(&self, asset: &str) -> String {
+        let words: Vec<&str> = asset.split_whitespace().collect();
+        let starts_with = |words: &[&str], phrase: &str| {
+            let phrase: Vec<&str> = phrase.split_whitespace().collect();
+            words.len() >= phrase.len()
+                && words
+                    .iter()
+                    .zip(&phrase)
+                    .all(|(word, phrase_word)| word.eq_ignore_ascii_case(phrase_word))
+                && !phrase.is_empty()
+        };
+
+        let mut rest = &words[..];
+        if let Some(prefix) = ISSUER_PREFIXES
+            .iter()
+            .find(|prefix| starts_with(rest, prefix))
+        {
+            rest = &rest[prefix.split_whitespace().count()..];
+        }
+
+        let mut kept = Vec::new();
+        while let Some((word, after)) = rest.split_first() {
+            let stop_token = STOP_TOKENS
+                .iter()
+                .copied()
+                .chain(self.stop_tokens.iter().map(String::as_str))
+                .filter(|token| starts_with(rest, token))
+                .max_by_key(|token| token.split_whitespace().count());
+            match stop_token {
+                Some(token) => rest = &rest[token.split_whitespace().count()..],
+                None if is_share_class_code(word) => rest = after,
+                None => {
+                    kept.push(*word);
+                    rest = after;
+                }
+            }
+        }
+
+        if kept.is_empty() {
+            asset.to_string()
+        } else {
+            kept.join(" ")
+        }
+    }
+
+    /// Returns the complete file stem as used for the file, ASCII only if configured.
+    pub fn clean_stem(&self, stem: String) -> String {
+        if !self.ascii || stem.is_ascii() {
+            return stem;
+        }
+
+        let mut ascii = String::with_capacity(stem.len());
+        for c in stem.chars() {
+            match c {
+                'ä' => ascii.push_str("ae"),
+                'ö' => ascii.push_str("oe"),
+                'ü' => ascii.push_str("ue"),
+                'Ä' => ascii.push_str("Ae"),
+                'Ö' => ascii.push_str("Oe"),
+                'Ü' => ascii.push_str("Ue"),
+                'ß' => ascii.push_str("ss"),
+                c if c.is_ascii() => ascii.push(c),
+                _ => {}
+            }
+        }
+        ascii
+    }
+}
+
+//share class codes of funds like "1C" or "2D"
+fn is_share_class_code(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 2 && bytes[0].is_ascii_digit() && matches!(bytes[1], b'C' | b'D')
+}
+
+/// Shortens an asset name to at most `max_len` characters at word boundaries (spaces or
+/// underscores), dropping expendable words like "UCITS" first and then words from the end.
+pub fn truncate_asset(asset: &str, max_len: usize) -> String {
+    if asset.chars().count() <= max_len {
+        return asset.to_string();
+    }
+
+    //every word with the separator in front of it, the first one has none
+    let mut words: Vec<&str> = Vec::new();
+    let mut start = 0;
+    for (i, c) in asset.char_indices().skip(1) {
+        if c == ' ' || c == '_' {
+            words.push(&asset[start..i]);
+            start = i;
+        }
+    }
+    words.push(&asset[start..]);
+
+    let len = |words: &[&str]| words.iter().map(|word| word.chars().count()).sum::<usize>();
+    while len(&words) > max_len {
+        let expendable = words.iter().skip(1).rposition(|word| {
+            let word = word.trim_start_matches([' ', '_']);
+            EXPENDABLE_WORDS
+                .iter()
+                .any(|expendable| expendable.eq_ignore_ascii_case(word))
+        });
+        match expendable {
+            Some(i) => words.remove(i + 1),
+            None if words.len() > 1 => words.remove(words.len() - 1),
+            None => break,
+        };
+    }
+
+    //a single word that is still too long is cut
+    words.concat().chars().take(max_len).collect()
+}
+
+/// Decides which files are named already and are not processed again.
+#[derive(Debug, Clone)]
+pub enum RenamedDetector {
+    /// Names the template could have built, possibly with a counter for duplicates.
+    Template(NameMatcher),
+    /// Filenames matching a user supplied regex.
+    Pattern(Regex),
+}
+
+impl RenamedDetector {
+    /// Detects names matching `pattern`, or names built with `template` without a pattern.
+    pub fn new(template: &Template, pattern: Option<&Regex>) -> RenamedDetector {
+        match pattern {
+            Some(pattern) => RenamedDetector::Pattern(pattern.clone()),
+            None => RenamedDetector::Template(template.matcher()),
+        }
+    }
+
+    /// Checks if the file `file_name` was renamed already.
+    pub fn is_renamed(&self, file_name: &str) -> bool {
+        match self {
+            RenamedDetector::Pattern(pattern) => pattern.is_match(file_name),
+            RenamedDetector::Template(_) => self.fields(file_name).is_some(),
+        }
+    }
+
+    /// The fields of `file_name` if it was built with the template, `None` with a pattern.
+    pub fn fields(&self, file_name: &str) -> Option<Fields> {
+        let RenamedDetector::Template(matcher) = self else {
+            return None;
+        };
+        let stem = file_name.strip_suffix(".pdf").unwrap_or(file_name);
+        //the counter added for duplicate names (_1, _2, ...) is not part of the template, it is
+        //stripped first as the asset would take it otherwise
+        let without_counter = stem
+            .rsplit_once(matcher.separator)
+            .filter(|(_, counter)| counter.parse::<u32>().is_ok())
+            .filter(|_| !matcher.tokens.contains(&Token::Counter))
+            .map(|(stem, _)| stem);
+        without_counter
+            .and_then(|stem| matcher.parse(stem))
+            .or_else(|| matcher.parse(stem))
+    }
+}
+
+/// Checks if `stem` is the generated name `expected`, possibly followed by the counter added for
+/// duplicate names (`_1`, `_2`, ... with `separator` in front of the number).
+pub fn matches_generated(stem: &str, expected: &str, separator: char) -> bool {
+    match stem.strip_prefix(expected) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix(separator)
+            .is_some_and(|counter| counter.parse::<u32>().is_ok()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(source: &str, separator: char) -> Template {
+        source
+            .parse::<Template>()
+            .unwrap()
+            .with_separator(separator)
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    //the fields of a dividend, with the year, month and day a matcher reads from the date
+    fn dividend() -> Fields {
+        Fields {
+            date: date(2024, 6, 15),
+            year: Some(2024),
+            month: Some(6),
+            day: Some(15),
+            doc_type: Some("Dividende".to_string()),
+            asset: Some("Apple Inc.".to_string()),
+            ..Fields::default()
+        }
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for source in [
+            "{date}_{unknown}",
+            "{date",
+            "{date}/{type}",
+            "{date}}",
+            "Dividende",
+        ] {
+            assert!(source.parse::<Template>().is_err(), "{source}");
+        }
+        assert!("{date} {type}".parse::<Template>().is_ok());
+    }
+
+    #[test]
+    fn default_names_round_trip() {
+        let template = Template::default();
+        let stem = template.render_fields(&dividend()).unwrap();
+        assert_eq!(stem, "2024_06_15_Dividende_Apple Inc.");
+        assert_eq!(template.matcher().parse(&stem), Some(dividend()));
+    }
+
+    #[test]
+    fn all_tokens_round_trip_with_a_separator() {
+        let template = template(
+            "{date}_{type}_{asset}_{isin}_{amount}_{quantity}_{tax_year}_{counter}__{orig_stem}",
+            '-',
+        );
+        let fields = Fields {
+            doc_type: Some("Wertpapierabrechnung_Sparplan".to_string()),
+            isin: Some("US0378331005".to_string()),
+            amount: Some("-1234_56EUR".to_string()),
+            quantity: Some("3_512Stk".to_string()),
+            tax_year: Some("2023".to_string()),
+            counter: Some(2),
+            original: Some("Abrechnung 1.pdf".to_string()),
+            ..dividend()
+        };
+        let stem = template.render_fields(&fields).unwrap();
+        assert_eq!(
+            stem,
+            "2024-06-15-Wertpapierabrechnung-Sparplan-Apple Inc.-US0378331005--1234-56EUR-3-512Stk-2023-2--Abrechnung 1"
+        );
+        assert_eq!(template.matcher().parse(&stem), Some(fields));
+    }
+
+    #[test]
+    fn empty_values_round_trip() {
+        let template = template("{date}_{isin}_{amount}_{asset}", '_');
+        let fields = Fields {
+            doc_type: None,
+            isin: Some(String::new()),
+            amount: Some(String::new()),
+            ..dividend()
+        };
+        let stem = template.render_fields(&fields).unwrap();
+        assert_eq!(stem, "2024_06_15___Apple Inc.");
+        assert_eq!(template.matcher().parse(&stem), Some(fields));
+    }
+
+    #[test]
+    fn date_parts_round_trip() {
+        let template = template("{day}.{month}.{year}_{type}_{asset}", '_');
+        let stem = template.render_fields(&dividend()).unwrap();
+        assert_eq!(stem, "15.06.2024_Dividende_Apple Inc.");
+        //the date is assembled from its parts
+        assert_eq!(template.matcher().parse(&stem), Some(dividend()));
+    }
+
+    #[test]
+    fn doc_type_labels_round_trip() {
+        let template = Template::default().with_doc_type_labels(BTreeMap::from([(
+            "Dividende".to_string(),
+            "Ausschüttung".to_string(),
+        )]));
+        let stem = template.render_fields(&dividend()).unwrap();
+        assert_eq!(stem, "2024_06_15_Ausschüttung_Apple Inc.");
+        assert_eq!(template.matcher().parse(&stem), Some(dividend()));
+    }
+
+    #[test]
+    fn missing_values_are_reported() {
+        let fields = Fields {
+            asset: None,
+            ..dividend()
+        };
+        assert_eq!(
+            Template::default().render_fields(&fields),
+            Err(Token::Asset)
+        );
+    }
+
+    #[test]
+    fn path_separators_in_values_are_replaced() {
+        let fields = Fields {
+            asset: Some("A/B Holding\\C".to_string()),
+            ..dividend()
+        };
+        assert_eq!(
+            template(DEFAULT_TEMPLATE, '-').render_fields(&fields),
+            Ok("2024-06-15-Dividende-A-B Holding-C".to_string())
+        );
+
+        let template = Template::default().with_doc_type_labels(BTreeMap::from([(
+            "Dividende".to_string(),
+            "Dividende/Ausschüttung".to_string(),
+        )]));
+        let data = PdfData {
+            asset: "A/B Holding".to_string(),
+            ..crate::parser::parse_pdf_data(
+                "Trade Republic\nDATUM 15.06.2024\nDIVIDENDE\nApple Inc.\nISIN: US0378331005",
+                None,
+            )
+            .unwrap()
+        };
+        let layout: FolderLayout = "{year}/{type}/{asset}".parse().unwrap();
+        assert_eq!(
+            layout.render(&data, Path::new("a.pdf"), &template),
+            Ok(PathBuf::from("2024/Dividende_Ausschüttung/A_B Holding"))
+        );
+    }
+
+    #[test]
+    fn renamed_files_are_detected() {
+        let detector = RenamedDetector::new(&Template::default(), None);
+        assert!(detector.is_renamed("2024_06_15_Dividende_Apple Inc..pdf"));
+        assert!(detector.is_renamed("2024_06_15_Dividende_Apple Inc._2.pdf"));
+        assert_eq!(
+            detector.fields("2024_06_15_Dividende_Apple Inc._2.pdf"),
+            Some(dividend())
+        );
+        assert!(!detector.is_renamed("Abrechnung.pdf"));
+        assert!(!detector.is_renamed("2024_06_15_Unbekannt_Apple Inc..pdf"));
+
+        let pattern = Regex::new(r"^Depot_").unwrap();
+        let detector = RenamedDetector::new(&Template::default(), Some(&pattern));
+        assert!(detector.is_renamed("Depot_2024.pdf"));
+        assert!(!detector.is_renamed("2024_06_15_Dividende_Apple Inc..pdf"));
+        assert_eq!(detector.fields("Depot_2024.pdf"), None);
+    }
+
+    #[test]
+    fn generated_names_with_counters() {
+        assert!(matches_generated("2024_Apple", "2024_Apple", '_'));
+        assert!(matches_generated("2024_Apple_3", "2024_Apple", '_'));
+        assert!(!matches_generated("2024_Apple_x", "2024_Apple", '_'));
+        assert!(!matches_generated("2024_Apple-3", "2024_Apple", '_'));
+        assert!(matches_generated("2024-Apple-3", "2024-Apple", '-'));
+    }
+
+    #[test]
+    fn truncation_drops_expendable_words_first() {
+        assert_eq!(
+            truncate_asset("iShares Core MSCI World UCITS ETF", 25),
+            "iShares Core MSCI World"
+        );
+        //only as many as needed, the last one first
+        assert_eq!(
+            truncate_asset("iShares Core MSCI World UCITS ETF", 29),
+            "iShares Core MSCI World UCITS"
+        );
+        //the first word is kept even if it is expendable
+        assert_eq!(truncate_asset("ETF Securities Gold", 10), "ETF");
+    }
+
+    #[test]
+    fn truncation_at_word_boundaries() {
+        assert_eq!(truncate_asset("Apple Inc.", 20), "Apple Inc.");
+        assert_eq!(truncate_asset("Apple Inc. Common Stock", 12), "Apple Inc.");
+        assert_eq!(truncate_asset("Apple_Inc._Common", 11), "Apple_Inc.");
+        assert_eq!(truncate_asset("Supercalifragilistic", 5), "Super");
+        assert_eq!(truncate_asset("Äpfel Öl", 5), "Äpfel");
+    }
+
+    #[test]
+    fn boilerplate_is_stripped() {
+        let rules = NameRules {
+            strip_boilerplate: true,
+            ..NameRules::default()
+        };
+        assert_eq!(
+            rules.clean_name("iShares Core MSCI World UCITS ETF USD (Acc)"),
+            "MSCI World USD"
+        );
+        assert_eq!(
+            rules.clean_name("Xtrackers MSCI World Swap 1C"),
+            "MSCI World Swap"
+        );
+        assert_eq!(
+            rules.clean_name("Apple Inc. Registered Shares o.N."),
+            "Apple Inc."
+        );
+        //nothing would be left
+        assert_eq!(rules.clean_name("iShares ETF"), "iShares ETF");
+
+        let rules = NameRules {
+            stop_tokens: vec!["USD".to_string()],
+            ..rules
+        };
+        assert_eq!(
+            rules.clean_name("iShares Core MSCI World UCITS ETF USD (Acc)"),
+            "MSCI World"
+        );
+    }
+
+    #[test]
+    fn abbreviations_before_other_rules() {
+        let rules = NameRules {
+            abbreviations: BTreeMap::from([(
+                "iShares Core MSCI World UCITS ETF".to_string(),
+                "World ETF".to_string(),
+            )]),
+            strip_boilerplate: true,
+            max_asset_len: Some(5),
+            ..NameRules::default()
+        };
+        assert_eq!(
+            rules.clean_name(" ishares core msci world ucits etf "),
+            "World"
+        );
+    }
+
+    #[test]
+    fn ascii_transliteration() {
+        let rules = NameRules {
+            ascii: true,
+            ..NameRules::default()
+        };
+        assert_eq!(
+            rules.clean_stem("2024_Müller Größe Äß €".to_string()),
+            "2024_Mueller Groesse Aess "
+        );
+        assert_eq!(
+            NameRules::default().clean_stem("Müller".to_string()),
+            "Müller"
+        );
+    }
+
+    #[test]
+    fn scheme_keeps_token_names() {
+        assert_eq!(
+            template("{date}_{tax_year}_{orig_stem}", '-').scheme(),
+            "{date}-{tax_year}-{orig_stem}|sep=-"
+        );
+        assert_eq!(template(DEFAULT_TEMPLATE, '_').scheme(), DEFAULT_TEMPLATE);
+    }
+
+    #[test]
+    fn scheme_tells_separators_apart() {
+        assert_ne!(
+            template("{date}_{type}", '-').scheme(),
+            template("{date}-{type}", '_').scheme()
+        );
+        //both build the same names
+        assert_eq!(
+            template("{date}_{type}", '-').scheme(),
+            template("{date}-{type}", '-').scheme()
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::process::{Outcome, Summary};
+
+/// Shows a desktop notification about renamed files and files that could not be parsed.
+#[cfg(feature = "desktop-notifications")]
+pub fn show(summary: &Summary) -> Result<(), String> {
+    let Some((title, body)) = message(summary) else {
+        return Ok(());
+    };
+
+    notify_rust::Notification::new()
+        .appname(env!("CARGO_PKG_NAME"))
+        .summary(&title)
+        .body(&body)
+        .show()
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+pub fn show(_summary: &Summary) -> Result<(), String> {
+    Ok(())
+}
+
+//title and body of the notification, nothing to tell if no file was renamed or skipped
+#[cfg_attr(not(feature = "desktop-notifications"), allow(dead_code))]
+fn message(summary: &Summary) -> Option<(String, String)> {
+    let renamed = summary.renamed();
+    let skipped = summary.skipped();
+
+    let title = match (renamed, skipped) {
+        (0, 0) => return None,
+        (renamed, 0) => format!("{renamed} document(s) renamed"),
+        (0, skipped) => format!("{skipped} document(s) could not be parsed"),
+        (renamed, skipped) => {
+            format!("{renamed} document(s) renamed, {skipped} could not be parsed")
+        }
+    };
+
+    let lines: Vec<String> = summary
+        .files
+        .iter()
+        .filter_map(|file| match &file.outcome {
+            Outcome::Renamed { new_name, .. } => Some(new_name.clone()),
+            Outcome::Skipped { reason, .. } => Some(format!("{}: {reason}", file.file)),
+            Outcome::AlreadyRenamed { .. } | Outcome::Filtered { .. } => None,
+        })
+        .collect();
+
+    Some((title, lines.join("\n")))
+}
@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use crate::{config::EmailConfig, process::Summary};
+
+/// Environment variable holding the SMTP user name.
+pub const USERNAME_VAR: &str = "TR_PDF_RENAME_SMTP_USERNAME";
+/// Environment variable holding the SMTP password.
+pub const PASSWORD_VAR: &str = "TR_PDF_RENAME_SMTP_PASSWORD";
+
+/// E-mails the summary to the configured recipients.
+#[cfg(feature = "email")]
+pub fn send(email: &EmailConfig, path: &Path, summary: &Summary) -> Result<(), String> {
+    use lettre::{
+        message::Mailbox,
+        transport::smtp::{authentication::Credentials, SmtpTransport},
+        Message, Transport,
+    };
+
+    use crate::config::SmtpTls;
+
+    let mailbox = |address: &str| {
+        address
+            .parse::<Mailbox>()
+            .map_err(|err| format!("invalid address {address:?}: {err}"))
+    };
+
+    let mut message = Message::builder()
+        .from(mailbox(&email.from)?)
+        .subject(format!(
+            "{}: {} renamed, {} skipped in {}",
+            env!("CARGO_PKG_NAME"),
+            summary.renamed(),
+            summary.skipped(),
+            path.display()
+        ));
+    for to in &email.to {
+        message = message.to(mailbox(to)?);
+    }
+    let message = message
+        .body(super::summary_text(path, summary))
+        .map_err(|err| err.to_string())?;
+
+    let mut transport = match email.tls {
+        SmtpTls::Starttls => SmtpTransport::starttls_relay(&email.server),
+        SmtpTls::Tls => SmtpTransport::relay(&email.server),
+        SmtpTls::None => Ok(SmtpTransport::builder_dangerous(&email.server)),
+    }
+    .map_err(|err| err.to_string())?;
+    if let Some(port) = email.port {
+        transport = transport.port(port);
+    }
+    if let (Ok(username), Ok(password)) = (std::env::var(USERNAME_VAR), std::env::var(PASSWORD_VAR))
+    {
+        transport = transport.credentials(Credentials::new(username, password));
+    }
+
+    transport
+        .build()
+        .send(&message)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "email"))]
+pub fn send(_email: &EmailConfig, _path: &Path, _summary: &Summary) -> Result<(), String> {
+    Err(format!(
+        "this build has no e-mail support, install with --features email (credentials in {USERNAME_VAR} and {PASSWORD_VAR})"
+    ))
+}
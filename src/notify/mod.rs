@@ -0,0 +1,93 @@
+mod desktop;
+mod email;
+mod webhook;
+
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    config::Config,
+    process::{Outcome, Summary},
+};
+
+/// Whether desktop notifications are available in this build.
+pub const DESKTOP_SUPPORTED: bool = cfg!(feature = "desktop-notifications");
+
+/// Sends the summary of a finished run (or watch mode batch) on `path` to every configured
+/// receiver. Failures are reported but do not fail the run, the files are renamed already.
+pub fn run_finished(config: &Config, path: &Path, summary: &Summary, desktop: bool) {
+    if let Some(webhook) = &config.webhook {
+        if let Err(err) = webhook::send(webhook, path, summary) {
+            eprintln!("Sending the summary to the webhook failed: {err}");
+        }
+    }
+
+    if let Some(email) = &config.email {
+        if let Err(err) = email::send(email, path, summary) {
+            eprintln!("Sending the summary e-mail failed: {err}");
+        }
+    }
+
+    if desktop {
+        if let Err(err) = desktop::show(summary) {
+            eprintln!("Showing the desktop notification failed: {err}");
+        }
+    }
+}
+
+//plain text summary listing renamed, skipped and already renamed files
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+fn summary_text(path: &Path, summary: &Summary) -> String {
+    let mut text = format!(
+        "{} renamed, {} skipped, {} already renamed in {}\n",
+        summary.renamed(),
+        summary.skipped(),
+        summary.already_renamed(),
+        path.display()
+    );
+
+    for (title, filter) in [
+        (
+            "Renamed",
+            (|o| matches!(o, Outcome::Renamed { .. })) as fn(&Outcome) -> bool,
+        ),
+        ("Skipped", |o| matches!(o, Outcome::Skipped { .. })),
+        ("Filtered out", |o| matches!(o, Outcome::Filtered { .. })),
+        ("Already renamed", |o| {
+            matches!(o, Outcome::AlreadyRenamed { .. })
+        }),
+    ] {
+        let files: Vec<_> = summary
+            .files
+            .iter()
+            .filter(|file| filter(&file.outcome))
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        let _ = writeln!(text, "\n{title}:");
+        for file in files {
+            let _ = match &file.outcome {
+                Outcome::Renamed {
+                    new_name,
+                    low_confidence_date,
+                    ..
+                } => writeln!(
+                    text,
+                    "  {} -> {new_name}{}",
+                    file.file,
+                    if *low_confidence_date {
+                        " (date taken from the filename, please check)"
+                    } else {
+                        ""
+                    }
+                ),
+                Outcome::Skipped { code, reason } | Outcome::Filtered { code, reason } => {
+                    writeln!(text, "  {}: {reason} ({code})", file.file)
+                }
+                Outcome::AlreadyRenamed { .. } => writeln!(text, "  {}", file.file),
+            };
+        }
+    }
+
+    text
+}
@@ -0,0 +1,52 @@
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+use serde::Serialize;
+
+use crate::{
+    config::WebhookConfig,
+    process::{FileOutcome, Summary},
+    reason::Reason,
+};
+
+//a hanging webhook must not block scheduled runs forever
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    tool: &'static str,
+    version: &'static str,
+    path: String,
+    renamed: usize,
+    already_renamed: usize,
+    skipped: usize,
+    filtered: usize,
+    reasons: BTreeMap<Reason, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<&'a [FileOutcome]>,
+}
+
+/// POSTs the summary as JSON to the configured URL.
+pub fn send(webhook: &WebhookConfig, path: &Path, summary: &Summary) -> Result<(), String> {
+    let payload = Payload {
+        tool: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        path: path.display().to_string(),
+        renamed: summary.renamed(),
+        already_renamed: summary.already_renamed(),
+        skipped: summary.skipped(),
+        filtered: summary.filtered(),
+        reasons: summary.reasons(),
+        files: webhook.include_files.then_some(summary.files.as_slice()),
+    };
+    let body = serde_json::to_string(&payload).map_err(|err| err.to_string())?;
+
+    ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .build()
+        .post(&webhook.url)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of comdirect: settlements, distributions and the monthly Finanzreport. They are
+/// printed in columns, e.g. the name with the WKN next to it and the ISIN in the line below.
+pub static COMDIRECT: Layout = Layout {
+    name: "comdirect",
+    names: &["comdirect"],
+    //the Finanzreport lists the transactions of all types, so it is checked first
+    doc_types: &[
+        DocType::fixed("Finanzreport", "Konto", "Finanzreport"),
+        DocType::security("Dividendengutschrift", "Dividende"),
+        DocType::security("Ertragsgutschrift", "Dividende"),
+        DocType::security("Sparplan", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("Wertpapierkauf", "Wertpapierabrechnung"),
+        DocType::security("Wertpapierverkauf", "Wertpapierabrechnung"),
+        DocType::security("Wertpapierabrechnung", "Wertpapierabrechnung"),
+    ],
+    //"iShsIII-Core MSCI World U.ETF   A0RPWH" above "Registered Shares USD (Acc) o.N.   IE00B4L5Y983"
+    name_lines: &[NameLine::Above, NameLine::Same],
+    name_labels: &[],
+    //"IBAN   Valuta   Zu Ihren Lasten vor Steuern" above "DE12 ...   14.03.2024   EUR 345,93"
+    amount_labels: &["Zu Ihren Lasten", "Zu Ihren Gunsten"],
+    amount_in_columns: true,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/comdirect_buy.txt
+    const PURCHASE: &str = "comdirect bank AG 25449 Quickborn\n\
+Wertpapierkauf\n\
+Geschaeftstag : 12.03.2024 Handelszeit : 09:04 Uhr\n\
+Wertpapier-Bezeichnung                     WPKNR/ISIN\n\
+iShsIII-Core MSCI World U.ETF              A0RPWH\n\
+Registered Shares USD (Acc) o.N.           IE00B4L5Y983\n\
+St.   3,512   EUR   98,50\n\
+Kurswert : EUR 345,93\n\
+IBAN                    Valuta       Zu Ihren Lasten vor Steuern\n\
+XX00REDACTEDIBAN         14.03.2024   EUR 345,93";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "comdirect");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "iShsIII-Core MSCI World U.ETF");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
@@ -0,0 +1,58 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of Consorsbank and the former Cortal Consors: settlements, distributions and tax
+/// notices.
+pub static CONSORSBANK: Layout = Layout {
+    name: "Consorsbank",
+    names: &["Consorsbank", "Cortal Consors"],
+    //the headings are printed in capitals
+    doc_types: &[
+        DocType::security("DIVIDENDENGUTSCHRIFT", "Dividende"),
+        DocType::security("ERTRAGSGUTSCHRIFT", "Dividende"),
+        DocType::security("Steuermitteilung", "Steuermitteilung"),
+        DocType::security("STEUERMITTEILUNG", "Steuermitteilung"),
+        DocType::security("SPARPLAN", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("VERKAUF", "Wertpapierabrechnung"),
+        DocType::security("KAUF", "Wertpapierabrechnung"),
+        DocType::security("Wertpapierabrechnung", "Wertpapierabrechnung"),
+    ],
+    //"iSh.Core MSCI World U.ETF A0RPWH IE00B4L5Y983" below "Wertpapier WKN ISIN"
+    name_lines: &[NameLine::Same, NameLine::Below],
+    name_labels: &[],
+    amount_labels: &[
+        "Betrag zu Ihren Lasten",
+        "Betrag zu Ihren Gunsten",
+        "Netto zu Ihren Gunsten",
+    ],
+    amount_in_columns: false,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/consorsbank_buy.txt
+    const PURCHASE: &str = "Consorsbank 90318 Nuernberg\n\
+KAUF\n\
+Wertpapier WKN ISIN\n\
+iSh.Core MSCI World U.ETF A0RPWH IE00B4L5Y983\n\
+Registered Shs USD (Acc) o.N.\n\
+Einheit Umsatz\n\
+ST 3,512\n\
+Kurswert EUR 345,93\n\
+Handelstag 12.03.2024\n\
+Betrag zu Ihren Lasten EUR 345,93\n\
+Valuta 14.03.2024";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "Consorsbank");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "iSh.Core MSCI World U.ETF");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
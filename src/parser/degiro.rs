@@ -0,0 +1,147 @@
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use super::{BrokerParser, DocType, Layout, NameLine, PdfData, Trace};
+
+//the Dutch and English month names, the ones spelled the same in both languages only once
+const MONTHS: &[(&str, u32)] = &[
+    ("januari", 1),
+    ("january", 1),
+    ("februari", 2),
+    ("february", 2),
+    ("maart", 3),
+    ("march", 3),
+    ("april", 4),
+    ("mei", 5),
+    ("may", 5),
+    ("juni", 6),
+    ("june", 6),
+    ("juli", 7),
+    ("july", 7),
+    ("augustus", 8),
+    ("august", 8),
+    ("september", 9),
+    ("oktober", 10),
+    ("october", 10),
+    ("november", 11),
+    ("december", 12),
+];
+
+//dates as written by DEGIRO, e.g. "31-12-2024" or "31 december 2024"
+static DATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    let months: Vec<&str> = MONTHS.iter().map(|(name, _)| *name).collect();
+    Regex::new(&format!(
+        r"(?i)\b(\d{{1,2}})(?:-(\d{{1,2}})-|\s+({})\s+)(\d{{4}})\b",
+        months.join("|")
+    ))
+    .unwrap()
+});
+
+//the layout of the English and Dutch documents, once their dates are rewritten
+static LAYOUT: Layout = Layout {
+    name: "DEGIRO",
+    names: &["DEGIRO"],
+    doc_types: &[
+        DocType::fixed("Jaaroverzicht", "Depot", "Jahresübersicht"),
+        DocType::fixed("Annual Report", "Depot", "Jahresübersicht"),
+        DocType::fixed("Annual Overview", "Depot", "Jahresübersicht"),
+        DocType::fixed("Transactieoverzicht", "Wertpapierabrechnung", "Übersicht"),
+        DocType::fixed("Transactions", "Wertpapierabrechnung", "Übersicht"),
+        DocType::security("Dividend", "Dividende"),
+    ],
+    name_lines: &[NameLine::Same, NameLine::Above],
+    name_labels: &["Product"],
+    amount_labels: &["Totaal", "Total"],
+    amount_in_columns: false,
+};
+
+/// Documents of DEGIRO: annual reports, transaction overviews and dividends in English or Dutch.
+/// Documents of flatex name their bank flatexDEGIRO as well, they are not DEGIRO documents.
+pub struct Degiro;
+
+impl BrokerParser for Degiro {
+    fn name(&self) -> &'static str {
+        "DEGIRO"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.match_indices("DEGIRO")
+            .any(|(i, _)| !text[..i].to_lowercase().ends_with("flatex"))
+    }
+
+    fn parse(&self, text: &str, file_name: Option<&str>, trace: &mut Trace) -> Option<PdfData> {
+        let text = rewrite_dates(text, trace);
+        LAYOUT.parse(&text, file_name, trace)
+    }
+
+    fn doc_types(&self) -> Vec<&'static str> {
+        LAYOUT.doc_types()
+    }
+}
+
+//rewrite the dates as dd.mm.yyyy, the format all other brokers use
+fn rewrite_dates(text: &str, trace: &mut Trace) -> String {
+    DATE_RE
+        .replace_all(text, |captures: &Captures| {
+            let month = match captures.get(2) {
+                Some(month) => month.as_str().parse().unwrap_or_default(),
+                None => {
+                    let name = captures[3].to_lowercase();
+                    MONTHS
+                        .iter()
+                        .find(|(month_name, _)| *month_name == name)
+                        .map_or(0, |(_, month)| *month)
+                }
+            };
+            let rewritten = format!("{}.{month:02}.{}", &captures[1], &captures[4]);
+            trace.note("date", || format!("read {:?} as {rewritten}", &captures[0]));
+            rewritten
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/degiro_annual.txt
+    const ANNUAL_REPORT: &str = "DEGIRO\n\
+flatexDEGIRO Bank Dutch Branch\n\
+Jaaroverzicht 2024\n\
+Periode 01-01-2024 - 31-12-2024\n\
+Datum 15 januari 2025\n\
+Product ISIN\n\
+iShares Core MSCI World IE00B4L5Y983";
+
+    #[test]
+    fn annual_report() {
+        let data = parse_pdf_data(ANNUAL_REPORT, None).unwrap();
+        assert_eq!(data.broker, "DEGIRO");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        assert_eq!(data.doc_type, "Depot");
+        assert_eq!(data.asset, "Jahresübersicht");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+
+    #[test]
+    fn flatex_documents_are_not_degiro() {
+        let text = "flatexDEGIRO Bank AG\nWertpapierabrechnung Kauf";
+        assert!(!Degiro.detect(text));
+        assert!(Degiro.detect(ANNUAL_REPORT));
+    }
+
+    #[test]
+    fn dates_rewritten_as_dd_mm_yyyy() {
+        assert_eq!(
+            rewrite_dates(
+                "01-01-2024 - 31-12-2024, 15 januari 2025, 3 March 2024",
+                &mut Trace::default()
+            ),
+            "01.01.2024 - 31.12.2024, 15.01.2025, 3.03.2024"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of DKB: settlements, dividends and account statements.
+pub static DKB: Layout = Layout {
+    name: "DKB",
+    names: &["Deutsche Kreditbank", "DKB AG", "DKB-Broker"],
+    //statements list the transactions of all types, so they are checked first
+    doc_types: &[
+        DocType::fixed("Kontoauszug", "Konto", "Auszug"),
+        DocType::security("Dividendengutschrift", "Dividende"),
+        DocType::security("Ausschüttung", "Dividende"),
+        DocType::security("Sparplan", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("Wertpapier Abrechnung", "Wertpapierabrechnung"),
+    ],
+    //"Stück 3,512 ISHSIII-CORE MSCI WORLD U.ETF IE00B4L5Y983 (A0RPWH)"
+    name_lines: &[NameLine::Same, NameLine::Below],
+    name_labels: &[],
+    //"Ausmachender Betrag 345,93- EUR", the sign is behind the amount
+    amount_labels: &["Ausmachender Betrag"],
+    amount_in_columns: false,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/dkb_buy.txt
+    const PURCHASE: &str = "DKB AG Taubenstrasse 7-9 10117 Berlin\n\
+Wertpapier Abrechnung Kauf\n\
+Nominale Wertpapierbezeichnung ISIN (WKN)\n\
+Stück 3,512 ISHSIII-CORE MSCI WORLD U.ETF IE00B4L5Y983 (A0RPWH)\n\
+REGISTERED SHS USD (ACC) O.N.\n\
+Schlusstag/-Zeit 12.03.2024 09:04:00 Auftraggeber Max Mustermann\n\
+Ausmachender Betrag 345,93- EUR\n\
+Den Betrag buchen wir mit Valuta 14.03.2024 zu Lasten des Kontos 1234567890";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "DKB");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "ISHSIII-CORE MSCI WORLD U.ETF");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
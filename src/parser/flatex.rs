@@ -0,0 +1,51 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of flatex: settlements, distributions and the annual statements of the depot.
+pub static FLATEX: Layout = Layout {
+    name: "flatex",
+    names: &["flatex"],
+    //statements list the transactions of all types, so they are checked first
+    doc_types: &[
+        DocType::fixed("Jahresdepotauszug", "Depot", "Jahresauszug"),
+        DocType::fixed("Depotauszug", "Depot", "Auszug"),
+        DocType::security("Dividendengutschrift", "Dividende"),
+        DocType::security("Ertragsgutschrift", "Dividende"),
+        DocType::security("Ausschüttung", "Dividende"),
+        DocType::security("Sparplan", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("Sammelabrechnung", "Wertpapierabrechnung"),
+        DocType::security("Wertpapierabrechnung", "Wertpapierabrechnung"),
+    ],
+    //"Nr.123456789/1  Kauf  IS.C.MSCI WORLD (IE00B4L5Y983/A0RPWH)"
+    name_lines: &[NameLine::Same],
+    name_labels: &["Kauf", "Verkauf"],
+    //"Endbetrag : -345,93 EUR"
+    amount_labels: &["Endbetrag"],
+    amount_in_columns: false,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/flatex_buy.txt
+    const PURCHASE: &str =
+        "flatexDEGIRO Bank AG Omniturm Grosse Gallusstrasse 16-18 60312 Frankfurt\n\
+Sammelabrechnung (Wertpapierkauf/-verkauf)\n\
+Nr.123456789/1  Kauf  IS.C.MSCI WORLD (IE00B4L5Y983/A0RPWH)\n\
+Ausgefuehrt : 3,512 St. Kurswert : 345,93 EUR\n\
+Schlusstag : 12.03.2024\n\
+Valuta : 14.03.2024\n\
+Endbetrag : -345,93 EUR";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "flatex");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "IS.C.MSCI WORLD");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
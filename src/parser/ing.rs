@@ -0,0 +1,49 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of ING (Germany): settlements and distributions of securities.
+pub static ING: Layout = Layout {
+    name: "ING",
+    names: &["ING-DiBa", "ING Deutschland"],
+    doc_types: &[
+        DocType::security("Dividendengutschrift", "Dividende"),
+        DocType::security("Ertragsgutschrift", "Dividende"),
+        DocType::security("Sparplan", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("Wertpapierabrechnung", "Wertpapierabrechnung"),
+    ],
+    //"ISIN (WKN) IE00B4L5Y983 (A0RPWH)" followed by "Wertpapierbezeichnung iShsIII-Core MSCI ..."
+    name_lines: &[NameLine::Same, NameLine::Below],
+    name_labels: &["Wertpapierbezeichnung"],
+    //"Endbetrag zu Ihren Lasten EUR 345,93"
+    amount_labels: &["Endbetrag", "Gesamtbetrag"],
+    amount_in_columns: false,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/ing_buy.txt
+    const PURCHASE: &str = "ING-DiBa AG 60628 Frankfurt am Main\n\
+Datum: 14.03.2024\n\
+Wertpapierabrechnung Kauf\n\
+Ordernummer 12345678.001\n\
+ISIN (WKN) IE00B4L5Y983 (A0RPWH)\n\
+Wertpapierbezeichnung iShsIII-Core MSCI World U.ETF\n\
+Registered Shares USD (Acc) o.N.\n\
+Nominale Stueck 3,512\n\
+Ausfuehrungskurs 98,50 EUR\n\
+Kurswert EUR 345,93\n\
+Endbetrag zu Ihren Lasten EUR 345,93";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "ING");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 14).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "iShsIII-Core MSCI World U.ETF");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
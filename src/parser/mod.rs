@@ -0,0 +1,1620 @@
+mod comdirect;
+mod consorsbank;
+mod degiro;
+mod dkb;
+mod flatex;
+mod ing;
+mod plugin;
+mod scalable_capital;
+mod smartbroker;
+mod trade_republic;
+
+use std::{
+    fmt,
+    ops::Range,
+    str::FromStr,
+    sync::{LazyLock, OnceLock},
+};
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use comdirect::COMDIRECT;
+use consorsbank::CONSORSBANK;
+use degiro::Degiro;
+use dkb::DKB;
+use flatex::FLATEX;
+use ing::ING;
+use scalable_capital::SCALABLE_CAPITAL;
+use smartbroker::SMARTBROKER;
+use trade_republic::TradeRepublic;
+
+pub use plugin::{load_plugins, PLUGINS_SUPPORTED};
+
+/// Version of the parser rules, the date of the last change to a parser that gives documents
+/// other names or types. Two builds with the same rules name the same documents the same.
+pub const RULES_VERSION: &str = "2026-10-16";
+
+/// Information about a broker document found in its extracted text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PdfData {
+    /// Name of the broker that issued the document, e.g. `Trade Republic`.
+    pub broker: &'static str,
+    /// Date of the transaction or document.
+    pub date: NaiveDate,
+    /// Where the date was found.
+    pub date_source: DateSource,
+    /// All dates found, by source, in the order they are preferred by default.
+    pub dates: Vec<(DateSource, NaiveDate)>,
+    /// First and last day of the period a statement covers, e.g. `01.07.2025 bis 31.07.2025`.
+    pub period: Option<(NaiveDate, NaiveDate)>,
+    /// Document type, e.g. `Wertpapierabrechnung_Sparplan`.
+    pub doc_type: String,
+    /// Name of the security or a fixed description for documents without one.
+    pub asset: String,
+    /// ISIN of the security, if the document mentions one.
+    pub isin: Option<String>,
+    /// Total amount of the document (the GESAMT line), if it has one.
+    pub amount: Option<Amount>,
+    /// Amount before taxes and fees, e.g. of a dividend, if the document lists one.
+    pub gross: Option<Amount>,
+    /// Interest credited, e.g. the Cash Zinsen of a Sammelbeleg, if the document lists any.
+    pub interest: Option<Amount>,
+    /// Taxes withheld or refunded.
+    pub taxes: Taxes,
+    /// Whether shares were bought or sold, for settlements.
+    pub side: Option<Side>,
+    /// Price per share, if the document lists one.
+    pub price: Option<Amount>,
+    /// Fees charged, the sum of all fee lines, if the document lists any.
+    pub fees: Option<Amount>,
+    /// Gain realized by a sale, negative for a loss, if the document lists one.
+    pub gain: Option<Amount>,
+    /// Number of shares as a decimal like `3.512`, if the document lists one.
+    pub quantity: Option<String>,
+    /// Year a tax document refers to, which is usually not the year of its date.
+    pub tax_year: Option<i32>,
+    /// Securities held, listed by a Depotauszug.
+    pub positions: Vec<Position>,
+    /// Costs of a year, listed by an Ex-Post Kosteninformation.
+    pub costs: Costs,
+}
+
+impl PdfData {
+    /// The date found at `source`, e.g. the value date of a settlement.
+    pub fn date_of(&self, source: DateSource) -> Option<NaiveDate> {
+        self.dates
+            .iter()
+            .find(|(found_source, _)| *found_source == source)
+            .map(|(_, date)| *date)
+    }
+
+    /// Uses the first date found of the sources in `preference` instead of the default choice.
+    /// Keeps the date if none of them was found.
+    pub fn prefer_date(&mut self, preference: &[DateSource]) {
+        let preferred = preference
+            .iter()
+            .find_map(|&source| Some((source, self.date_of(source)?)));
+        if let Some((source, date)) = preferred {
+            self.date = date;
+            self.date_source = source;
+        }
+    }
+}
+
+/// Where the date of a document was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateSource {
+    /// Labeled as execution date, e.g. `Ausführung 12.03.2024`.
+    Execution,
+    /// Labeled as trade date of a settlement, e.g. `Handelstag 12.03.2024`.
+    Trade,
+    /// Labeled as booking date, e.g. `Buchungsdatum 13.03.2024`.
+    Booking,
+    /// Labeled as value date of a settlement, e.g. `Valuta 14.03.2024`.
+    Value,
+    /// Last day of the period a statement covers.
+    #[serde(rename = "period")]
+    PeriodEnd,
+    /// After the `DATUM` header of the document, sometimes the date of the letter.
+    Header,
+    /// The most plausible unlabeled date of the document, scored by nearby keywords, position
+    /// and distance to the other dates.
+    Any,
+    /// In the original filename, as the document contains no date.
+    FileName,
+    /// Found by a parser plugin.
+    #[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+    Plugin,
+}
+
+/// The order in which the dates of a document are preferred unless configured otherwise.
+pub const DEFAULT_DATE_PREFERENCE: &[DateSource] = &[
+    DateSource::Execution,
+    DateSource::Trade,
+    DateSource::Booking,
+    DateSource::Value,
+    DateSource::PeriodEnd,
+    DateSource::Header,
+    DateSource::Any,
+];
+
+impl FromStr for DateSource {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<DateSource, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "execution" => Ok(DateSource::Execution),
+            "trade" => Ok(DateSource::Trade),
+            "booking" => Ok(DateSource::Booking),
+            "value" => Ok(DateSource::Value),
+            "period" => Ok(DateSource::PeriodEnd),
+            "header" => Ok(DateSource::Header),
+            "any" => Ok(DateSource::Any),
+            _ => Err(format!(
+                "unknown date {name:?}, known dates: execution, trade, booking, value, period, header, any"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DateSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<DateSource, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl DateSource {
+    /// Checks if the date is a guess that should be checked by the user.
+    pub fn is_low_confidence(self) -> bool {
+        matches!(self, DateSource::FileName)
+    }
+}
+
+impl fmt::Display for DateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DateSource::Execution => "execution date",
+            DateSource::Trade => "trade date",
+            DateSource::Booking => "booking date",
+            DateSource::Value => "value date",
+            DateSource::PeriodEnd => "end of the period",
+            DateSource::Header => "DATUM header",
+            DateSource::Any => "best scored date in the document",
+            DateSource::FileName => "filename",
+            DateSource::Plugin => "date found by the plugin",
+        })
+    }
+}
+
+/// A security held, one line of the table of a Depotauszug.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub isin: String,
+    pub name: String,
+    /// Number of shares as a decimal like `3.512`.
+    pub quantity: String,
+    /// Market value at the date of the statement, if listed.
+    pub value: Option<Amount>,
+}
+
+/// Direction of a trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        })
+    }
+}
+
+/// The taxes listed in a document, negative amounts are withheld and positive ones refunded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Taxes {
+    /// Kapitalertragsteuer.
+    pub capital_gains: Option<Amount>,
+    /// Solidaritätszuschlag.
+    pub solidarity: Option<Amount>,
+    /// Kirchensteuer.
+    pub church: Option<Amount>,
+    /// Foreign withholding tax (Quellensteuer), usually in the currency of the payment.
+    pub withholding: Option<Amount>,
+}
+
+/// The yearly costs listed in an Ex-Post Kosteninformation, all amounts positive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Costs {
+    /// Costs of the securities themselves, e.g. the running costs of a fund.
+    pub product: Option<Amount>,
+    /// Costs of the services of the broker, e.g. order fees.
+    pub service: Option<Amount>,
+    /// Inducements the broker received from third parties, part of the product costs.
+    pub inducements: Option<Amount>,
+    /// Sum of all costs.
+    pub total: Option<Amount>,
+}
+
+impl Costs {
+    pub fn is_empty(&self) -> bool {
+        *self == Costs::default()
+    }
+}
+
+/// An amount of money in German notation like `1.234,56 EUR`, kept in cents.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Amount {
+    /// Amount in the smallest unit of the currency, negative for debits.
+    pub cents: i64,
+    /// ISO currency code, e.g. `EUR`.
+    pub currency: String,
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.cents < 0 { "-" } else { "" };
+        let cents = self.cents.unsigned_abs();
+        write!(
+            f,
+            "{sign}{}.{:02} {}",
+            cents / 100,
+            cents % 100,
+            self.currency
+        )
+    }
+}
+
+//amount with thousands separators and decimal comma followed by the currency, e.g. "-1.234,56 EUR"
+//or "1.234,56- EUR" with the sign behind, or following it, e.g. "EUR 1.234,56"
+static AMOUNT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(-?)\b(\d{1,3}(?:\.\d{3})*|\d+),(\d{2})([-+]?)\s*([A-Z]{3})\b|\b([A-Z]{3})\s+(-?)(\d{1,3}(?:\.\d{3})*|\d+),(\d{2})\b",
+    )
+    .unwrap()
+});
+
+//labels of the lines holding the amount before taxes, e.g. "Bruttoertrag 12,00 EUR"
+const GROSS_LABELS: &[&str] = &["Brutto", "BRUTTO", "Gross"];
+
+//labels of the lines holding interest, summed up if a document lists several
+const INTEREST_LABELS: &[&str] = &[
+    "Cash Zinsen",
+    "Geldmarkt Dividende",
+    "Zinsertrag",
+    "Zinsgutschrift",
+    "Habenzinsen",
+];
+
+//labels of the lines holding fees, summed up if a document lists several
+const FEE_LABELS: &[&str] = &[
+    "Fremdkostenzuschlag",
+    "Ordergebühr",
+    "Transaktionsgebühr",
+    "Gebühr",
+    "Provision",
+    "Courtage",
+    "Fremde Spesen",
+    "Fee",
+];
+
+//labels of the lines holding a realized gain or loss, "Gewinn " is not "Gewinnanteil" and
+//"Verlust " not "Verlustverrechnung"
+const GAIN_LABELS: &[&str] = &["Veräußerungsgewinn", "Realisierter Gewinn", "Gewinn "];
+const LOSS_LABELS: &[&str] = &["Veräußerungsverlust", "Realisierter Verlust", "Verlust "];
+
+//labels of the lines holding the price per share, "Kurs " is not "Kurswert"
+const PRICE_LABELS: &[&str] = &["Ausführungskurs", "Kurs ", "Kurs:", "Preis ", "Price "];
+
+//words of a sale and of a purchase, e.g. "Market-Order Verkauf", a sale is checked first. Whole
+//words only, "Verkaufsprovision" or "Kaufkraft" say nothing about the order
+static SELL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(?:verkauf|sell)\b").unwrap());
+static BUY_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(?:kauf|buy)\b").unwrap());
+
+//labels of the line naming the order, e.g. "Market-Order Kauf" or "Geschäftsart: Verkauf", which
+//is read before the rest of the text
+const ORDER_LABELS: &[&str] = &["Order", "Geschäftsart", "Transaktion", "Transaction"];
+
+//number of shares in German notation followed by "Stk." or "St.", e.g. "1.000 Stk." or
+//"3,512 St.", or following "Stück", "STK", "ST" or "St.", e.g. "STK 3,512"
+static QUANTITY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(?:(\d{1,3}(?:\.\d{3})*|\d+)(?:,(\d+))?\s*Stk?\.|(?:Stück|STK?|St\.)\s+(\d{1,3}(?:\.\d{3})*|\d+)(?:,(\d+))?\b)",
+    )
+    .unwrap()
+});
+
+//year a tax document refers to, e.g. "Steuerjahr 2023" or "für das Jahr 2023"
+static TAX_YEAR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:Steuerjahr|Kalenderjahr|Veranlagungsjahr|für das Jahr)\s*:?\s*(\d{4})\b")
+        .unwrap()
+});
+
+//labels of the dates preferred over the DATUM header, followed by the date on the same line
+static LABELED_DATE_RES: LazyLock<Vec<(DateSource, Regex)>> = LazyLock::new(|| {
+    [
+        (DateSource::Execution, r"Ausführung\w*|Execution(?: date)?"),
+        (
+            DateSource::Trade,
+            r"Handelstag|Handelsdatum|Schlusstag|Geschäftstag|Trade date",
+        ),
+        (DateSource::Booking, r"Buchung\w*|Booking(?: date)?"),
+        (
+            DateSource::Value,
+            r"Valuta\w*|Wertstellung|Zahltag|Value date|Pay(?:ment)? date",
+        ),
+    ]
+    .into_iter()
+    .map(|(source, labels)| {
+        let pattern = format!(r"(?i)\b(?:{labels})\b[^\d\n]{{0,30}}?({DATE_PATTERN})");
+        (source, Regex::new(&pattern).unwrap())
+    })
+    .collect()
+});
+
+//words in front of a date making it likely the date of the document
+const DATE_HINTS: &[&str] = &["datum", "date", " am ", "vom ", "per ", "stichtag"];
+
+//words in front of a date making it likely some other date, e.g. a birth date or period start
+const MISLEADING_DATE_HINTS: &[&str] = &[
+    "geburt", "geboren", "geb.", "birth", "born", "seit", "zeitraum", "period", "gültig", "von ",
+    "from ",
+];
+
+//a date as dd.mm.yyyy, "12. März 2025", "March 12, 2025" or with slashes, MM/DD/YYYY in English
+//documents and DD/MM/YYYY otherwise
+const DATE_PATTERN: &str = r"(?i:\b(?:\d{1,2}\.\d{1,2}\.\d{4}|\d{1,2}\.\s*(?:Januar|Februar|März|Maerz|April|Mai|Juni|Juli|August|September|Oktober|November|Dezember|Jan|Feb|Mär|Apr|Jun|Jul|Aug|Sep|Okt|Nov|Dez)\b\.?\s+\d{4}|(?:January|February|March|April|May|June|July|August|September|October|November|December|Jan|Feb|Mar|Apr|Jun|Jul|Aug|Sep|Sept|Oct|Nov|Dec)\b\.?\s+\d{1,2},?\s+\d{4}|\d{1,2}/\d{1,2}/\d{4})\b)";
+
+//a period like "01.07.2025 - 31.07.2025", "01.07.2025 bis 31.07.2025" or "from ... to ..."
+static PERIOD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)({DATE_PATTERN})\s*(?:-|–|bis(?: zum)?|to|until)\s*({DATE_PATTERN})"
+    ))
+    .unwrap()
+});
+
+//first three letters of the German and English month names
+const MONTH_PREFIXES: &[(&str, u32)] = &[
+    ("jan", 1),
+    ("feb", 2),
+    ("mär", 3),
+    ("mae", 3),
+    ("mar", 3),
+    ("apr", 4),
+    ("mai", 5),
+    ("may", 5),
+    ("jun", 6),
+    ("jul", 7),
+    ("aug", 8),
+    ("sep", 9),
+    ("okt", 10),
+    ("oct", 10),
+    ("nov", 11),
+    ("dez", 12),
+    ("dec", 12),
+];
+
+//words that only English documents contain a lot of
+const ENGLISH_WORDS: &[&str] = &[" the ", " and ", " of ", "date", "total"];
+//words that only German documents contain a lot of
+const GERMAN_WORDS: &[&str] = &[" der ", " die ", " und ", "datum", "gesamt"];
+
+//any date in one of the supported formats
+static ANY_DATE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(DATE_PATTERN).unwrap());
+
+//date in a filename, e.g. "2024-01-31", "2024_01_31", "20240131" or "31.01.2024"
+static FILE_NAME_DATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\D)(?:(\d{4})[-_.]?(\d{2})[-_.]?(\d{2})|(\d{2})\.(\d{2})\.(\d{4}))(?:\D|$)")
+        .unwrap()
+});
+
+/// Records which heuristics fired while parsing a document.
+#[derive(Debug, Default)]
+pub struct Trace {
+    enabled: bool,
+    /// The recorded steps as (topic, description).
+    pub steps: Vec<(&'static str, String)>,
+}
+
+impl Trace {
+    /// Creates a trace that records steps.
+    pub fn enabled() -> Trace {
+        Trace {
+            enabled: true,
+            steps: Vec::new(),
+        }
+    }
+
+    fn note(&mut self, topic: &'static str, step: impl FnOnce() -> String) {
+        if self.enabled {
+            self.steps.push((topic, step()));
+        }
+    }
+}
+
+/// A parser for the documents of one broker.
+pub trait BrokerParser: Sync {
+    /// Name of the broker, e.g. `Trade Republic`.
+    fn name(&self) -> &'static str;
+
+    /// Checks if `text` is a document of this broker, e.g. by the name of the broker in it.
+    fn detect(&self, text: &str) -> bool;
+
+    /// Finds date, document type, asset and ISIN in a document of this broker, recording every
+    /// decision in `trace`. Returns `None` if the text is none of the known document types.
+    fn parse(&self, text: &str, file_name: Option<&str>, trace: &mut Trace) -> Option<PdfData>;
+
+    /// Lists the document type labels this parser can produce.
+    fn doc_types(&self) -> Vec<&'static str>;
+}
+
+//the supported brokers, checked in this order. Documents not naming any of them, e.g. redacted
+//ones, are tried with the first
+static BROKERS: &[&dyn BrokerParser] = &[
+    &TradeRepublic,
+    &SCALABLE_CAPITAL,
+    &ING,
+    &COMDIRECT,
+    &DKB,
+    &Degiro,
+    &FLATEX,
+    &CONSORSBANK,
+    &SMARTBROKER,
+];
+
+/// Finds date, document type, asset and ISIN in the text extracted from a PDF. If the text
+/// contains no date, it is taken from the `file_name` of the PDF as a last resort.
+/// Returns `None` if the text is not a known document of a supported broker.
+pub fn parse_pdf_data(text: &str, file_name: Option<&str>) -> Option<PdfData> {
+    parse_pdf_data_traced(text, file_name, &mut Trace::default())
+}
+
+/// Whether the text extracted from a PDF, or else its `file_name`, has a date, to tell why
+/// [`parse_pdf_data`] found no document.
+pub fn has_date(text: &str, file_name: Option<&str>) -> bool {
+    extract_document_dates(text, file_name, &mut Trace::default()).is_some()
+}
+
+/// Like [`parse_pdf_data`], recording every decision in `trace`.
+pub fn parse_pdf_data_traced(
+    text: &str,
+    file_name: Option<&str>,
+    trace: &mut Trace,
+) -> Option<PdfData> {
+    let broker = match brokers().find(|broker| broker.detect(text)) {
+        Some(broker) => {
+            trace.note("broker", || format!("{} document", broker.name()));
+            broker
+        }
+        None => {
+            let broker = BROKERS[0];
+            trace.note("broker", || {
+                format!("no broker named, trying {}", broker.name())
+            });
+            broker
+        }
+    };
+    broker.parse(text, file_name, trace)
+}
+
+//parsers loaded at runtime, see load_plugins
+static PLUGINS: OnceLock<Vec<&'static dyn BrokerParser>> = OnceLock::new();
+
+//the built-in brokers followed by the plugins
+fn brokers() -> impl Iterator<Item = &'static dyn BrokerParser> {
+    BROKERS
+        .iter()
+        .chain(PLUGINS.get().into_iter().flatten())
+        .copied()
+}
+
+//the dates found in a document
+struct Dates {
+    //first and last day of the period a statement covers
+    period: Option<(NaiveDate, NaiveDate)>,
+    //the dates of all sources, the first is used unless configured otherwise
+    dates: Vec<(DateSource, NaiveDate)>,
+}
+
+//find the period and the dates of a document, the date in `file_name` if the text has none.
+//None if there is no date at all
+fn extract_document_dates(text: &str, file_name: Option<&str>, trace: &mut Trace) -> Option<Dates> {
+    let english = is_english(text);
+    if english {
+        trace.note("date", || {
+            "English document, dates with slashes are read as MM/DD/YYYY".to_string()
+        });
+    }
+    let period = extract_period(text, english, trace);
+
+    let mut dates = extract_dates(text, english, period, trace);
+    if dates.is_empty() {
+        let date = file_name.and_then(|file_name| date_from_file_name(file_name, trace))?;
+        dates.push((DateSource::FileName, date));
+    }
+    let (date_source, date) = dates[0];
+    trace.note("date", || format!("using the {date_source} {date}"));
+
+    Some(Dates { period, dates })
+}
+
+//a document type of a bank, identified by a keyword
+#[derive(Debug)]
+struct DocType {
+    keyword: &'static str,
+    label: &'static str,
+    //description used as asset of documents without a security, e.g. statements
+    fixed_asset: Option<&'static str>,
+}
+
+impl DocType {
+    //a document about a security, its name is found next to the ISIN
+    const fn security(keyword: &'static str, label: &'static str) -> DocType {
+        DocType {
+            keyword,
+            label,
+            fixed_asset: None,
+        }
+    }
+
+    //a document without a security, named with a fixed description
+    const fn fixed(keyword: &'static str, label: &'static str, asset: &'static str) -> DocType {
+        DocType {
+            keyword,
+            label,
+            fixed_asset: Some(asset),
+        }
+    }
+}
+
+//where the name of the security is found, relative to the first line with its ISIN
+#[derive(Debug, Clone, Copy)]
+enum NameLine {
+    Same,
+    Above,
+    Below,
+}
+
+//the layout of the documents of a bank that lists every security with its ISIN, which is enough
+//to parse most brokers without code of their own
+#[derive(Debug)]
+struct Layout {
+    name: &'static str,
+    //names of the bank, one of them in any case identifies its documents
+    names: &'static [&'static str],
+    //the document types, checked in this order
+    doc_types: &'static [DocType],
+    //lines tried for the name of the security, in this order
+    name_lines: &'static [NameLine],
+    //words in front of the name, e.g. "Kauf" or "Wertpapierbezeichnung"
+    name_labels: &'static [&'static str],
+    //labels of the lines holding the total amount, checked in this order
+    amount_labels: &'static [&'static str],
+    //the amounts are printed in a table, below their labels
+    amount_in_columns: bool,
+}
+
+impl BrokerParser for Layout {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.names
+            .iter()
+            .any(|name| text.contains(&name.to_lowercase()))
+    }
+
+    fn parse(&self, text: &str, file_name: Option<&str>, trace: &mut Trace) -> Option<PdfData> {
+        let Dates { period, dates } = extract_document_dates(text, file_name, trace)?;
+
+        let Some(doc_type) = self
+            .doc_types
+            .iter()
+            .find(|doc_type| text.contains(doc_type.keyword))
+        else {
+            trace.note("doc type", || {
+                let keywords: Vec<&str> = self
+                    .doc_types
+                    .iter()
+                    .map(|doc_type| doc_type.keyword)
+                    .collect();
+                format!("none of the keywords {keywords:?} found")
+            });
+            return None;
+        };
+        trace.note("doc type", || {
+            format!("keyword {:?} -> {}", doc_type.keyword, doc_type.label)
+        });
+
+        let isin = extract_isin(text, trace);
+        let asset = match (doc_type.fixed_asset, &isin) {
+            (Some(asset), _) => {
+                trace.note("asset", || format!("fixed description {asset:?}"));
+                asset.to_string()
+            }
+            (None, Some(isin)) => self.extract_asset(text, isin, trace),
+            (None, None) => {
+                trace.note("asset", || "no ISIN to find the name next to".to_string());
+                String::new()
+            }
+        };
+        let amount = if self.amount_in_columns {
+            extract_column_amount(text, self.amount_labels, trace)
+        } else {
+            extract_amount(text, self.amount_labels, "amount", trace)
+        };
+        let gross = extract_amount(text, GROSS_LABELS, "gross", trace);
+        let interest = extract_interest(text, trace);
+        let taxes = extract_taxes(text, trace);
+        let costs = if doc_type.label == "Kosteninformation" {
+            extract_costs(text, trace)
+        } else {
+            Costs::default()
+        };
+        let side = extract_side(text, doc_type.label, trace);
+        let price = extract_price(text, trace);
+        let fees = extract_fees(text, trace);
+        let gain = extract_gain(text, trace);
+        let quantity = extract_quantity(text, trace);
+        let tax_year = extract_tax_year(text, trace);
+
+        let (date_source, date) = dates[0];
+        Some(PdfData {
+            broker: self.name,
+            date,
+            date_source,
+            dates,
+            period,
+            doc_type: doc_type.label.to_string(),
+            asset,
+            isin,
+            amount,
+            gross,
+            interest,
+            taxes,
+            side,
+            price,
+            fees,
+            gain,
+            quantity,
+            tax_year,
+            positions: Vec::new(),
+            costs,
+        })
+    }
+
+    fn doc_types(&self) -> Vec<&'static str> {
+        self.doc_types
+            .iter()
+            .map(|doc_type| doc_type.label)
+            .collect()
+    }
+}
+
+impl Layout {
+    //find the name of the security in the first of the `name_lines` that still has one once the
+    //ISIN, WKN, amounts, quantities and labels are removed
+    fn extract_asset(&self, text: &str, isin: &str, trace: &mut Trace) -> String {
+        let lines: Vec<&str> = text.lines().collect();
+        let Some(isin_line) = lines.iter().position(|line| line.contains(isin)) else {
+            return String::new();
+        };
+
+        for &name_line in self.name_lines {
+            let i = match name_line {
+                NameLine::Same => Some(isin_line),
+                NameLine::Above => (0..isin_line).rev().find(|&i| !lines[i].trim().is_empty()),
+                NameLine::Below => {
+                    (isin_line + 1..lines.len()).find(|&i| !lines[i].trim().is_empty())
+                }
+            };
+            let Some(i) = i else {
+                continue;
+            };
+
+            let name = self.clean_name_line(lines[i], isin);
+            if name.chars().filter(|c| c.is_alphabetic()).count() >= 3 {
+                trace.note("asset", || {
+                    format!("line {}: {:?} -> {name:?}", i + 1, lines[i].trim())
+                });
+                return name;
+            }
+            trace.note("asset", || {
+                format!(
+                    "rejected line {}: {:?} (no name left)",
+                    i + 1,
+                    lines[i].trim()
+                )
+            });
+        }
+
+        trace.note("asset", || {
+            format!("no name found next to the ISIN on line {}", isin_line + 1)
+        });
+        String::new()
+    }
+
+    //the line without ISIN, WKN, amounts, quantities and the labels and numbers in front of the name
+    fn clean_name_line(&self, line: &str, isin: &str) -> String {
+        let line = AMOUNT_RE.replace_all(line, "");
+        let line = QUANTITY_RE.replace_all(&line, "");
+
+        let all_words: Vec<&str> = line.split_whitespace().collect();
+        //the last word apart from the ISIN, where multi-column layouts print the WKN
+        let last = all_words.iter().rposition(|word| !word.contains(isin));
+
+        let mut words = Vec::new();
+        let mut after_wkn_label = false;
+        for (n, &word) in all_words.iter().enumerate() {
+            let bare = word.trim_matches(|c: char| "():,;/".contains(c));
+            let is_label = |label: &str| bare.eq_ignore_ascii_case(label);
+            if word.contains(isin) || is_label("ISIN") || is_label("WKN") {
+                after_wkn_label = is_label("WKN");
+                continue;
+            }
+            //the WKN after its label, in parentheses like "(A0RPWH)" or in a column of its own
+            let is_wkn = bare.len() == 6
+                && bare
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                && bare.contains(|c: char| c.is_ascii_digit());
+            if is_wkn && (after_wkn_label || word.starts_with('(') || Some(n) == last) {
+                after_wkn_label = false;
+                continue;
+            }
+            after_wkn_label = false;
+            //labels and numbers like "Nr.123/1" in front of the name
+            let is_number = bare.starts_with("Nr.")
+                || bare.contains(|c: char| c.is_ascii_digit())
+                    && bare
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || ".,/".contains(c));
+            if words.is_empty()
+                && (self.name_labels.iter().any(|label| is_label(label)) || is_number)
+            {
+                continue;
+            }
+            words.push(word);
+        }
+
+        words
+            .join(" ")
+            .trim_matches(|c: char| c == ',' || c == ':' || c == '-' || c.is_whitespace())
+            .to_string()
+    }
+}
+
+//find the dates of all sources, in the order of DEFAULT_DATE_PREFERENCE
+fn extract_dates(
+    text: &str,
+    english: bool,
+    period: Option<(NaiveDate, NaiveDate)>,
+    trace: &mut Trace,
+) -> Vec<(DateSource, NaiveDate)> {
+    let mut dates = Vec::new();
+    for &source in DEFAULT_DATE_PREFERENCE {
+        let date = match source {
+            DateSource::Execution | DateSource::Trade | DateSource::Booking | DateSource::Value => {
+                extract_labeled_date(text, source, english, trace)
+            }
+            DateSource::PeriodEnd => period.map(|(_, end)| end),
+            DateSource::Header => extract_header_date(text, english, trace),
+            DateSource::Any => extract_any_date(text, english, trace),
+            DateSource::FileName | DateSource::Plugin => None,
+        };
+        if let Some(date) = date {
+            dates.push((source, date));
+        }
+    }
+    dates
+}
+
+//find the first date following one of the labels of `source`
+fn extract_labeled_date(
+    text: &str,
+    source: DateSource,
+    english: bool,
+    trace: &mut Trace,
+) -> Option<NaiveDate> {
+    let (_, regex) = LABELED_DATE_RES.iter().find(|(s, _)| *s == source)?;
+    for (i, line) in text.lines().enumerate() {
+        let Some(captures) = regex.captures(line) else {
+            continue;
+        };
+        if let Some(date) = parse_date(&captures[1], english) {
+            trace.note("date", || {
+                format!("{source} in line {}: {:?} -> {date}", i + 1, line.trim())
+            });
+            return Some(date);
+        }
+    }
+
+    trace.note("date", || format!("no {source} found"));
+    None
+}
+
+//find the most plausible of all dates in the text, the first one in the text on a tie
+fn extract_any_date(text: &str, english: bool, trace: &mut Trace) -> Option<NaiveDate> {
+    let candidates: Vec<(usize, usize, NaiveDate)> = ANY_DATE_RE
+        .find_iter(text)
+        .filter_map(|found| {
+            let date = parse_date(found.as_str(), english)?;
+            Some((found.start(), found.end(), date))
+        })
+        .collect();
+
+    let mut sorted: Vec<NaiveDate> = candidates.iter().map(|(_, _, date)| *date).collect();
+    sorted.sort();
+    let Some(&median) = sorted.get(sorted.len() / 2) else {
+        trace.note("date", || "no date in the document".to_string());
+        return None;
+    };
+
+    let mut best: Option<(i32, NaiveDate)> = None;
+    for &(offset, end, date) in &candidates {
+        let (score, reasons) = score_date(text, offset..end, date, median);
+        trace.note("date", || {
+            format!("candidate {date} at offset {offset} scores {score} ({})", {
+                if reasons.is_empty() {
+                    "no hints".to_string()
+                } else {
+                    reasons.join(", ")
+                }
+            })
+        });
+        if best.is_none_or(|(best_score, _)| score > best_score) {
+            best = Some((score, date));
+        }
+    }
+
+    let (score, date) = best?;
+    trace.note("date", || {
+        format!("best scored date in the document -> {date} (score {score})")
+    });
+    Some(date)
+}
+
+//how likely an unlabeled date at `range` is the date of the document, with the reasons
+fn score_date(
+    text: &str,
+    range: Range<usize>,
+    date: NaiveDate,
+    median: NaiveDate,
+) -> (i32, Vec<&'static str>) {
+    let mut score = 0;
+    let mut reasons = Vec::new();
+
+    let offset = range.start;
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let before = text[line_start..offset].to_lowercase();
+    let after = text[range.end..]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start()
+        .to_lowercase();
+
+    if DATE_HINTS.iter().any(|hint| before.contains(hint)) {
+        score += 3;
+        reasons.push("+3 near a date keyword");
+    }
+    if MISLEADING_DATE_HINTS
+        .iter()
+        .any(|hint| before.contains(hint))
+    {
+        score -= 4;
+        reasons.push("-4 near a birth date or period keyword");
+    }
+    if ["bis", "-", "–", "to "]
+        .iter()
+        .any(|separator| after.starts_with(separator))
+    {
+        score -= 3;
+        reasons.push("-3 start of a range");
+    }
+    if offset < text.len() / 5 {
+        score += 1;
+        reasons.push("+1 near the top");
+    }
+    if (date - median).num_days().abs() > 366 {
+        score -= 2;
+        reasons.push("-2 more than a year away from the other dates");
+    }
+
+    (score, reasons)
+}
+
+//find date of transaction following the DATUM header
+fn extract_header_date(text: &str, english: bool, trace: &mut Trace) -> Option<NaiveDate> {
+    let Some(position) = text.find("DATUM") else {
+        trace.note("date", || "no \"DATUM\" header found".to_string());
+        return None;
+    };
+
+    let rest = &text[position + "DATUM".len()..];
+    let candidate = ANY_DATE_RE
+        .find(rest)
+        .filter(|found| rest[..found.start()].trim().is_empty())
+        .map_or_else(
+            || rest.split_whitespace().next().unwrap_or_default(),
+            |found| found.as_str(),
+        );
+
+    match parse_date(candidate, english) {
+        Some(date) => {
+            trace.note("date", || {
+                format!("{candidate:?} after \"DATUM\" at offset {position} -> {date}")
+            });
+            Some(date)
+        }
+        None => {
+            trace.note("date", || {
+                format!("{candidate:?} after \"DATUM\" at offset {position} is not a date")
+            });
+            None
+        }
+    }
+}
+
+//find the first period, the start must not be after the end
+fn extract_period(text: &str, english: bool, trace: &mut Trace) -> Option<(NaiveDate, NaiveDate)> {
+    for (i, line) in text.lines().enumerate() {
+        for captures in PERIOD_RE.captures_iter(line) {
+            let (Some(start), Some(end)) = (
+                parse_date(&captures[1], english),
+                parse_date(&captures[2], english),
+            ) else {
+                continue;
+            };
+            if start <= end {
+                trace.note("period", || {
+                    format!("line {}: {:?} -> {start} to {end}", i + 1, line.trim())
+                });
+                return Some((start, end));
+            }
+        }
+    }
+
+    trace.note("period", || "no period found".to_string());
+    None
+}
+
+//parse a date matched by DATE_PATTERN
+fn parse_date(text: &str, english: bool) -> Option<NaiveDate> {
+    let numbers: Vec<u32> = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|number| !number.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let month_name = text
+        .split(|c: char| !c.is_alphabetic())
+        .find(|word| !word.is_empty())
+        .map(str::to_lowercase);
+
+    let (day, month, year) = match (numbers.as_slice(), month_name) {
+        (&[day, year], Some(month_name)) => {
+            let (_, month) = MONTH_PREFIXES
+                .iter()
+                .find(|(prefix, _)| month_name.starts_with(prefix))?;
+            (day, *month, year)
+        }
+        //an impossible month decides the order, otherwise the language
+        (&[first, second, year], None) if text.contains('/') => {
+            if english && first <= 12 || second > 12 {
+                (second, first, year)
+            } else {
+                (first, second, year)
+            }
+        }
+        (&[day, month, year], None) => (day, month, year),
+        _ => return None,
+    };
+    NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, day)
+}
+
+//whether the document is written in English rather than German
+fn is_english(text: &str) -> bool {
+    let text = text.to_lowercase();
+    let count =
+        |words: &[&str]| -> usize { words.iter().map(|word| text.matches(word).count()).sum() };
+    count(ENGLISH_WORDS) > count(GERMAN_WORDS)
+}
+
+//last resort for documents without a date, e.g. scanned ones: a date in the filename
+fn date_from_file_name(file_name: &str, trace: &mut Trace) -> Option<NaiveDate> {
+    let date = FILE_NAME_DATE_RE
+        .captures_iter(file_name)
+        .find_map(|captures| {
+            let number = |i: usize| captures.get(i)?.as_str().parse::<u32>().ok();
+            match number(1) {
+                Some(year) => NaiveDate::from_ymd_opt(year as i32, number(2)?, number(3)?),
+                None => NaiveDate::from_ymd_opt(number(6)? as i32, number(5)?, number(4)?),
+            }
+        });
+
+    match date {
+        Some(date) => trace.note("date", || {
+            format!("{file_name:?} -> {date}, taken from the filename (low confidence)")
+        }),
+        None => trace.note("date", || format!("no date in the filename {file_name:?}")),
+    }
+    date
+}
+
+//find the first valid ISIN in the text
+fn extract_isin(text: &str, trace: &mut Trace) -> Option<String> {
+    for (i, line) in text.lines().enumerate() {
+        let isin = line
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find(|word| is_valid_isin(word));
+
+        if let Some(isin) = isin {
+            trace.note("isin", || format!("line {}: {:?}", i + 1, line.trim()));
+            return Some(isin.to_string());
+        }
+    }
+
+    trace.note("isin", || "no valid ISIN found".to_string());
+    None
+}
+
+//find the total amount in the last line starting with one of the `labels`, checked in this order
+fn extract_amount(
+    text: &str,
+    labels: &[&str],
+    topic: &'static str,
+    trace: &mut Trace,
+) -> Option<Amount> {
+    for label in labels {
+        let line = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.trim_start().starts_with(label))
+            .filter_map(|(i, line)| Some((i, line, AMOUNT_RE.captures_iter(line).last()?)))
+            .last();
+
+        if let Some((i, line, captures)) = line {
+            let amount = parse_amount(&captures)?;
+            trace.note(topic, || {
+                format!("line {}: {:?} -> {amount}", i + 1, line.trim())
+            });
+            return Some(amount);
+        }
+    }
+
+    trace.note(topic, || {
+        format!("no line starting with {labels:?} contains an amount")
+    });
+    None
+}
+
+//find the total amount in a table with the values below the labels: the last amount behind the
+//last occurrence of one of the `labels`, checked in this order, or in the next line
+fn extract_column_amount(text: &str, labels: &[&str], trace: &mut Trace) -> Option<Amount> {
+    let lines: Vec<&str> = text.lines().collect();
+    for label in labels {
+        let Some((i, position)) = lines
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, line)| Some((i, line.find(label)?)))
+        else {
+            continue;
+        };
+
+        let behind = &lines[i][position + label.len()..];
+        let found = AMOUNT_RE
+            .captures_iter(behind)
+            .last()
+            .map(|captures| (i, captures))
+            .or_else(|| {
+                let next = (i + 1..lines.len()).find(|&next| !lines[next].trim().is_empty())?;
+                Some((next, AMOUNT_RE.captures_iter(lines[next]).last()?))
+            });
+
+        if let Some((line, captures)) = found {
+            let amount = parse_amount(&captures)?;
+            trace.note("amount", || {
+                format!(
+                    "line {}: {:?} (column {label:?}) -> {amount}",
+                    line + 1,
+                    lines[line].trim()
+                )
+            });
+            return Some(amount);
+        }
+    }
+
+    trace.note("amount", || {
+        format!("no amount found behind or below {labels:?}")
+    });
+    None
+}
+
+//sum the interest lines of a document, e.g. "Cash Zinsen 12,34 EUR"
+fn extract_interest(text: &str, trace: &mut Trace) -> Option<Amount> {
+    sum_amounts(text, INTEREST_LABELS, "interest", trace)
+}
+
+//sum the fee lines of a document, e.g. "Fremdkostenzuschlag -1,00 EUR"
+fn extract_fees(text: &str, trace: &mut Trace) -> Option<Amount> {
+    sum_amounts(text, FEE_LABELS, "fees", trace)
+}
+
+//sum the amounts of the lines starting with one of the `labels`, lines in another currency than
+//the first are left out
+fn sum_amounts(
+    text: &str,
+    labels: &[&str],
+    topic: &'static str,
+    trace: &mut Trace,
+) -> Option<Amount> {
+    let mut sum: Option<Amount> = None;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if !labels.iter().any(|label| trimmed.starts_with(label)) {
+            continue;
+        }
+        let Some(amount) = AMOUNT_RE
+            .captures_iter(line)
+            .last()
+            .and_then(|captures| parse_amount(&captures))
+        else {
+            continue;
+        };
+        trace.note(topic, || {
+            format!("line {}: {:?} -> {amount}", i + 1, trimmed)
+        });
+        match &mut sum {
+            None => sum = Some(amount),
+            Some(sum) if sum.currency == amount.currency => sum.cents += amount.cents,
+            Some(sum) => trace.note(topic, || {
+                format!("line {}: {amount} left out, not in {}", i + 1, sum.currency)
+            }),
+        }
+    }
+    sum
+}
+
+//the gain realized by a sale, losses are subtracted whether or not they are listed as negative
+fn extract_gain(text: &str, trace: &mut Trace) -> Option<Amount> {
+    let gain = sum_amounts(text, GAIN_LABELS, "gain", trace);
+    let loss = sum_amounts(text, LOSS_LABELS, "gain", trace);
+    match (gain, loss) {
+        (gain, None) => gain,
+        (None, Some(loss)) => Some(Amount {
+            cents: -loss.cents.abs(),
+            currency: loss.currency,
+        }),
+        (Some(gain), Some(loss)) if gain.currency == loss.currency => Some(Amount {
+            cents: gain.cents - loss.cents.abs(),
+            currency: gain.currency,
+        }),
+        (Some(gain), Some(loss)) => {
+            trace.note("gain", || {
+                format!("loss {loss} left out, not in {}", gain.currency)
+            });
+            Some(gain)
+        }
+    }
+}
+
+//whether the shares of a settlement were bought or sold, savings plans, Saveback and round-ups
+//only buy
+fn extract_side(text: &str, doc_type: &str, trace: &mut Trace) -> Option<Side> {
+    if !doc_type.starts_with("Wertpapierabrechnung") {
+        return None;
+    }
+    let side = if ["Sparplan", "Saveback", "Roundup"]
+        .iter()
+        .any(|kind| doc_type.ends_with(kind))
+    {
+        Side::Buy
+    } else if let Some((side, found)) = text
+        .lines()
+        .filter(|line| ORDER_LABELS.iter().any(|label| line.contains(label)))
+        .find_map(find_side)
+        .or_else(|| find_side(text))
+    {
+        trace.note("side", || format!("{found:?} -> {side}"));
+        return Some(side);
+    } else {
+        trace.note("side", || "neither Kauf nor Verkauf found".to_string());
+        return None;
+    };
+    trace.note("side", || format!("{doc_type} -> {side}"));
+    Some(side)
+}
+
+//the side named in `text` and the word naming it, a sale is checked first
+fn find_side(text: &str) -> Option<(Side, &str)> {
+    SELL_RE
+        .find(text)
+        .map(|found| (Side::Sell, found.as_str()))
+        .or_else(|| BUY_RE.find(text).map(|found| (Side::Buy, found.as_str())))
+}
+
+//the price per share: the first of the amounts in the line with the number of shares, e.g.
+//"3,512 Stk. 98,50 EUR 345,93 EUR", or a line starting with a price label
+fn extract_price(text: &str, trace: &mut Trace) -> Option<Amount> {
+    let position = text
+        .lines()
+        .enumerate()
+        .find(|(_, line)| QUANTITY_RE.is_match(line))
+        .and_then(|(i, line)| {
+            let amounts: Vec<_> = AMOUNT_RE.captures_iter(line).collect();
+            if amounts.len() < 2 {
+                return None;
+            }
+            Some((i, line, parse_amount(&amounts[0])?))
+        });
+    if let Some((i, line, price)) = position {
+        trace.note("price", || {
+            format!("line {}: {:?} (position) -> {price}", i + 1, line.trim())
+        });
+        return Some(price);
+    }
+    extract_amount(text, PRICE_LABELS, "price", trace)
+}
+
+//find the tax lines of a document, e.g. "Kapitalertragsteuer -12,34 EUR"
+fn extract_taxes(text: &str, trace: &mut Trace) -> Taxes {
+    Taxes {
+        //also "Kapitalertragssteuer"
+        capital_gains: extract_amount(text, &["Kapitalertrag"], "taxes", trace),
+        solidarity: extract_amount(text, &["Solidaritätszuschlag"], "taxes", trace),
+        church: extract_amount(text, &["Kirchensteuer"], "taxes", trace),
+        withholding: extract_amount(text, &["Quellensteuer"], "taxes", trace),
+    }
+}
+
+//find the cost lines of an Ex-Post Kosteninformation, e.g. "Dienstleistungskosten 12,00 EUR 0,05 %",
+//costs are positive whether or not they are listed as debits
+fn extract_costs(text: &str, trace: &mut Trace) -> Costs {
+    let mut cost = |labels: &[&str]| {
+        extract_amount(text, labels, "costs", trace).map(|amount| Amount {
+            cents: amount.cents.abs(),
+            currency: amount.currency,
+        })
+    };
+    Costs {
+        product: cost(&[
+            "Produktkosten",
+            "Kosten des Finanzinstruments",
+            "Product costs",
+        ]),
+        service: cost(&["Dienstleistungskosten", "Service costs"]),
+        inducements: cost(&[
+            "Zuwendungen",
+            "davon Zuwendungen",
+            "Erhaltene Zuwendungen",
+            "davon erhaltene Zuwendungen",
+            "Inducements",
+        ]),
+        total: cost(&["Gesamtkosten", "Summe der Kosten", "Total costs"]),
+    }
+}
+
+//find the number of shares in the first line listing one
+fn extract_quantity(text: &str, trace: &mut Trace) -> Option<String> {
+    for (i, line) in text.lines().enumerate() {
+        if let Some(quantity) = QUANTITY_RE.captures(line).and_then(|c| parse_quantity(&c)) {
+            trace.note("quantity", || {
+                format!("line {}: {:?} -> {quantity}", i + 1, line.trim())
+            });
+            return Some(quantity);
+        }
+    }
+
+    trace.note("quantity", || {
+        "no line with \"Stk.\", \"Stück\", \"STK\", \"ST\" or \"St.\" found".to_string()
+    });
+    None
+}
+
+//find the year a tax document refers to in a line like "Steuerjahr 2023"
+fn extract_tax_year(text: &str, trace: &mut Trace) -> Option<i32> {
+    for (i, line) in text.lines().enumerate() {
+        if let Some(tax_year) = TAX_YEAR_RE
+            .captures(line)
+            .and_then(|captures| captures[1].parse().ok())
+        {
+            trace.note("tax year", || {
+                format!("line {}: {:?} -> {tax_year}", i + 1, line.trim())
+            });
+            return Some(tax_year);
+        }
+    }
+
+    trace.note("tax year", || "no line with a tax year found".to_string());
+    None
+}
+
+//the number of shares matched by QUANTITY_RE as a decimal like "3.512"
+fn parse_quantity(captures: &regex::Captures) -> Option<String> {
+    let (units, fraction) = match captures.get(1) {
+        Some(units) => (units, captures.get(2)),
+        None => (captures.get(3)?, captures.get(4)),
+    };
+    let units = units.as_str().replace('.', "");
+    Some(match fraction {
+        Some(fraction) => format!("{units}.{}", fraction.as_str()),
+        None => units,
+    })
+}
+
+fn parse_amount(captures: &regex::Captures) -> Option<Amount> {
+    //the groups of the first form of AMOUNT_RE, or of the second one with the currency in front
+    let group = |first: usize, second: usize| {
+        captures
+            .get(first)
+            .or_else(|| captures.get(second))
+            .map_or("", |group| group.as_str())
+    };
+    let units: i64 = group(2, 8).replace('.', "").parse().ok()?;
+    let cents: i64 = group(3, 9).parse().ok()?;
+    let cents = units.checked_mul(100)?.checked_add(cents)?;
+    let negative = group(1, 7) == "-" || captures.get(4).is_some_and(|sign| sign.as_str() == "-");
+
+    Some(Amount {
+        cents: if negative { -cents } else { cents },
+        currency: group(5, 6).to_string(),
+    })
+}
+
+/// Lists the document type labels the parsers of all brokers can produce, each label once.
+pub fn known_doc_types() -> impl Iterator<Item = &'static str> {
+    let mut seen = Vec::new();
+    brokers()
+        .flat_map(|broker| broker.doc_types())
+        .filter(move |doc_type| {
+            let new = !seen.contains(doc_type);
+            seen.push(*doc_type);
+            new
+        })
+}
+
+/// Checks if `word` has the format of an ISIN: country code, nine alphanumeric characters and a
+/// check digit. The check digit itself is not verified.
+pub fn has_isin_format(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 12
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..11]
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && bytes[11].is_ascii_digit()
+}
+
+/// Checks the format and the check digit of an ISIN like `IE00B4L5Y983`.
+pub fn is_valid_isin(isin: &str) -> bool {
+    if !has_isin_format(isin) {
+        return false;
+    }
+
+    //letters count as two digits (A = 10 ... Z = 35), then the Luhn algorithm is applied
+    let mut digits = Vec::with_capacity(24);
+    for b in isin.bytes() {
+        if b.is_ascii_digit() {
+            digits.push(u32::from(b - b'0'));
+        } else {
+            let value = u32::from(b - b'A') + 10;
+            digits.push(value / 10);
+            digits.push(value % 10);
+        }
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                digit
+            }
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+/// Entry points into the parser for the fuzz targets in `fuzz/`, only built by `cargo fuzz`.
+#[cfg(fuzzing)]
+pub mod fuzzing {
+    use chrono::NaiveDate;
+
+    use super::{extract_document_dates, Trace};
+
+    /// The date a document with `text` from the file `file_name` gets.
+    pub fn extract_date(text: &str, file_name: Option<&str>) -> Option<NaiveDate> {
+        extract_document_dates(text, file_name, &mut Trace::default()).map(|dates| dates.dates[0].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(text: &str) -> Option<Side> {
+        extract_side(text, "Wertpapierabrechnung", &mut Trace::default())
+    }
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    //the date the document is named with and where it was found
+    fn date(text: &str) -> Option<(DateSource, NaiveDate)> {
+        let dates = extract_document_dates(text, None, &mut Trace::default())?;
+        dates.dates.first().copied()
+    }
+
+    #[test]
+    fn labeled_dates_before_the_header() {
+        let text = "DATUM 15.03.2024\nValuta 14.03.2024\nAusführung am 12.03.2024";
+        assert_eq!(date(text), Some((DateSource::Execution, ymd(2024, 3, 12))));
+        let text = "DATUM 15.03.2024\nBuchungsdatum: 13.03.2024";
+        assert_eq!(date(text), Some((DateSource::Booking, ymd(2024, 3, 13))));
+        assert_eq!(
+            date("DATUM 15.03.2024\nZahlungseingang 20.03.2024"),
+            Some((DateSource::Header, ymd(2024, 3, 15)))
+        );
+    }
+
+    #[test]
+    fn all_dates_in_the_order_of_preference() {
+        let text = "DATUM 15.03.2024\nValuta 14.03.2024\nHandelstag 12.03.2024";
+        let dates = extract_document_dates(text, None, &mut Trace::default()).unwrap();
+        assert_eq!(
+            dates.dates,
+            [
+                (DateSource::Trade, ymd(2024, 3, 12)),
+                (DateSource::Value, ymd(2024, 3, 14)),
+                (DateSource::Header, ymd(2024, 3, 15)),
+                (DateSource::Any, ymd(2024, 3, 15)),
+            ]
+        );
+    }
+
+    #[test]
+    fn slashes_by_language() {
+        assert_eq!(parse_date("03/04/2024", true), Some(ymd(2024, 3, 4)));
+        assert_eq!(parse_date("03/04/2024", false), Some(ymd(2024, 4, 3)));
+        //a number over 12 cannot be the month, whatever the language
+        assert_eq!(parse_date("13/04/2024", true), Some(ymd(2024, 4, 13)));
+        assert_eq!(parse_date("04/13/2024", false), Some(ymd(2024, 4, 13)));
+        assert_eq!(parse_date("13/13/2024", false), None);
+        assert_eq!(parse_date("03.04.2024", true), Some(ymd(2024, 4, 3)));
+    }
+
+    #[test]
+    fn slashes_in_english_documents() {
+        let text = "Trade date 03/04/2024\nThe total of the order";
+        assert_eq!(date(text), Some((DateSource::Trade, ymd(2024, 3, 4))));
+        let text = "Handelstag 03/04/2024\nDer Betrag und die Gebühren";
+        assert_eq!(date(text), Some((DateSource::Trade, ymd(2024, 4, 3))));
+    }
+
+    #[test]
+    fn month_names() {
+        assert_eq!(parse_date("12. März 2025", false), Some(ymd(2025, 3, 12)));
+        assert_eq!(parse_date("12. Maerz 2025", false), Some(ymd(2025, 3, 12)));
+        assert_eq!(parse_date("3. Okt. 2024", false), Some(ymd(2024, 10, 3)));
+        assert_eq!(parse_date("March 12, 2025", true), Some(ymd(2025, 3, 12)));
+        assert_eq!(parse_date("Sept. 3 2024", true), Some(ymd(2024, 9, 3)));
+        assert_eq!(parse_date("31. Februar 2025", false), None);
+        assert_eq!(
+            date("Ausführung am 5. Dezember 2024"),
+            Some((DateSource::Execution, ymd(2024, 12, 5)))
+        );
+        assert_eq!(
+            date("Execution date: December 5, 2024"),
+            Some((DateSource::Execution, ymd(2024, 12, 5)))
+        );
+    }
+
+    #[test]
+    fn period_ranges() {
+        let period = |text| extract_period(text, false, &mut Trace::default());
+        assert_eq!(
+            period("Zeitraum 01.07.2025 - 31.07.2025"),
+            Some((ymd(2025, 7, 1), ymd(2025, 7, 31)))
+        );
+        assert_eq!(
+            period("vom 01.07.2025 bis zum 31.07.2025"),
+            Some((ymd(2025, 7, 1), ymd(2025, 7, 31)))
+        );
+        assert_eq!(
+            extract_period(
+                "from June 1, 2025 to June 30, 2025",
+                true,
+                &mut Trace::default()
+            ),
+            Some((ymd(2025, 6, 1), ymd(2025, 6, 30)))
+        );
+        //the start must not be after the end
+        assert_eq!(period("31.07.2025 - 01.07.2025"), None);
+    }
+
+    #[test]
+    fn statements_are_dated_by_the_end_of_their_period() {
+        let text = "Kontoauszug\nZeitraum 01.07.2025 bis 31.07.2025\nErstellt 02.08.2025";
+        assert_eq!(date(text), Some((DateSource::PeriodEnd, ymd(2025, 7, 31))));
+    }
+
+    #[test]
+    fn unlabeled_dates_by_score() {
+        //a birth date and a range lose against the date of the letter
+        let text = "geboren am 01.01.1980\n01.07.2025 - 31.07.2025\nBerlin, Datum: 02.08.2025";
+        let date = extract_any_date(text, false, &mut Trace::default());
+        assert_eq!(date, Some(ymd(2025, 8, 2)));
+        //without hints the first date in the text wins the tie, the start of a range does not count
+        let text = "01.07.2025 - 31.07.2025\n02.08.2025";
+        let date = extract_any_date(text, false, &mut Trace::default());
+        assert_eq!(date, Some(ymd(2025, 7, 31)));
+    }
+
+    #[test]
+    fn unlabeled_dates_tied_by_position() {
+        //neither is near the top, the one written first wins even though it is the later date
+        let text = format!("{}\n31.07.2025\n01.07.2025", "Depot ".repeat(20));
+        let date = extract_any_date(&text, false, &mut Trace::default());
+        assert_eq!(date, Some(ymd(2025, 7, 31)));
+    }
+
+    #[test]
+    fn side_from_the_order_line() {
+        assert_eq!(side("Market-Order Kauf am 12.03.2024"), Some(Side::Buy));
+        assert_eq!(side("Limit-Order Verkauf am 12.03.2024"), Some(Side::Sell));
+        assert_eq!(side("Geschäftsart: Verkauf"), Some(Side::Sell));
+    }
+
+    #[test]
+    fn buy_mentioning_sales_words() {
+        let text = "Hinweise zum Verkaufsprospekt\nMarket-Order Kauf\nVerkaufsprovision 0,00 EUR";
+        assert_eq!(side(text), Some(Side::Buy));
+        assert_eq!(
+            side("WERTPAPIERABRECHNUNG KAUF\nseller: Lang & Schwarz"),
+            Some(Side::Buy)
+        );
+    }
+
+    #[test]
+    fn sale_mentioning_buy_words() {
+        assert_eq!(side("Verkauf\nKaufkraft erhöht"), Some(Side::Sell));
+        assert_eq!(side("Sell\nbuyback program"), Some(Side::Sell));
+    }
+
+    #[test]
+    fn side_needs_whole_words() {
+        assert_eq!(side("Verkaufsprovision\nKaufkraft\nbuyback"), None);
+    }
+
+    #[test]
+    fn savings_plans_are_purchases() {
+        assert_eq!(
+            extract_side(
+                "Verkauf",
+                "Wertpapierabrechnung_Sparplan",
+                &mut Trace::default()
+            ),
+            Some(Side::Buy)
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use std::io;
+use std::path::Path;
+#[cfg(feature = "plugins")]
+use std::{ffi::OsStr, fs, io::ErrorKind, sync::Mutex};
+
+#[cfg(feature = "plugins")]
+use chrono::NaiveDate;
+#[cfg(feature = "plugins")]
+use serde::Deserialize;
+#[cfg(feature = "plugins")]
+use wasmi::{Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+#[cfg(feature = "plugins")]
+use super::{
+    extract_document_dates, Amount, BrokerParser, Costs, DateSource, Dates, PdfData, Taxes, Trace,
+};
+
+/// Whether parser plugins can be loaded in this build.
+pub const PLUGINS_SUPPORTED: bool = cfg!(feature = "plugins");
+
+//instructions a plugin may execute per call, so a broken plugin cannot hang the run
+#[cfg(feature = "plugins")]
+const FUEL_PER_CALL: u64 = 1_000_000_000;
+
+//what the `parse` export of a plugin returns as JSON, the date is found by the tool if missing
+#[cfg(feature = "plugins")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParsedDocument {
+    date: Option<NaiveDate>,
+    doc_type: String,
+    asset: String,
+    isin: Option<String>,
+    amount: Option<ParsedAmount>,
+    quantity: Option<String>,
+    tax_year: Option<i32>,
+}
+
+#[cfg(feature = "plugins")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParsedAmount {
+    cents: i64,
+    currency: String,
+}
+
+//the instance of a plugin, calls need exclusive access to its store
+#[cfg(feature = "plugins")]
+struct Runtime {
+    store: Store<()>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    detect: TypedFunc<(i32, i32), i32>,
+    parse: TypedFunc<(i32, i32), i64>,
+}
+
+/// A parser for the documents of a broker loaded from a WASM module. See the README for the
+/// functions the module has to export.
+#[cfg(feature = "plugins")]
+pub struct Plugin {
+    name: &'static str,
+    doc_types: Vec<&'static str>,
+    runtime: Mutex<Runtime>,
+}
+
+#[cfg(feature = "plugins")]
+impl Plugin {
+    /// Loads the plugin at `path`, named after the file.
+    pub fn load(path: &Path) -> io::Result<Plugin> {
+        let invalid = |err: String| {
+            io::Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display()))
+        };
+
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module =
+            Module::new(&engine, &fs::read(path)?).map_err(|err| invalid(err.to_string()))?;
+        let mut store = Store::new(&engine, ());
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|err| invalid(err.to_string()))?;
+        //plugins get no imports, they cannot touch files or the network
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|err| invalid(err.to_string()))?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| invalid("no exported memory".to_string()))?;
+        let mut runtime = Runtime {
+            alloc: typed_func(&instance, &store, "alloc").map_err(invalid)?,
+            detect: typed_func(&instance, &store, "detect").map_err(invalid)?,
+            parse: typed_func(&instance, &store, "parse").map_err(invalid)?,
+            store,
+            memory,
+        };
+
+        let doc_types_func: TypedFunc<(), i64> =
+            typed_func(&instance, &runtime.store, "doc_types").map_err(invalid)?;
+        runtime
+            .store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|err| invalid(err.to_string()))?;
+        let packed = doc_types_func
+            .call(&mut runtime.store, ())
+            .map_err(|err| invalid(err.to_string()))?;
+        let doc_types: Vec<String> =
+            serde_json::from_slice(&runtime.read(packed).map_err(invalid)?)
+                .map_err(|err| invalid(format!("invalid document types: {err}")))?;
+        if doc_types.is_empty() {
+            return Err(invalid("no document types".to_string()));
+        }
+
+        let name = path
+            .file_stem()
+            .map_or_else(|| "plugin".into(), |stem| stem.to_string_lossy());
+        Ok(Plugin {
+            name: leak(name.into_owned()),
+            doc_types: doc_types.into_iter().map(leak).collect(),
+            runtime: Mutex::new(runtime),
+        })
+    }
+}
+
+#[cfg(feature = "plugins")]
+impl BrokerParser for Plugin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        let mut runtime = self.runtime.lock().unwrap_or_else(|err| err.into_inner());
+        let detect = runtime.detect;
+        match runtime.call(detect, text) {
+            Ok(detected) => detected != 0,
+            Err(err) => {
+                eprintln!("Plugin {} failed to detect a document: {err}", self.name);
+                false
+            }
+        }
+    }
+
+    fn parse(&self, text: &str, file_name: Option<&str>, trace: &mut Trace) -> Option<PdfData> {
+        let result = {
+            let mut runtime = self.runtime.lock().unwrap_or_else(|err| err.into_inner());
+            let parse = runtime.parse;
+            runtime.call(parse, text).and_then(|packed| {
+                if packed == 0 {
+                    Ok(None)
+                } else {
+                    runtime.read(packed).map(Some)
+                }
+            })
+        };
+        let json = match result {
+            Ok(Some(json)) => json,
+            Ok(None) => {
+                trace.note("plugin", || {
+                    format!("{} did not recognize the document", self.name)
+                });
+                return None;
+            }
+            Err(err) => {
+                trace.note("plugin", || format!("{} failed: {err}", self.name));
+                return None;
+            }
+        };
+
+        let parsed: ParsedDocument = match serde_json::from_slice(&json) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                trace.note("plugin", || {
+                    format!("{} returned invalid JSON: {err}", self.name)
+                });
+                return None;
+            }
+        };
+        let Some(&doc_type) = self
+            .doc_types
+            .iter()
+            .find(|doc_type| **doc_type == parsed.doc_type)
+        else {
+            trace.note("plugin", || {
+                format!(
+                    "{} returned the undeclared document type {:?}",
+                    self.name, parsed.doc_type
+                )
+            });
+            return None;
+        };
+        trace.note("doc type", || format!("{} -> {doc_type}", self.name));
+
+        let Dates { period, dates } = match parsed.date {
+            Some(date) => {
+                trace.note("date", || format!("{} -> {date}", self.name));
+                Dates {
+                    period: None,
+                    dates: vec![(DateSource::Plugin, date)],
+                }
+            }
+            None => extract_document_dates(text, file_name, trace)?,
+        };
+        let (date_source, date) = dates[0];
+        Some(PdfData {
+            broker: self.name,
+            date,
+            date_source,
+            dates,
+            period,
+            doc_type: doc_type.to_string(),
+            asset: parsed.asset,
+            isin: parsed.isin,
+            amount: parsed.amount.map(|amount| Amount {
+                cents: amount.cents,
+                currency: amount.currency,
+            }),
+            gross: None,
+            interest: None,
+            taxes: Taxes::default(),
+            side: None,
+            price: None,
+            fees: None,
+            gain: None,
+            quantity: parsed.quantity,
+            tax_year: parsed.tax_year,
+            positions: Vec::new(),
+            costs: Costs::default(),
+        })
+    }
+
+    fn doc_types(&self) -> Vec<&'static str> {
+        self.doc_types.clone()
+    }
+}
+
+#[cfg(feature = "plugins")]
+impl Runtime {
+    //copy `text` into the memory of the plugin and call `func` with its pointer and length
+    fn call<R: wasmi::WasmResults>(
+        &mut self,
+        func: TypedFunc<(i32, i32), R>,
+        text: &str,
+    ) -> Result<R, String> {
+        let len = i32::try_from(text.len()).map_err(|_| "document too large".to_string())?;
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|err| err.to_string())?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|err| err.to_string())?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, text.as_bytes())
+            .map_err(|err| err.to_string())?;
+        func.call(&mut self.store, (ptr, len))
+            .map_err(|err| err.to_string())
+    }
+
+    //the bytes at a pointer and length packed into one value, the pointer in the upper half
+    fn read(&self, packed: i64) -> Result<Vec<u8>, String> {
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & u64::from(u32::MAX)) as usize;
+        self.memory
+            .data(&self.store)
+            .get(ptr..ptr + len)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| format!("result at {ptr} with {len} bytes is out of bounds"))
+    }
+}
+
+#[cfg(feature = "plugins")]
+fn typed_func<Params: wasmi::WasmParams, Results: wasmi::WasmResults>(
+    instance: &Instance,
+    store: &Store<()>,
+    name: &str,
+) -> Result<TypedFunc<Params, Results>, String> {
+    instance
+        .get_typed_func(store, name)
+        .map_err(|err| format!("export {name:?}: {err}"))
+}
+
+//names live as long as the process, plugins are loaded once
+#[cfg(feature = "plugins")]
+fn leak(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+/// Loads every `*.wasm` parser plugin in `dir`, they are tried after the built-in brokers.
+/// With `skip_broken` a plugin that cannot be loaded is reported and left out instead of failing.
+/// Returns the number of plugins. Can only be called once.
+#[cfg(feature = "plugins")]
+pub fn load_plugins(dir: &Path, skip_broken: bool) -> io::Result<usize> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    paths.retain(|path| path.extension() == Some(OsStr::new("wasm")));
+    paths.sort();
+
+    let mut plugins = Vec::new();
+    for path in &paths {
+        match Plugin::load(path) {
+            Ok(plugin) => plugins.push(&*Box::leak(Box::new(plugin)) as &dyn BrokerParser),
+            //the error names the file already
+            Err(err) if skip_broken => eprintln!("Plugin skipped: {err}"),
+            Err(err) => return Err(err),
+        }
+    }
+    let count = plugins.len();
+    super::PLUGINS
+        .set(plugins)
+        .map_err(|_| io::Error::other("plugins are loaded already"))?;
+    Ok(count)
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn load_plugins(_dir: &Path, _skip_broken: bool) -> io::Result<usize> {
+    Err(io::Error::other(
+        "this build has no plugin support, install with --features plugins",
+    ))
+}
@@ -0,0 +1,51 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of Scalable Capital: trade confirmations, dividends and statements.
+pub static SCALABLE_CAPITAL: Layout = Layout {
+    name: "Scalable Capital",
+    names: &["Scalable Capital"],
+    //statements list the transactions of all types, so they are checked first
+    doc_types: &[
+        DocType::fixed("Kontoauszug", "Konto", "Auszug"),
+        DocType::fixed("Depotauszug", "Depot", "Auszug"),
+        DocType::fixed("Vermögensübersicht", "Depot", "Auszug"),
+        DocType::security("Dividende", "Dividende"),
+        DocType::security("Ausschüttung", "Dividende"),
+        DocType::security("Sparplan", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("Wertpapierabrechnung", "Wertpapierabrechnung"),
+    ],
+    //"Kauf iShares Core MSCI World USD (Acc) 3,512 Stk. 98,50 EUR 345,93 EUR" above the ISIN
+    name_lines: &[NameLine::Same, NameLine::Above],
+    name_labels: &["Kauf", "Verkauf", "Sparplan", "Wertpapier"],
+    amount_labels: &["Total", "Gesamtbetrag", "Zu Lasten", "Zu Gunsten", "Betrag"],
+    amount_in_columns: false,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/scalable_capital_buy.txt
+    const PURCHASE: &str = "Scalable Capital GmbH\n\
+Seitzstrasse 8e 80538 Muenchen\n\
+Datum 14.03.2024\n\
+Wertpapierabrechnung\n\
+Kauf\n\
+Ausfuehrung 12.03.2024 09:04 Uhr\n\
+Typ Wertpapier Anzahl Kurs Betrag\n\
+Kauf iShares Core MSCI World USD (Acc) 3,512 Stk. 98,50 EUR 345,93 EUR\n\
+IE00B4L5Y983\n\
+Total 345,93 EUR";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "Scalable Capital");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 14).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "iShares Core MSCI World USD (Acc)");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
@@ -0,0 +1,51 @@
+use super::{DocType, Layout, NameLine};
+
+/// Documents of Smartbroker+ and the other brokers whose documents are created by Baader Bank:
+/// settlements, distributions and statements.
+pub static SMARTBROKER: Layout = Layout {
+    name: "Smartbroker",
+    names: &["Smartbroker", "Baader Bank"],
+    //statements list the transactions of all types, so they are checked first
+    doc_types: &[
+        DocType::fixed("Kontoauszug", "Konto", "Auszug"),
+        DocType::fixed("Depotauszug", "Depot", "Auszug"),
+        DocType::security("Dividende", "Dividende"),
+        DocType::security("Ausschüttung", "Dividende"),
+        DocType::security("Sparplan", "Wertpapierabrechnung_Sparplan"),
+        DocType::security("Wertpapierabrechnung", "Wertpapierabrechnung"),
+    ],
+    //"Nominale ISIN: IE00B4L5Y983 WKN: A0RPWH Kurs 98,50 EUR" above "STK 3,512 iShsIII-Core ..."
+    name_lines: &[NameLine::Below],
+    name_labels: &[],
+    //"Zu Lasten Konto 12345678 Valuta: 14.03.2024 EUR 345,93"
+    amount_labels: &["Zu Lasten", "Zu Gunsten"],
+    amount_in_columns: false,
+};
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parser::parse_pdf_data;
+
+    //the document of fuzz/corpus/parse_pdf_data/smartbroker_buy.txt
+    const PURCHASE: &str = "Baader Bank AG Weihenstephaner Strasse 4 85716 Unterschleissheim\n\
+Smartbroker+\n\
+Wertpapierabrechnung Kauf\n\
+Handelstag 12.03.2024 Handelszeit 09:04:00\n\
+Nominale ISIN: IE00B4L5Y983 WKN: A0RPWH Kurs 98,50 EUR\n\
+STK 3,512 iShsIII-Core MSCI World U.ETF\n\
+Registered Shares USD (Acc) o.N.\n\
+Kurswert EUR 345,93\n\
+Zu Lasten Konto 12345678 Valuta: 14.03.2024 EUR 345,93";
+
+    #[test]
+    fn purchase() {
+        let data = parse_pdf_data(PURCHASE, None).unwrap();
+        assert_eq!(data.broker, "Smartbroker");
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap());
+        assert_eq!(data.doc_type, "Wertpapierabrechnung");
+        assert_eq!(data.asset, "iShsIII-Core MSCI World U.ETF");
+        assert_eq!(data.isin.as_deref(), Some("IE00B4L5Y983"));
+    }
+}
@@ -0,0 +1,473 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{
+    extract_amount, extract_costs, extract_document_dates, extract_fees, extract_gain,
+    extract_interest, extract_isin, extract_price, extract_quantity, extract_side,
+    extract_tax_year, extract_taxes, is_valid_isin, parse_amount, parse_quantity, Amount,
+    BrokerParser, Costs, Dates, PdfData, Position, Trace, AMOUNT_RE, GROSS_LABELS, QUANTITY_RE,
+};
+
+//headers of the sections listing the securities of a document
+const SECTION_HEADERS: &[&str] = &["POSITION", "ORDER", "ABRECHNUNG", "ÜBERSICHT"];
+
+//lines starting with these labels end a name wrapped over several lines
+const NAME_END_LABELS: &[&str] = &["ISIN", "WKN", "POSITION", "GESAMT", "Gesamt", "BETRAG"];
+
+//lines starting with these labels end the position table of a Depotauszug
+const POSITIONS_END_LABELS: &[&str] = &["ANZAHL POSITIONEN", "DEPOTWERT", "GESAMT", "Gesamt"];
+
+//a value without currency like "1.234,56", the currency is given in the header of the table
+static VALUE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(-?)\b(\d{1,3}(?:\.\d{3})*|\d+),(\d{2})\b").unwrap());
+
+//a date like "31.03.2024", printed next to the price in the position table
+static DATE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d{2}\.\d{2}\.\d{4}\b").unwrap());
+
+//labels of the lines holding the total amount, checked in this order
+const AMOUNT_LABELS: &[&str] = &["GESAMT", "Gesamt", "BETRAG"];
+
+//where the asset name of a document type is found
+#[derive(Debug, Clone, Copy)]
+enum AssetSource {
+    //line two lines below the first line starting with POSITION
+    FirstPosition,
+    //line two lines below the last line starting with POSITION
+    LastPosition,
+    //line starting with "1 Depottransfer"
+    Depottransfer,
+    //the document has no asset, use a fixed description
+    Fixed(&'static str),
+}
+
+//keywords identifying the document types, checked in this order
+const DOC_TYPES: &[(&str, &str, AssetSource)] = &[
+    (
+        "KOSTENINFORMATION",
+        "Kosteninformation",
+        AssetSource::Fixed("Ex-Post"),
+    ),
+    ("DIVIDENDE", "Dividende", AssetSource::FirstPosition),
+    (
+        "SAVEBACK",
+        "Wertpapierabrechnung_Saveback",
+        AssetSource::LastPosition,
+    ),
+    (
+        "ROUND UP",
+        "Wertpapierabrechnung_Roundup",
+        AssetSource::LastPosition,
+    ),
+    (
+        "SPARPLAN",
+        "Wertpapierabrechnung_Sparplan",
+        AssetSource::LastPosition,
+    ),
+    (
+        "STEUERLICHE OPTIMIERUNG",
+        "Steuerliche",
+        AssetSource::Fixed("Optimierung"),
+    ),
+    ("DEPOTAUSZUG", "Depot", AssetSource::Fixed("Auszug")),
+    (
+        "WERTPAPIERABRECHNUNG",
+        "Wertpapierabrechnung",
+        AssetSource::LastPosition,
+    ),
+    ("DEPOTTRANSFER", "Depottransfer", AssetSource::Depottransfer),
+    //ZINSABRECHNUNG, ZINSZAHLUNG or ZINSEN
+    ("ZINS", "Zinsen", AssetSource::Fixed("Abrechnung")),
+];
+
+/// Documents of Trade Republic, recognized by the keywords of their document types.
+pub struct TradeRepublic;
+
+impl BrokerParser for TradeRepublic {
+    fn name(&self) -> &'static str {
+        "Trade Republic"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.to_lowercase().contains("trade republic")
+    }
+
+    fn parse(&self, text: &str, file_name: Option<&str>, trace: &mut Trace) -> Option<PdfData> {
+        let Dates { period, dates } = extract_document_dates(text, file_name, trace)?;
+
+        let Some(&(keyword, doc_type, source)) = DOC_TYPES
+            .iter()
+            .find(|(keyword, _, _)| text.contains(keyword))
+        else {
+            trace.note("doc type", || {
+                let keywords: Vec<&str> =
+                    DOC_TYPES.iter().map(|(keyword, _, _)| *keyword).collect();
+                format!("none of the keywords {keywords:?} found")
+            });
+            return None;
+        };
+        trace.note("doc type", || format!("keyword {keyword:?} -> {doc_type}"));
+
+        //ISIN and asset are only looked for in the table of the document, not in the footer
+        let section = relevant_section(text, trace);
+        let isin = extract_isin(&section, trace);
+        let asset = extract_asset(&section, source, trace);
+        let amount = extract_amount(text, AMOUNT_LABELS, "amount", trace);
+        let gross = extract_amount(text, GROSS_LABELS, "gross", trace).or_else(|| {
+            if doc_type == "Dividende" {
+                extract_position_amount(&section, trace)
+            } else {
+                None
+            }
+        });
+        let interest = extract_interest(text, trace);
+        let taxes = extract_taxes(text, trace);
+        let side = extract_side(text, doc_type, trace);
+        let price = extract_price(&section, trace);
+        let fees = extract_fees(text, trace);
+        let gain = extract_gain(text, trace);
+        let quantity = extract_quantity(text, trace);
+        let tax_year = extract_tax_year(text, trace);
+        let costs = if doc_type == "Kosteninformation" {
+            extract_costs(text, trace)
+        } else {
+            Costs::default()
+        };
+        let positions = if doc_type == "Depot" {
+            extract_positions(text, trace)
+        } else {
+            Vec::new()
+        };
+
+        let (date_source, date) = dates[0];
+        Some(PdfData {
+            broker: self.name(),
+            date,
+            date_source,
+            dates,
+            period,
+            doc_type: doc_type.to_string(),
+            asset,
+            isin,
+            amount,
+            gross,
+            interest,
+            taxes,
+            side,
+            price,
+            fees,
+            gain,
+            quantity,
+            tax_year,
+            positions,
+            costs,
+        })
+    }
+
+    fn doc_types(&self) -> Vec<&'static str> {
+        DOC_TYPES.iter().map(|(_, doc_type, _)| *doc_type).collect()
+    }
+}
+
+//the text with the lines outside the section holding the securities blanked, so line numbers
+//stay the same: from the first POSITION, ORDER or ABRECHNUNG header to the last total line.
+//The whole text if there is no such header
+fn relevant_section(text: &str, trace: &mut Trace) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let Some(start) = lines.iter().position(|line| {
+        SECTION_HEADERS
+            .iter()
+            .any(|header| line.trim_start().starts_with(header))
+    }) else {
+        trace.note("section", || {
+            format!("none of the headers {SECTION_HEADERS:?} found, using the whole text")
+        });
+        return text.to_string();
+    };
+    let end = lines
+        .iter()
+        .rposition(|line| {
+            AMOUNT_LABELS
+                .iter()
+                .any(|label| line.trim_start().starts_with(label))
+        })
+        .filter(|&end| end > start)
+        .unwrap_or(lines.len() - 1);
+
+    trace.note("section", || {
+        format!(
+            "lines {} to {} ({:?})",
+            start + 1,
+            end + 1,
+            lines[start].trim()
+        )
+    });
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if (start..=end).contains(&i) {
+                *line
+            } else {
+                ""
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+//find the asset name according to the formatting of the document type
+fn extract_asset(text: &str, source: AssetSource, trace: &mut Trace) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+
+    match source {
+        AssetSource::Fixed(asset) => {
+            trace.note("asset", || format!("fixed description {asset:?}"));
+            asset.to_string()
+        }
+        AssetSource::Depottransfer => {
+            let mut asset = String::new();
+            for (i, line) in lines.iter().enumerate() {
+                if line.starts_with("1 Depottransfer") {
+                    let name = line
+                        .strip_prefix("1 Depottransfer eingegangen ")
+                        .or_else(|| line.strip_prefix("1 Depottransfer "))
+                        .unwrap_or_default();
+                    if !asset.is_empty() {
+                        trace.note("asset", || {
+                            format!("rejected {asset:?}, a later \"1 Depottransfer\" line is used")
+                        });
+                    }
+                    trace.note("asset", || format!("candidate line {}: {:?}", i + 1, line));
+                    asset = join_name_lines(name, &lines[i + 1..], false, trace);
+                }
+            }
+            asset
+        }
+        AssetSource::FirstPosition | AssetSource::LastPosition => {
+            //the name is two lines below the POSITION header of the table
+            let candidates: Vec<(usize, usize)> = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.starts_with("POSITION"))
+                .map(|(i, _)| (i, i + 2))
+                .filter(|&(_, name_line)| name_line < lines.len())
+                .collect();
+
+            let chosen = match source {
+                AssetSource::FirstPosition => candidates.first(),
+                _ => candidates.last(),
+            };
+
+            for &(position_line, name_line) in &candidates {
+                let line = lines[name_line];
+                if Some(&(position_line, name_line)) == chosen {
+                    trace.note("asset", || {
+                        format!(
+                            "chose line {}: {line:?} (two lines below POSITION on line {})",
+                            name_line + 1,
+                            position_line + 1
+                        )
+                    });
+                } else {
+                    trace.note("asset", || {
+                        let reason = match source {
+                            AssetSource::FirstPosition => "only the first POSITION table is used",
+                            _ => "only the last POSITION table is used",
+                        };
+                        format!("rejected line {}: {line:?} ({reason})", name_line + 1)
+                    });
+                }
+            }
+
+            match chosen {
+                Some(&(_, name_line)) => {
+                    join_name_lines(lines[name_line], &lines[name_line + 1..], true, trace)
+                }
+                None => {
+                    trace.note("asset", || {
+                        "no line starting with POSITION found".to_string()
+                    });
+                    String::new()
+                }
+            }
+        }
+    }
+}
+
+//join a name hyphenated at the end of its line with the following lines: "Vanguard FTSE Develo-"
+//and "ped World" become "Vanguard FTSE Developed World", "MSCI-" and "World" keep the hyphen.
+//With `join_wrapped` the following lines without numbers are joined as well, until the numbers
+//of the table start
+fn join_name_lines(
+    first_line: &str,
+    following: &[&str],
+    join_wrapped: bool,
+    trace: &mut Trace,
+) -> String {
+    let mut name = first_line.to_string();
+    for line in following {
+        let trimmed = name.trim_end();
+        let next = line.trim();
+        if next.is_empty() {
+            break;
+        }
+
+        //a dash after a space is part of the name, e.g. "Bank - "
+        let hyphenated = trimmed
+            .strip_suffix('-')
+            .filter(|stem| stem.ends_with(char::is_alphabetic));
+        let joined = match hyphenated {
+            Some(stem) if next.starts_with(char::is_lowercase) => format!("{stem}{next}"),
+            Some(_) => format!("{trimmed}{next}"),
+            None if join_wrapped && is_name_continuation(next) => format!("{trimmed} {next}"),
+            None => break,
+        };
+        trace.note("asset", || {
+            if hyphenated.is_some() {
+                format!("joined the hyphenated {trimmed:?} with the next line {next:?}")
+            } else {
+                format!("joined {trimmed:?} with the wrapped next line {next:?}")
+            }
+        });
+        name = joined;
+    }
+    name
+}
+
+//whether a line below the name still belongs to it: no numbers and no label of the table
+fn is_name_continuation(line: &str) -> bool {
+    !line.contains(|c: char| c.is_ascii_digit())
+        && !NAME_END_LABELS.iter().any(|label| line.starts_with(label))
+}
+
+//the amount of the first position of a dividend, the last amount in the line with the number of
+//shares: "10 Stk. 0,24 USD 2,40 USD" is the payment before taxes
+fn extract_position_amount(section: &str, trace: &mut Trace) -> Option<Amount> {
+    let (i, line) = section
+        .lines()
+        .enumerate()
+        .find(|(_, line)| QUANTITY_RE.is_match(line))?;
+    let amount = parse_amount(&AMOUNT_RE.captures_iter(line).last()?)?;
+    trace.note("gross", || {
+        format!("line {}: {:?} (position) -> {amount}", i + 1, line.trim())
+    });
+    Some(amount)
+}
+
+//the securities listed by a Depotauszug: every position starts with a line like
+//"3,512 Stk. iShares Core MSCI World USD (Acc) 98,50 31.03.2024 345,93", followed by lines with
+//the rest of the name, the ISIN and the custody country
+fn extract_positions(text: &str, trace: &mut Trace) -> Vec<Position> {
+    //"KURSWERT IN EUR" in the header of the table
+    let currency = text
+        .lines()
+        .find_map(|line| {
+            let (_, rest) = line.split_once("KURSWERT IN ")?;
+            rest.get(..3)
+                .filter(|code| code.bytes().all(|b| b.is_ascii_uppercase()))
+        })
+        .unwrap_or("EUR");
+
+    let mut blocks: Vec<(usize, Vec<&str>)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if !blocks.is_empty()
+            && POSITIONS_END_LABELS
+                .iter()
+                .any(|label| line.starts_with(label))
+        {
+            break;
+        }
+        if QUANTITY_RE
+            .find(line)
+            .is_some_and(|found| found.start() == 0)
+        {
+            blocks.push((i, vec![line]));
+        } else if let Some((_, lines)) = blocks.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    let positions: Vec<Position> = blocks
+        .iter()
+        .filter_map(|(i, lines)| {
+            let position = parse_position(lines, currency);
+            trace.note("positions", || match &position {
+                Some(position) => format!(
+                    "line {}: {} {:?} x {} -> {}",
+                    i + 1,
+                    position.isin,
+                    position.name,
+                    position.quantity,
+                    position
+                        .value
+                        .as_ref()
+                        .map_or("no value".to_string(), Amount::to_string)
+                ),
+                None => format!("line {}: {:?} left out, no ISIN found", i + 1, lines[0]),
+            });
+            position
+        })
+        .collect();
+    if positions.is_empty() {
+        trace.note("positions", || {
+            "no line starting with \"Stk.\" found".to_string()
+        });
+    }
+    positions
+}
+
+//one position of a Depotauszug from its lines, amounts without currency are in `currency`
+fn parse_position(lines: &[&str], currency: &str) -> Option<Position> {
+    let first = QUANTITY_RE.captures(lines[0])?;
+    let quantity = parse_quantity(&first)?;
+    let isin = lines
+        .iter()
+        .flat_map(|line| line.split(|c: char| !c.is_ascii_alphanumeric()))
+        .find(|word| is_valid_isin(word))?
+        .to_string();
+
+    //the name follows the number of shares up to the first price, value or date
+    let rest = &lines[0][first.get(0)?.end()..];
+    let end = [&*AMOUNT_RE, &*VALUE_RE, &*DATE_RE]
+        .iter()
+        .filter_map(|re| re.find(rest).map(|found| found.start()))
+        .min()
+        .unwrap_or(rest.len());
+    let mut name = rest[..end].trim().to_string();
+    if name.is_empty() {
+        name = lines[1..]
+            .iter()
+            .find(|line| !line.is_empty() && is_name_continuation(line))
+            .map_or(String::new(), |line| line.to_string());
+    }
+
+    //the market value is the last amount of the position, after the price
+    let value = lines.iter().fold(None, |value, line| {
+        if line.contains(&isin) {
+            return value;
+        }
+        let found = AMOUNT_RE
+            .captures_iter(line)
+            .last()
+            .and_then(|captures| parse_amount(&captures))
+            .or_else(|| {
+                let captures = VALUE_RE.captures_iter(line).last()?;
+                let units: i64 = captures[2].replace('.', "").parse().ok()?;
+                let cents = units.checked_mul(100)? + captures[3].parse::<i64>().ok()?;
+                Some(Amount {
+                    cents: if &captures[1] == "-" { -cents } else { cents },
+                    currency: currency.to_string(),
+                })
+            });
+        found.or(value)
+    });
+
+    Some(Position {
+        isin,
+        name,
+        quantity,
+        value,
+    })
+}
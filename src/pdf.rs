@@ -0,0 +1,199 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{self, BufWriter, ErrorKind, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use lopdf::{
+    encryption::crypt_filters::{Aes256CryptFilter, CryptFilter},
+    text_string, Dictionary, Document, EncryptionState, EncryptionVersion, Object, ObjectId,
+    Permissions, SaveOptions,
+};
+use serde::Serialize;
+
+use crate::reason::Reason;
+
+/// Size of a PDF in bytes before and after it was optimized.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Optimized {
+    pub before: u64,
+    pub after: u64,
+}
+
+/// Changes [`rewrite`] makes to a PDF.
+#[derive(Debug, Default)]
+pub struct Rewrite<'a> {
+    /// Shrink the file with [`optimize`].
+    pub optimize: bool,
+    /// Title and subject written into the document information, shown by PDF readers.
+    pub info: Option<(&'a str, &'a str)>,
+    /// Encrypt the file with AES-256, opening it needs this password.
+    pub password: Option<&'a str>,
+}
+
+/// Writes the PDF at `path` with the `changes` to `target`. Returns the sizes before and after
+/// if it was optimized.
+pub fn rewrite(path: &Path, target: &Path, changes: &Rewrite) -> io::Result<Option<Optimized>> {
+    let Rewrite {
+        optimize,
+        info,
+        password,
+    } = *changes;
+
+    let mut document = load(path)?;
+    if document.is_encrypted() || document.was_encrypted() {
+        return Err(Reason::AlreadyEncrypted.error(format!(
+            "{} is encrypted already and cannot be rewritten",
+            path.display()
+        )));
+    }
+
+    if let Some((title, subject)) = info {
+        set_info(&mut document, title, subject);
+    }
+    if optimize {
+        self::optimize(&mut document);
+    }
+    //the information is encrypted as well, so it is set before
+    if let Some(password) = password {
+        encrypt(&mut document, password).map_err(|err| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("encryption of {} failed: {err}", path.display()),
+            )
+        })?;
+    }
+    save(&mut document, target, optimize)?;
+
+    if !optimize {
+        return Ok(None);
+    }
+    let before = fs::metadata(path)?.len();
+    let mut after = fs::metadata(target)?.len();
+    //files written compactly already may grow, they are better kept as they are
+    if after >= before && password.is_none() && info.is_none() {
+        fs::copy(path, target)?;
+        after = before;
+    }
+    Ok(Some(Optimized { before, after }))
+}
+
+/// Shrinks `document`: identical streams, like a font embedded once per page, are kept once,
+/// objects nothing refers to are dropped and uncompressed streams are compressed.
+pub fn optimize(document: &mut Document) {
+    let mut by_content: HashMap<&[u8], Vec<ObjectId>> = HashMap::new();
+    let mut duplicates: HashMap<ObjectId, ObjectId> = HashMap::new();
+    for (&id, object) in &document.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        let kept = by_content.entry(&stream.content).or_default();
+        //equal content with a different dictionary, e.g. another filter, is not a duplicate
+        match kept
+            .iter()
+            .find(|first| document.objects.get(first) == Some(object))
+        {
+            Some(&first) => {
+                duplicates.insert(id, first);
+            }
+            None => kept.push(id),
+        }
+    }
+
+    if !duplicates.is_empty() {
+        document.traverse_objects(|object| {
+            if let Object::Reference(id) = object {
+                if let Some(&first) = duplicates.get(id) {
+                    *id = first;
+                }
+            }
+        });
+    }
+    document.prune_objects();
+    document.compress();
+}
+
+//set the title and subject of the document information, creating it if there is none
+fn set_info(document: &mut Document, title: &str, subject: &str) {
+    let existing = match document.trailer.get(b"Info") {
+        Ok(Object::Reference(id)) if document.get_dictionary(*id).is_ok() => Some(*id),
+        _ => None,
+    };
+    let info_id = existing.unwrap_or_else(|| {
+        //some writers put the dictionary right into the trailer
+        let info = document
+            .trailer
+            .get(b"Info")
+            .and_then(Object::as_dict)
+            .cloned()
+            .unwrap_or_else(|_| Dictionary::new());
+        let id = document.add_object(info);
+        document.trailer.set("Info", id);
+        id
+    });
+
+    if let Ok(info) = document.get_dictionary_mut(info_id) {
+        info.set("Title", text_string(title));
+        info.set("Subject", text_string(subject));
+    }
+}
+
+//encrypt all strings and streams of `document` with AES-256
+fn encrypt(document: &mut Document, password: &str) -> Result<(), String> {
+    let mut file_encryption_key = [0; 32];
+    getrandom::fill(&mut file_encryption_key).map_err(|err| err.to_string())?;
+    let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+    EncryptionState::try_from(EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
+        file_encryption_key: &file_encryption_key,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        //the owner has no further rights, there is nothing to restrict in one's own archive
+        owner_password: password,
+        user_password: password,
+        permissions: Permissions::all(),
+    })
+    .and_then(|state| document.encrypt(&state))
+    .map_err(|err| err.to_string())?;
+
+    //AES-256 encryption was introduced with PDF 2.0
+    document.version = "2.0".to_string();
+    Ok(())
+}
+
+/// Whether the PDF in `bytes` is encrypted, e.g. by an earlier `--encrypt`.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    Document::load_mem(bytes)
+        .is_ok_and(|document| document.is_encrypted() || document.was_encrypted())
+}
+
+/// Reads a PDF, errors are invalid data like failed text extraction.
+pub fn load(path: &Path) -> io::Result<Document> {
+    Document::load(path)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display())))
+}
+
+//write `document` to `path` through a temporary file, so a failed write does not leave half a
+//file, `compact` packs objects into compressed object streams
+fn save(document: &mut Document, path: &Path, compact: bool) -> io::Result<()> {
+    let temp_path = path.with_extension("pdf.tmp");
+    let written = File::create(&temp_path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        let options = SaveOptions::builder()
+            .use_object_streams(compact)
+            .use_xref_streams(compact)
+            .build();
+        document
+            .save_with_options(&mut writer, options)
+            .map_err(|err| io::Error::other(format!("{}: {err}", path.display())))?;
+        writer.flush()
+    });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+    fs::rename(&temp_path, path)
+}
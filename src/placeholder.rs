@@ -0,0 +1,74 @@
+use std::{fs::Metadata, str::FromStr};
+
+use serde::{Deserialize, Deserializer};
+
+/// What to do with online-only files of OneDrive, Dropbox, iCloud Drive and other sync clients,
+/// which are downloaded as soon as they are read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Placeholders {
+    /// Read them anyway, downloading them.
+    #[default]
+    Hydrate,
+    /// Leave them alone and report them as skipped.
+    Skip,
+    /// Only process files that are already local, without reporting the others as a problem.
+    Local,
+}
+
+impl FromStr for Placeholders {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Placeholders, String> {
+        match name {
+            "hydrate" => Ok(Placeholders::Hydrate),
+            "skip" => Ok(Placeholders::Skip),
+            "local" => Ok(Placeholders::Local),
+            _ => Err(format!(
+                "unknown placeholder policy {name:?}, known policies: hydrate, skip, local"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Placeholders {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Placeholders, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks if the file with `metadata` is an online-only placeholder whose content is not stored
+/// locally.
+#[cfg(windows)]
+pub fn is_placeholder(metadata: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    //FILE_ATTRIBUTE_OFFLINE, FILE_ATTRIBUTE_RECALL_ON_OPEN and FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+    metadata.file_attributes() & (0x1000 | 0x40000 | 0x400000) != 0
+}
+
+/// Checks if the file with `metadata` is an online-only placeholder whose content is not stored
+/// locally.
+#[cfg(unix)]
+pub fn is_placeholder(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    //dataless files of the File Provider sync clients
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::macos::fs::MetadataExt as _;
+
+        //SF_DATALESS
+        if metadata.st_flags() & 0x40000000 != 0 {
+            return true;
+        }
+    }
+    //a file without any blocks on disk is entirely sparse, its content is somewhere else
+    metadata.len() > 0 && metadata.blocks() == 0
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn is_placeholder(_metadata: &Metadata) -> bool {
+    false
+}
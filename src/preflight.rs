@@ -0,0 +1,105 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{
+    commands::{pdf_files, pdf_files_recursive},
+    process::RunOptions,
+};
+
+/// Checks before a run on `path` that the files can be renamed: their directory, the archive and
+/// the directory for unparsed files are writable, and on Windows that no PDF is read-only. With
+/// `chmod` the read-only attribute is cleared instead. Fails with all problems found, so they
+/// don't show up as errors of single files halfway through the run.
+pub fn check(path: &Path, options: &RunOptions, chmod: bool) -> io::Result<()> {
+    let folder = if path.is_dir() {
+        path
+    } else {
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    };
+
+    let mut problems = Vec::new();
+    for (dir, purpose) in [
+        (Some(folder), "the folder"),
+        (options.archive_root.as_deref(), "the archive"),
+        (
+            options.unparsed_dir.as_deref(),
+            "the directory for unparsed files",
+        ),
+    ] {
+        let Some(dir) = dir else {
+            continue;
+        };
+        if let Err(err) = check_writable(dir) {
+            problems.push(format!("cannot create files in {purpose} {:?}: {err}", dir));
+        }
+    }
+
+    //Windows refuses to delete read-only files, which a rewrite or a move to another drive does
+    if cfg!(windows) {
+        let files = if path.is_dir() {
+            if options.recursive {
+                pdf_files_recursive(path)?
+            } else {
+                pdf_files(path)?
+            }
+        } else {
+            vec![path.to_path_buf()]
+        };
+        for file in files {
+            if !fs::metadata(&file)?.permissions().readonly() {
+                continue;
+            }
+            if chmod {
+                clear_readonly(&file)?;
+                println!("Cleared the read-only attribute of {:?}", file);
+            } else {
+                problems.push(format!(
+                    "{:?} is read-only, clear the attribute or pass --chmod",
+                    file
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        ErrorKind::PermissionDenied,
+        format!("files cannot be renamed:\n  {}", problems.join("\n  ")),
+    ))
+}
+
+//try to actually create a file in `dir` or the first existing directory above it, permissions
+//alone do not tell about ACLs or read-only mounts
+fn check_writable(dir: &Path) -> io::Result<()> {
+    let existing = dir
+        .ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .unwrap_or(Path::new("."));
+    let probe = existing.join(".tr_pdf_rename_preflight");
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(windows)]
+fn clear_readonly(file: &Path) -> io::Result<()> {
+    let mut permissions = fs::metadata(file)?.permissions();
+    //on Windows this only clears the read-only attribute
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(file, permissions)
+}
+
+#[cfg(not(windows))]
+fn clear_readonly(_file: &Path) -> io::Result<()> {
+    Ok(())
+}
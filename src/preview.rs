@@ -0,0 +1,69 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// A rename a dry run would do.
+#[derive(Debug)]
+pub struct Rename {
+    pub from: String,
+    /// The new name, the path below the archive root when archiving.
+    pub to: String,
+    /// A remark printed below the rename, like a date of low confidence.
+    pub note: Option<String>,
+}
+
+/// Prints the renames as old and new name in aligned columns, with the changed part of the names
+/// in color when stdout is a terminal and `NO_COLOR` is not set.
+pub fn print(renames: &[Rename]) {
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    let width = renames
+        .iter()
+        .map(|rename| rename.from.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for rename in renames {
+        let padding = " ".repeat(width - rename.from.chars().count());
+        if color {
+            let (prefix, from, to, suffix) = split_changes(&rename.from, &rename.to);
+            println!(
+                "{prefix}{RED}{from}{RESET}{suffix}{padding} {DIM}->{RESET} {prefix}{GREEN}{to}{RESET}{suffix}"
+            );
+        } else {
+            println!("{}{padding} -> {}", rename.from, rename.to);
+        }
+        if let Some(note) = &rename.note {
+            println!("  {note}");
+        }
+    }
+}
+
+//the common start and end of both names and the differing parts between them, split at chars
+fn split_changes<'a>(from: &'a str, to: &'a str) -> (&'a str, &'a str, &'a str, &'a str) {
+    let prefix = from
+        .char_indices()
+        .zip(to.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(from.len().min(to.len()), |((index, _), _)| index);
+    let (from_rest, to_rest) = (&from[prefix..], &to[prefix..]);
+    let suffix = from_rest
+        .chars()
+        .rev()
+        .zip(to_rest.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+
+    (
+        &from[..prefix],
+        &from_rest[..from_rest.len() - suffix],
+        &to_rest[..to_rest.len() - suffix],
+        &from_rest[from_rest.len() - suffix..],
+    )
+}
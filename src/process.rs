@@ -0,0 +1,1461 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    ffi::OsStr,
+    fs::{self, read_dir},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use chrono::{DateTime, Local, Utc};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    commands::pdf_files,
+    config::{Config, HooksConfig},
+    extract_text_from_mem,
+    filter::Filter,
+    hooks::{self, Decision},
+    i18n::tr,
+    journal::{self, Entry},
+    local_config::{Ignores, LocalConfig},
+    macos,
+    naming::{
+        matches_generated, truncate_asset, FolderLayout, NameRules, RenamedDetector, Template,
+        Token,
+    },
+    parser::{has_date, parse_pdf_data, DateSource, PdfData, RULES_VERSION},
+    pdf::{self, Optimized, Rewrite},
+    placeholder::{is_placeholder, Placeholders},
+    preview::{self, Rename},
+    provenance,
+    reason::Reason,
+    retry::{self, is_sharing_violation, is_transient, Retry},
+    state::State,
+    thumbnail,
+    timings::{Phase, Timings},
+};
+
+//MAX_PATH of Windows without the terminating NUL, longer paths only work if enabled system-wide
+const WINDOWS_MAX_PATH: usize = 259;
+
+/// Settings of a rename run.
+#[derive(Clone)]
+pub struct RunOptions {
+    /// Template the new names are built from.
+    pub template: Template,
+    /// How asset names are cleaned up for the new names.
+    pub name_rules: NameRules,
+    /// Filenames matching this are renamed already, names built with the template without it.
+    pub renamed_pattern: Option<Regex>,
+    /// Filenames matching one of these were organized by another tool and are left alone.
+    pub organized_patterns: Vec<Regex>,
+    /// Only process files added since the previous run on the directory.
+    pub since_last_run: bool,
+    /// Only process files added after this time.
+    pub newer_than: Option<DateTime<Utc>>,
+    /// Only process files that could not be parsed in previous runs.
+    pub retry_unparsed: bool,
+    /// External commands run while renaming.
+    pub hooks: HooksConfig,
+    /// Do not print a message per file.
+    pub quiet: bool,
+    /// Only report what would be renamed, without touching any file.
+    pub dry_run: bool,
+    /// Also process the subdirectories of a directory.
+    pub recursive: bool,
+    /// Also process symlinked subdirectories and junctions, each directory only once.
+    pub follow_links: bool,
+    /// Conditions the parsed documents have to meet.
+    pub filter: Filter,
+    /// Order in which the dates of a document are preferred, the parser's choice if empty.
+    pub date_preference: Vec<DateSource>,
+    /// Move the files into the `folder_layout` below this directory instead of renaming them in
+    /// place.
+    pub archive_root: Option<PathBuf>,
+    /// Directories below `archive_root` the files are moved into.
+    pub folder_layout: FolderLayout,
+    /// Move files that cannot be read or parsed into this directory, with a note why.
+    pub unparsed_dir: Option<PathBuf>,
+    /// Copy the files into `unparsed_dir` instead of moving them.
+    pub copy_unparsed: bool,
+    /// Encrypt the files moved into the archive with this password.
+    pub encrypt_password: Option<String>,
+    /// Recompress the files moved into the archive to save space.
+    pub optimize: bool,
+    /// Write a PNG preview of the first page next to each renamed file.
+    pub thumbnails: bool,
+    /// Set the title of the PDFs to their new name and the subject to broker and type.
+    pub set_info: bool,
+    /// How often a rename blocked by another program having the file open is tried again.
+    pub rename_retry: Retry,
+    /// How often reads and renames failing with a network error are tried again.
+    pub io_retry: Retry,
+    /// Set the Spotlight title and keywords of the renamed files, on macOS.
+    pub spotlight: bool,
+    /// Remove the quarantine attribute of the renamed files, on macOS.
+    pub clear_quarantine: bool,
+    /// Record the original name, parse time and version in extended attributes, on Linux.
+    pub provenance: bool,
+    /// What to do with online-only files of sync clients.
+    pub placeholders: Placeholders,
+    /// Maximum number of characters of the absolute path of a renamed file, the asset name is
+    /// shortened to stay within it.
+    pub max_path_len: Option<usize>,
+}
+
+impl RunOptions {
+    /// The options of a run with `template` and the settings of the config file.
+    pub fn from_config(config: &Config, template: Template) -> io::Result<RunOptions> {
+        let patterns = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+        };
+
+        Ok(RunOptions {
+            template,
+            name_rules: NameRules {
+                abbreviations: config.abbreviations.clone(),
+                ascii: false,
+                max_asset_len: config.max_asset_length.filter(|&max| max > 0),
+                strip_boilerplate: config.strip_boilerplate,
+                stop_tokens: config.stop_tokens.clone(),
+            },
+            renamed_pattern: patterns(config.renamed_pattern.as_slice())?.pop(),
+            organized_patterns: patterns(&config.organized_patterns)?,
+            since_last_run: false,
+            newer_than: None,
+            retry_unparsed: false,
+            hooks: config.hooks.clone(),
+            quiet: false,
+            dry_run: false,
+            recursive: false,
+            follow_links: false,
+            filter: Filter::default(),
+            date_preference: config.date_preference.clone().unwrap_or_default(),
+            archive_root: None,
+            folder_layout: config.folder_layout.clone().unwrap_or_default(),
+            unparsed_dir: config.unparsed_dir.clone(),
+            copy_unparsed: false,
+            encrypt_password: None,
+            optimize: false,
+            thumbnails: false,
+            set_info: config.set_pdf_info,
+            rename_retry: retry::RENAME.with(config.rename_retries, config.rename_retry_delay_ms),
+            io_retry: retry::IO.with(config.io_retries, config.io_retry_delay_ms),
+            spotlight: config.spotlight,
+            clear_quarantine: config.clear_quarantine,
+            provenance: config.provenance_xattrs.unwrap_or(true),
+            placeholders: config.placeholders.unwrap_or_default(),
+            max_path_len: config
+                .max_path_length
+                .or(cfg!(windows).then_some(WINDOWS_MAX_PATH))
+                .filter(|&max| max > 0),
+        })
+    }
+
+    //the options for a directory with a .trrename.toml
+    fn with_local(&self, local: LocalConfig) -> RunOptions {
+        let mut options = self.clone();
+        if let Some(template) = local.template {
+            options.template = template
+                .with_separator(self.template.separator())
+                .with_doc_type_labels(self.template.doc_type_labels().clone());
+        }
+        options
+    }
+}
+
+/// What happened to a single file during a run.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome {
+    Renamed {
+        new_name: String,
+        doc_type: String,
+        /// The date is a guess, e.g. taken from the filename.
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        low_confidence_date: bool,
+        /// Sizes before and after the file was optimized.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        optimized: Option<Optimized>,
+    },
+    AlreadyRenamed {
+        code: Reason,
+    },
+    Filtered {
+        code: Reason,
+        reason: String,
+    },
+    Skipped {
+        code: Reason,
+        reason: String,
+    },
+}
+
+/// The outcome of a file, identified by its original name.
+#[derive(Debug, Serialize)]
+pub struct FileOutcome {
+    pub file: String,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    /// Time spent reading, parsing and renaming the file.
+    #[serde(skip)]
+    pub elapsed: Duration,
+}
+
+/// A file that could be parsed, with the name it got.
+#[derive(Debug, Serialize)]
+pub struct ParsedFile {
+    pub path: PathBuf,
+    pub new_name: String,
+    #[serde(flatten)]
+    pub data: PdfData,
+}
+
+/// The outcomes of all files a run looked at.
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub files: Vec<FileOutcome>,
+    /// The data of the files that could be parsed, for `--dump-parsed`.
+    #[serde(skip)]
+    pub parsed: Vec<ParsedFile>,
+    //the renames of a dry run, printed as a table once all files are looked at
+    #[serde(skip)]
+    preview: Vec<Rename>,
+}
+
+impl Summary {
+    fn push(&mut self, path: &Path, outcome: Outcome, elapsed: Duration) {
+        self.files.push(FileOutcome {
+            file: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            outcome,
+            elapsed,
+        });
+    }
+
+    pub fn renamed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Renamed { .. }))
+    }
+
+    pub fn already_renamed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::AlreadyRenamed { .. }))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Skipped { .. }))
+    }
+
+    /// The number of files not renamed, per reason.
+    pub fn reasons(&self) -> BTreeMap<Reason, usize> {
+        let mut reasons = BTreeMap::new();
+        for file in &self.files {
+            match &file.outcome {
+                Outcome::Renamed { .. } => {}
+                Outcome::AlreadyRenamed { code }
+                | Outcome::Filtered { code, .. }
+                | Outcome::Skipped { code, .. } => *reasons.entry(*code).or_default() += 1,
+            }
+        }
+        reasons
+    }
+
+    /// Prints the number of files not renamed per reason, nothing if all were renamed.
+    pub fn print_reasons(&self) {
+        let reasons = self.reasons();
+        if reasons.is_empty() {
+            return;
+        }
+        let reasons: Vec<String> = reasons
+            .iter()
+            .map(|(code, count)| format!("{count} {code}"))
+            .collect();
+        println!("{}", tr!("not-renamed", reasons = reasons.join(", ")));
+    }
+
+    pub fn filtered(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Filtered { .. }))
+    }
+
+    fn count(&self, filter: impl Fn(&Outcome) -> bool) -> usize {
+        self.files
+            .iter()
+            .filter(|file| filter(&file.outcome))
+            .count()
+    }
+}
+
+/// Renames the PDF at `path`, or all PDFs in the directory at `path`.
+pub fn run(path: &Path, options: &RunOptions, timings: &mut Timings) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+
+    //check is path is file or directory
+    if path.is_file() && path.extension().and_then(OsStr::to_str) == Some("pdf") {
+        let started = Instant::now();
+        let processed = process_file(path, options, timings)?;
+        report(path, processed, options, &mut summary, started.elapsed());
+    } else if path.is_dir() {
+        process_folder(path, options, timings, &mut summary)?;
+    }
+    preview::print(&summary.preview);
+
+    Ok(summary)
+}
+
+/// Processes the directory at `dir` every `interval` until the process is stopped, calling
+/// `batch_finished` after every pass that renamed or skipped files. After the first pass only
+/// files added since the previous pass are looked at.
+pub fn watch(
+    dir: &Path,
+    options: &RunOptions,
+    interval: Duration,
+    mut batch_finished: impl FnMut(&Summary),
+) -> io::Result<()> {
+    let mut options = options.clone();
+
+    loop {
+        let batch_started = Utc::now();
+        let mut summary = Summary::default();
+        process_folder(dir, &options, &mut Timings::default(), &mut summary)?;
+
+        if summary.renamed() > 0 || summary.skipped() > 0 {
+            batch_finished(&summary);
+        }
+
+        options.since_last_run = false;
+        options.newer_than = Some(batch_started);
+        thread::sleep(interval);
+    }
+}
+
+fn process_folder(
+    dir: &Path,
+    options: &RunOptions,
+    timings: &mut Timings,
+    summary: &mut Summary,
+) -> io::Result<()> {
+    //with links followed, every directory may be reached a second time
+    let mut visited = HashSet::new();
+    if options.follow_links {
+        visited.insert(fs::canonicalize(dir)?);
+    }
+    process_dir(
+        dir,
+        options,
+        &Ignores::default(),
+        &mut visited,
+        timings,
+        summary,
+    )
+}
+
+fn process_dir(
+    dir: &Path,
+    options: &RunOptions,
+    ignores: &Ignores,
+    visited: &mut HashSet<PathBuf>,
+    timings: &mut Timings,
+    summary: &mut Summary,
+) -> io::Result<()> {
+    //settings of a .trrename.toml apply to the whole subtree
+    let local_options;
+    let options = match LocalConfig::load(dir)? {
+        Some(local) => {
+            local_options = options.with_local(local);
+            &local_options
+        }
+        None => options,
+    };
+    let ignores = ignores.with_dir(dir)?;
+    let detector = RenamedDetector::new(&options.template, options.renamed_pattern.as_ref());
+    let mut subdirs = Vec::new();
+
+    //remember when this run started, files added while it runs are picked up next time
+    let run_started = Utc::now();
+    //the state is only needed by the options reading it, a broken one must not stop other runs
+    let uses_state = options.since_last_run || options.retry_unparsed;
+    let had_state = State::exists(dir);
+    let (mut state, state_valid) = match State::load(dir) {
+        Ok(state) => (state, true),
+        Err(err) if !uses_state => {
+            eprintln!(
+                "{}",
+                tr!(
+                    "state-ignored",
+                    dir = format!("{:?}", dir),
+                    error = err.to_string()
+                )
+            );
+            (State::default(), false)
+        }
+        Err(err) => return Err(err),
+    };
+
+    let cutoff = if options.retry_unparsed {
+        None
+    } else if options.since_last_run {
+        state.last_run
+    } else {
+        options.newer_than
+    };
+    //forget files that were deleted or renamed by hand
+    state
+        .unparsed
+        .retain(|file_name| dir.join(file_name).is_file());
+
+    //println!("Is dir: {:?}",&path);
+    //sorted, so counters for duplicate names are assigned in the same order on every run
+    let mut entries = options.io_retry.run(is_transient, || {
+        read_dir(dir)?.collect::<io::Result<Vec<_>>>()
+    })?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let file_path = entry.path();
+
+        //symlinked directories and junctions may form cycles, they are only followed on request
+        let file_type = entry.file_type()?;
+        let is_dir = file_type.is_dir()
+            || (options.follow_links && file_type.is_symlink() && file_path.is_dir());
+        if ignores.is_ignored(&file_path, is_dir) {
+            continue;
+        }
+        if is_dir {
+            //the archive or the quarantine may be a subdirectory, their files are handled already
+            if options.recursive && !is_output_dir(&file_path, options) {
+                subdirs.push(file_path);
+            }
+            continue;
+        }
+
+        //only pdf files are of interest
+        if !file_path.is_file() || file_path.extension().and_then(OsStr::to_str) != Some("pdf") {
+            continue;
+        }
+
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if options.retry_unparsed && !state.unparsed.contains(&file_name) {
+            continue;
+        }
+
+        //skip files that were already there at the cutoff time
+        if let Some(cutoff) = cutoff {
+            let added = options
+                .io_retry
+                .run(is_transient, || added_time(&file_path))?;
+            if DateTime::<Utc>::from(added) <= cutoff {
+                continue;
+            }
+        }
+
+        //check if the filename already follows the naming scheme, renamed files are still archived
+        if options.archive_root.is_none() && detector.is_renamed(&file_name) {
+            if !options.quiet {
+                println!(
+                    "{}",
+                    tr!("already-renamed", file = format!("{:?}", entry.file_name()))
+                );
+            }
+            summary.push(
+                &file_path,
+                Outcome::AlreadyRenamed {
+                    code: Reason::AlreadyRenamed,
+                },
+                Duration::ZERO,
+            );
+            continue;
+        }
+
+        //files named by another tool are not parsed at all
+        if let Some(pattern) = options
+            .organized_patterns
+            .iter()
+            .find(|pattern| pattern.is_match(&file_name))
+        {
+            if !options.quiet {
+                println!(
+                    "{}",
+                    tr!(
+                        "organized",
+                        file = format!("{:?}", entry.file_name()),
+                        pattern = format!("{:?}", pattern.as_str())
+                    )
+                );
+            }
+            summary.push(
+                &file_path,
+                Outcome::AlreadyRenamed {
+                    code: Reason::Organized,
+                },
+                Duration::ZERO,
+            );
+            continue;
+        }
+
+        let started = Instant::now();
+        match process_file(&file_path, options, timings) {
+            Ok(processed) => {
+                state.unparsed.remove(&file_name);
+                report(&file_path, processed, options, summary, started.elapsed());
+            }
+            //not a Trade Republic document or not readable, leave it as it is
+            Err(err) if err.kind() == ErrorKind::InvalidData => {
+                if Reason::of(&err).is_unparsed() {
+                    state.unparsed.insert(file_name);
+                } else {
+                    state.unparsed.remove(&file_name);
+                }
+                skipped(&file_path, err, options, summary, started.elapsed());
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    //a dry run did not process anything, the next real run must still see the files. A folder
+    //only gets a state file once there is something to remember, a broken one is left for the user
+    let remember = uses_state || had_state || !state.unparsed.is_empty();
+    if !options.dry_run && state_valid && remember {
+        //a retry did not look at new files, the next --since-last-run must still see them
+        if !options.retry_unparsed {
+            state.last_run = Some(run_started);
+        }
+        state.save(dir)?;
+    }
+
+    subdirs.sort();
+    for subdir in subdirs {
+        //a link to a directory of this run would be a loop or process its files twice
+        if options.follow_links {
+            let target = fs::canonicalize(&subdir)?;
+            if visited.contains(&target) {
+                if !options.quiet {
+                    println!(
+                        "{}",
+                        tr!(
+                            "directory-visited",
+                            dir = format!("{:?}", subdir),
+                            target = format!("{:?}", target)
+                        )
+                    );
+                }
+                continue;
+            }
+            visited.insert(target);
+        }
+        process_dir(&subdir, options, &ignores, visited, timings, summary)?;
+    }
+
+    Ok(())
+}
+
+/// Shows the names `count` randomly chosen PDFs of `dir` would get, without renaming them.
+pub fn sample(
+    dir: &Path,
+    count: usize,
+    options: &RunOptions,
+    timings: &mut Timings,
+) -> io::Result<Summary> {
+    let options = RunOptions {
+        dry_run: true,
+        ..options.clone()
+    };
+    let mut summary = Summary::default();
+
+    let mut files = pdf_files(dir)?;
+    let total = files.len();
+    fastrand::shuffle(&mut files);
+    files.truncate(count);
+    files.sort();
+    println!("{}", tr!("sampling", count = files.len(), total = total));
+
+    for path in files {
+        let started = Instant::now();
+        match process_file(&path, &options, timings) {
+            Ok(processed) => report(&path, processed, &options, &mut summary, started.elapsed()),
+            Err(err) if err.kind() == ErrorKind::InvalidData => {
+                skipped(&path, err, &options, &mut summary, started.elapsed())
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    preview::print(&summary.preview);
+
+    Ok(summary)
+}
+
+//print and record a file that could not be read or parsed
+fn skipped(
+    path: &Path,
+    err: io::Error,
+    options: &RunOptions,
+    summary: &mut Summary,
+    elapsed: Duration,
+) {
+    if !options.quiet {
+        println!(
+            "{}",
+            tr!(
+                "skipped",
+                file = format!("{:?}", path.file_name().unwrap_or_default()),
+                code = Reason::of(&err).code(),
+                reason = err.to_string()
+            )
+        );
+    }
+    summary.push(
+        path,
+        Outcome::Skipped {
+            code: Reason::of(&err),
+            reason: err.to_string(),
+        },
+        elapsed,
+    );
+}
+
+//print and record what happened to a file that could be parsed
+fn report(
+    path: &Path,
+    processed: Processed,
+    options: &RunOptions,
+    summary: &mut Summary,
+    elapsed: Duration,
+) {
+    let file_name = path.file_name().unwrap_or_default();
+    let mut parsed = |data: Box<PdfData>, new_path: &Path| {
+        summary.parsed.push(ParsedFile {
+            path: path.to_path_buf(),
+            new_name: new_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            data: *data,
+        })
+    };
+    let outcome = match processed {
+        Processed::Renamed {
+            new_path,
+            data,
+            optimized,
+        } => {
+            let to = match &options.archive_root {
+                Some(root) => new_path.strip_prefix(root).unwrap_or(&new_path),
+                None => Path::new(new_path.file_name().unwrap_or_default()),
+            };
+            let low_confidence = data.date_source.is_low_confidence().then(|| {
+                tr!(
+                    "low-confidence-date",
+                    date = data.date.to_string(),
+                    source = data.date_source.to_string()
+                )
+            });
+            if options.dry_run {
+                if !options.quiet {
+                    summary.preview.push(Rename {
+                        from: file_name.to_string_lossy().into_owned(),
+                        to: to.to_string_lossy().into_owned(),
+                        note: low_confidence,
+                    });
+                }
+            } else if !options.quiet {
+                let (from, to) = (format!("{:?}", file_name), format!("{:?}", to));
+                if options.archive_root.is_some() {
+                    println!("{}", tr!("archived", from = from, to = to));
+                } else {
+                    println!("{}", tr!("renamed", from = from, to = to));
+                }
+                if let Some(low_confidence) = low_confidence {
+                    println!("  {low_confidence}");
+                }
+            }
+            if let Some(optimized) = optimized.filter(|_| !options.quiet) {
+                println!(
+                    "  {}",
+                    tr!(
+                        "optimized",
+                        before = format_size(optimized.before),
+                        after = format_size(optimized.after)
+                    )
+                );
+            }
+            let outcome = Outcome::Renamed {
+                low_confidence_date: data.date_source.is_low_confidence(),
+                new_name: new_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                doc_type: data.doc_type.clone(),
+                optimized,
+            };
+            parsed(data, &new_path);
+            outcome
+        }
+        Processed::Filtered { code, reason } => {
+            if !options.quiet {
+                println!(
+                    "{}",
+                    tr!(
+                        "filtered",
+                        file = format!("{:?}", file_name),
+                        code = code.code(),
+                        reason = reason.as_str()
+                    )
+                );
+            }
+            Outcome::Filtered { code, reason }
+        }
+        Processed::Skipped { code, reason } => {
+            if !options.quiet {
+                println!(
+                    "{}",
+                    tr!(
+                        "skipped",
+                        file = format!("{:?}", file_name),
+                        code = code.code(),
+                        reason = reason.as_str()
+                    )
+                );
+            }
+            Outcome::Skipped { code, reason }
+        }
+        Processed::Unchanged { data } => {
+            if !options.quiet {
+                println!("{}", tr!("unchanged", file = format!("{:?}", file_name)));
+            }
+            parsed(data, path);
+            Outcome::AlreadyRenamed {
+                code: Reason::Unchanged,
+            }
+        }
+        Processed::Archived { existing, data } => {
+            if !options.quiet {
+                let root = options.archive_root.as_deref().unwrap_or(Path::new(""));
+                println!(
+                    "{}",
+                    tr!(
+                        "in-archive",
+                        file = format!("{:?}", file_name),
+                        existing =
+                            format!("{:?}", existing.strip_prefix(root).unwrap_or(&existing))
+                    )
+                );
+            }
+            parsed(data, &existing);
+            Outcome::AlreadyRenamed {
+                code: Reason::InArchive,
+            }
+        }
+    };
+    summary.push(path, outcome, elapsed);
+}
+
+/// `bytes` in a unit fitting its size, like 1.5 MiB.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+//time a file was added to the directory: downloads get a fresh mtime, moved files keep theirs but get a new creation time
+fn added_time(path: &Path) -> io::Result<SystemTime> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata.modified()?;
+    Ok(match metadata.created() {
+        Ok(created) => created.max(modified),
+        Err(_) => modified,
+    })
+}
+
+//the file in `dir` named like `stem` that is the same document as `bytes`, byte for byte or by
+//date, type, ISIN and amount like a reissued export
+fn archived_copy(
+    dir: &Path,
+    stem: &str,
+    bytes: &[u8],
+    data: &PdfData,
+    options: &RunOptions,
+) -> io::Result<Option<PathBuf>> {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    for entry in entries {
+        let path = entry?.path();
+        let Some(existing_stem) = path.file_stem().and_then(OsStr::to_str) else {
+            continue;
+        };
+        if path.extension().and_then(OsStr::to_str) != Some("pdf")
+            || !matches_generated(existing_stem, stem, options.template.separator())
+        {
+            continue;
+        }
+
+        let existing = options.io_retry.run(is_transient, || fs::read(&path))?;
+        if existing == bytes {
+            return Ok(Some(path));
+        }
+        let same = extract_text_from_mem(&existing)
+            .ok()
+            .and_then(|text| parse_pdf_data(&text, path.file_name().and_then(OsStr::to_str)))
+            .is_some_and(|mut other| {
+                other.prefer_date(&options.date_preference);
+                (other.date, &other.doc_type, &other.isin, &other.amount)
+                    == (data.date, &data.doc_type, &data.isin, &data.amount)
+            });
+        if same {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
+
+//whether `dir` is the archive or the quarantine the files are moved to
+fn is_output_dir(dir: &Path, options: &RunOptions) -> bool {
+    let Ok(dir) = fs::canonicalize(dir) else {
+        return false;
+    };
+    [&options.archive_root, &options.unparsed_dir]
+        .into_iter()
+        .flatten()
+        .any(|output| fs::canonicalize(output).is_ok_and(|output| output == dir))
+}
+
+//move or copy a file that could not be processed because of `err` into the quarantine, with a
+//note why next to it, and return the error to report
+fn quarantine(path: &Path, err: io::Error, options: &RunOptions) -> io::Error {
+    let Some(dir) = &options.unparsed_dir else {
+        return err;
+    };
+    let verb = match (options.dry_run, options.copy_unparsed) {
+        (true, true) => "would be copied",
+        (true, false) => "would be moved",
+        (false, true) => "copied",
+        (false, false) => "moved",
+    };
+    let reason = Reason::of(&err);
+    if options.dry_run {
+        return reason.error(format!("{err}, {verb} to {dir:?}"));
+    }
+
+    match move_to_quarantine(path, dir, &err, options) {
+        Ok(target) => reason.error(format!("{err}, {verb} to {target:?}")),
+        Err(quarantine_err) => reason.error(format!(
+            "{err}, could not be {verb} to {dir:?}: {quarantine_err}"
+        )),
+    }
+}
+
+/// Cleans the asset of `data` read from the file at `path` and returns the file stem `options`
+/// give it, before a hook or the path length change it.
+pub fn file_stem(data: &mut PdfData, path: &Path, options: &RunOptions) -> String {
+    data.asset = options.name_rules.clean_name(&data.asset);
+    options
+        .name_rules
+        .clean_stem(options.template.render(data, path))
+}
+
+/// What happened to a PDF that could be read and parsed.
+pub enum Processed {
+    /// Renamed to `new_path`, or would have been in a dry run.
+    Renamed {
+        new_path: PathBuf,
+        data: Box<PdfData>,
+        /// Sizes before and after, if the file was optimized.
+        optimized: Option<Optimized>,
+    },
+    /// Left alone as it did not pass the filter.
+    Filtered { code: Reason, reason: String },
+    /// Left alone without reading it, e.g. as it is not downloaded.
+    Skipped { code: Reason, reason: String },
+    /// Left alone as it already has the name it would get.
+    Unchanged { data: Box<PdfData> },
+    /// Left alone as the archive holds the same document as `existing`.
+    Archived {
+        existing: PathBuf,
+        data: Box<PdfData>,
+    },
+}
+
+/// Reads, parses and renames a single PDF.
+#[tracing::instrument(
+    name = "file",
+    skip_all,
+    fields(path = %path.display(), doc_type),
+    err(Display)
+)]
+pub fn process_file(
+    path: &Path,
+    options: &RunOptions,
+    timings: &mut Timings,
+) -> io::Result<Processed> {
+    let started = Instant::now();
+
+    let metadata = timings.measure(Phase::Filesystem, || {
+        options.io_retry.run(is_transient, || fs::metadata(path))
+    })?;
+    if let Some((code, reason)) = options.filter.rejects_file(&metadata) {
+        return Ok(Processed::Filtered { code, reason });
+    }
+    //reading an online-only file downloads it
+    if options.placeholders != Placeholders::Hydrate && is_placeholder(&metadata) {
+        let reason = "online-only, not downloaded".to_string();
+        tracing::info!(%reason, "not processed");
+        let code = Reason::OnlineOnly;
+        return Ok(match options.placeholders {
+            Placeholders::Local => Processed::Filtered { code, reason },
+            _ => Processed::Skipped { code, reason },
+        });
+    }
+
+    //read pdf file
+    let bytes = timings.measure(Phase::Filesystem, || {
+        options.io_retry.run(is_transient, || fs::read(path))
+    })?;
+    let out = timings
+        .measure(Phase::Extraction, || extract_text_from_mem(&bytes))
+        .map_err(|err| quarantine(path, err, options))?;
+
+    //println!("Read: {}", out);
+
+    let mut data = timings
+        .measure(Phase::Parsing, || {
+            parse_pdf_data(&out, path.file_name().and_then(OsStr::to_str))
+        })
+        .ok_or_else(|| {
+            let file_name = path.file_name().and_then(OsStr::to_str);
+            let err = if has_date(&out, file_name) {
+                Reason::UnknownDocType.error("no known document type found")
+            } else {
+                Reason::NoDate.error("no date found")
+            };
+            quarantine(path, err, options)
+        })?;
+
+    data.prefer_date(&options.date_preference);
+    tracing::Span::current().record("doc_type", data.doc_type.as_str());
+
+    if let Some((code, reason)) = options.filter.rejects(&data) {
+        return Ok(Processed::Filtered { code, reason });
+    }
+
+    let mut stem = file_stem(&mut data, path, options);
+    let mut renamed_by_hook = false;
+
+    if let Some(command) = &options.hooks.pre_rename {
+        let timeout = options.hooks.timeout();
+        stem = match hooks::pre_rename(command, timeout, path, &format!("{stem}.pdf"), &data) {
+            Ok(Decision::Accept) => stem,
+            Ok(Decision::Rename { name }) => {
+                let name = name.strip_suffix(".pdf").unwrap_or(&name);
+                if name.is_empty() || name.contains(['/', '\\']) {
+                    return Err(Reason::InvalidName.error(format!(
+                        "pre-rename hook returned the invalid name {name:?}"
+                    )));
+                }
+                renamed_by_hook = true;
+                name.to_string()
+            }
+            Ok(Decision::Skip { reason }) => {
+                return Err(Reason::HookVeto.error(format!(
+                    "vetoed by pre-rename hook{}",
+                    reason
+                        .map(|reason| format!(": {reason}"))
+                        .unwrap_or_default()
+                )))
+            }
+            //without a decision the file is left alone
+            Err(err) if err.kind() == ErrorKind::TimedOut => {
+                return Err(Reason::HookTimeout.error(format!("pre-rename hook failed: {err}")))
+            }
+            Err(err) => {
+                return Err(Reason::HookFailed.error(format!("pre-rename hook failed: {err}")))
+            }
+        };
+    }
+
+    let current_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let current_dir = path.parent().unwrap_or(Path::new(""));
+    let target_dir = |data: &PdfData| -> io::Result<PathBuf> {
+        Ok(match &options.archive_root {
+            Some(root) => root.join(
+                options
+                    .folder_layout
+                    .render(data, path, &options.template)
+                    .map_err(|err| Reason::OutsideTargetDir.error(err))?,
+            ),
+            None => current_dir.to_path_buf(),
+        })
+    };
+    let mut dir = target_dir(&data)?;
+
+    //a path over the limit of Windows cannot be created, the asset name is the part to give up
+    if let Some(max_len) = options.max_path_len.filter(|_| !renamed_by_hook) {
+        let len = path_len(&dir, &stem)?;
+        if len > max_len {
+            let asset = data.asset.clone();
+            let asset_len = asset.chars().count();
+            data.asset = truncate_asset(&asset, asset_len.saturating_sub(len - max_len));
+            stem = options
+                .name_rules
+                .clean_stem(options.template.render(&data, path));
+            dir = target_dir(&data)?;
+            if data.asset.is_empty() || path_len(&dir, &stem)? > max_len {
+                return Err(Reason::NameTooLong.error(format!(
+                    "the new path in {:?} would be longer than {max_len} characters even with a shortened asset name",
+                    dir
+                )));
+            }
+            if !options.quiet {
+                println!(
+                    "{}",
+                    tr!(
+                        "asset-shortened",
+                        asset = format!("{asset:?}"),
+                        file = format!("{:?}", path.file_name().unwrap_or_default()),
+                        shortened = format!("{:?}", data.asset),
+                        max = max_len
+                    )
+                );
+            }
+        }
+    }
+    let in_place = dir == current_dir;
+
+    //repeated exports would otherwise pile up in the archive as copies with a counter
+    if !in_place && options.archive_root.is_some() {
+        let existing = timings.measure(Phase::Filesystem, || {
+            archived_copy(&dir, &stem, &bytes, &data, options)
+        })?;
+        if let Some(existing) = existing {
+            return Ok(Processed::Archived {
+                existing,
+                data: Box::new(data),
+            });
+        }
+    }
+
+    //{counter} counts up to the first name not taken by another file
+    if !renamed_by_hook && options.template.contains(Token::Counter) {
+        stem = timings.measure(Phase::Filesystem, || {
+            (1..)
+                .map(|counter| {
+                    options
+                        .name_rules
+                        .clean_stem(options.template.render_numbered(&data, path, counter))
+                })
+                .find(|candidate| {
+                    let candidate_path = dir.join(format!("{candidate}.pdf"));
+                    (in_place
+                        && (*candidate == current_stem || is_case_change(path, &candidate_path)))
+                        || !candidate_path.exists()
+                })
+                .expect("a free counter exists")
+        });
+    }
+
+    //nothing to do if the file already has its name, possibly with a counter for duplicates
+    if in_place && matches_generated(&current_stem, &stem, options.template.separator()) {
+        return Ok(Processed::Unchanged {
+            data: Box::new(data),
+        });
+    }
+
+    //an earlier rename already recorded the download name
+    let original_name =
+        (options.provenance && provenance::PROVENANCE_SUPPORTED && !options.dry_run)
+            .then(|| provenance::original_name(path));
+
+    //an encrypted file cannot be rewritten, it is left where it is instead of being quarantined
+    let rewrite = options.optimize || options.set_info || options.encrypt_password.is_some();
+    if rewrite && pdf::is_encrypted(&bytes) {
+        return Ok(Processed::Skipped {
+            code: Reason::AlreadyEncrypted,
+            reason: "the file is encrypted already and cannot be rewritten".to_string(),
+        });
+    }
+
+    let (new_path, optimized) = if options.dry_run {
+        let new_path = timings.measure(Phase::Filesystem, || {
+            target_path(path, &dir, &stem, options.template.separator())
+        });
+        (new_path, None)
+    } else if rewrite {
+        timings.measure(Phase::Filesystem, || {
+            rewrite_to(path, &dir, &stem, &data, options)
+        })?
+    } else {
+        let new_path =
+            timings.measure(Phase::Filesystem, || rename_to(path, &dir, &stem, options))?;
+        (new_path, None)
+    };
+
+    if let Some(root) = options.archive_root.as_ref().filter(|_| !options.dry_run) {
+        journal::append(
+            root,
+            &Entry {
+                moved_at: Utc::now(),
+                from: std::path::absolute(path)?,
+                to: new_path
+                    .strip_prefix(root)
+                    .unwrap_or(&new_path)
+                    .to_path_buf(),
+                template: Some(options.template.scheme()),
+                rules: Some(RULES_VERSION.to_string()),
+            },
+        )?;
+    }
+
+    //like a failing hook, a missing thumbnail is reported but does not undo the rename
+    if options.thumbnails && !options.dry_run {
+        if let Err(err) = timings.measure(Phase::Filesystem, || {
+            thumbnail::write(&new_path, options.encrypt_password.as_deref())
+        }) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "thumbnail-failed",
+                    file = format!("{:?}", new_path),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+
+    if !options.dry_run {
+        if let Err(err) = timings.measure(Phase::Filesystem, || {
+            set_attributes(&new_path, &data, original_name.as_deref(), options)
+        }) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "attributes-failed",
+                    file = format!("{:?}", new_path),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+
+    //a failing hook is reported but does not undo the rename
+    if let Some(command) = options
+        .hooks
+        .post_rename
+        .as_ref()
+        .filter(|_| !options.dry_run)
+    {
+        if let Err(err) =
+            hooks::post_rename(command, options.hooks.timeout(), path, &new_path, &data)
+        {
+            eprintln!(
+                "{}",
+                tr!(
+                    "post-rename-failed",
+                    file = format!("{:?}", new_path),
+                    error = err.to_string()
+                )
+            );
+        }
+    }
+
+    timings.record_file(path, started.elapsed());
+    tracing::info!(new_name = %new_path.file_name().unwrap_or_default().to_string_lossy(), "renamed");
+
+    Ok(Processed::Renamed {
+        new_path,
+        data: Box::new(data),
+        optimized,
+    })
+}
+
+//rename the file to date_ordertype_name.pdf in `dir`, which is created if needed
+fn rename_to(
+    path: &Path,
+    dir: &Path,
+    date_ordertype_name: &str,
+    options: &RunOptions,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let new_path = target_path(path, dir, date_ordertype_name, options.template.separator());
+
+    move_file(path, &new_path, options)?;
+
+    Ok(new_path)
+}
+
+//write the file optimized, with its title and/or encrypted to date_ordertype_name.pdf in `dir`
+//and delete it, it is left as it is if that fails
+fn rewrite_to(
+    path: &Path,
+    dir: &Path,
+    date_ordertype_name: &str,
+    data: &PdfData,
+    options: &RunOptions,
+) -> io::Result<(PathBuf, Option<Optimized>)> {
+    fs::create_dir_all(dir)?;
+    let new_path = target_path(path, dir, date_ordertype_name, options.template.separator());
+    //with a name differing only in case, writing to it would overwrite the original
+    let case_change = is_case_change(path, &new_path);
+    let written = if case_change {
+        temporary_path(&new_path)
+    } else {
+        new_path.clone()
+    };
+
+    let title = new_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let subject = format!(
+        "{} {}",
+        data.broker,
+        options.template.type_label(&data.doc_type)
+    );
+    let changes = Rewrite {
+        optimize: options.optimize,
+        info: Some((title.as_str(), subject.as_str())).filter(|_| options.set_info),
+        password: options.encrypt_password.as_deref(),
+    };
+    let optimized = pdf::rewrite(path, &written, &changes)?;
+    //Gatekeeper should still know where the file came from
+    if macos::MACOS {
+        let _ = macos::copy_quarantine(path, &written);
+    }
+    remove_file(path, options)?;
+    if case_change {
+        move_file(&written, &new_path, options)?;
+    }
+
+    Ok((new_path, optimized))
+}
+
+//the provenance, Spotlight title and keywords and the quarantine attribute of the renamed file at
+//`path`
+fn set_attributes(
+    path: &Path,
+    data: &PdfData,
+    original_name: Option<&str>,
+    options: &RunOptions,
+) -> io::Result<()> {
+    if let Some(original_name) = original_name {
+        provenance::record(path, original_name, Utc::now(), &options.template.scheme())?;
+    }
+    if options.spotlight {
+        let title = path.file_stem().unwrap_or_default().to_string_lossy();
+        let doc_type = options.template.type_label(&data.doc_type);
+        let keywords: Vec<&str> = [data.broker, doc_type.as_ref(), data.asset.as_str()]
+            .into_iter()
+            .chain(data.isin.as_deref())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        macos::set_spotlight(path, &title, &keywords)?;
+    }
+    if options.clear_quarantine {
+        macos::clear_quarantine(path)?;
+    }
+    Ok(())
+}
+
+//the path the file got in the quarantine `dir`, its note is the same path with .txt appended
+fn move_to_quarantine(
+    path: &Path,
+    dir: &Path,
+    err: &io::Error,
+    options: &RunOptions,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let target = get_unique_filename(
+        dir.join(path.file_name().unwrap_or_default()),
+        options.template.separator(),
+    );
+    let original = std::path::absolute(path)?;
+    if options.copy_unparsed {
+        fs::copy(path, &target)?;
+    } else {
+        move_file(path, &target, options)?;
+    }
+
+    let mut note = target.clone().into_os_string();
+    note.push(".txt");
+    fs::write(
+        note,
+        format!(
+            "{}\nnot processed on {}: {err}\n",
+            original.display(),
+            Local::now().format("%Y-%m-%d %H:%M")
+        ),
+    )?;
+
+    Ok(target)
+}
+
+//rename, or copy and delete as the archive may be on another filesystem than the downloads, and
+//try again while another program has the file open or the network share is gone for a moment
+fn move_file(path: &Path, new_path: &Path, options: &RunOptions) -> io::Result<()> {
+    if is_case_change(path, new_path) {
+        return change_case(path, new_path);
+    }
+    let mut attempted = false;
+    let renamed = options.io_retry.run(is_transient, || {
+        //a rename that timed out may have gone through on the server
+        if attempted && !path.exists() && new_path.exists() {
+            return Ok(());
+        }
+        attempted = true;
+        options
+            .rename_retry
+            .run(is_sharing_violation, || fs::rename(path, new_path))
+    });
+    match renamed {
+        Err(err) if err.kind() == ErrorKind::CrossesDevices => {
+            let metadata = fs::metadata(path)?;
+            options
+                .io_retry
+                .run(is_transient, || fs::copy(path, new_path))?;
+            //best effort, backup tools look at the timestamps
+            let _ = copy_times(&metadata, new_path);
+            remove_file(path, options)
+        }
+        result => result,
+    }
+}
+
+//delete the original of a copied or rewritten file, with the retries of a rename
+fn remove_file(path: &Path, options: &RunOptions) -> io::Result<()> {
+    let mut attempted = false;
+    options.io_retry.run(is_transient, || {
+        if attempted && !path.exists() {
+            return Ok(());
+        }
+        attempted = true;
+        options
+            .rename_retry
+            .run(is_sharing_violation, || fs::remove_file(path))
+    })
+}
+
+//give the copy at `path` the timestamps of the original, fs::copy keeps the attributes and
+//permissions but not all of them
+fn copy_times(original: &fs::Metadata, path: &Path) -> io::Result<()> {
+    let times = fs::FileTimes::new()
+        .set_accessed(original.accessed()?)
+        .set_modified(original.modified()?);
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::{FileTimesExt, OpenOptionsExt};
+
+        //FILE_WRITE_ATTRIBUTES is enough to set the times, also of read-only files
+        let file = fs::OpenOptions::new().access_mode(0x100).open(path)?;
+        file.set_times(times.set_created(original.created()?))
+    }
+    #[cfg(not(windows))]
+    {
+        fs::OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_times(times)
+    }
+}
+
+//the length of the absolute path of `stem`.pdf in `dir` the way Windows counts it
+fn path_len(dir: &Path, stem: &str) -> io::Result<usize> {
+    let path = std::path::absolute(dir.join(format!("{stem}.pdf")))?;
+    Ok(path.to_string_lossy().encode_utf16().count())
+}
+
+//the path the file would be renamed to
+fn target_path(path: &Path, dir: &Path, date_ordertype_name: &str, separator: char) -> PathBuf {
+    //prepare the new path to rename the file
+    let new_path = dir.join(format!("{date_ordertype_name}.pdf"));
+
+    //check if file exists and add counter to filename to create unique filename, on a
+    //case-insensitive filesystem it may be the file itself with a name differing in case
+    if new_path.exists() && !is_case_change(path, &new_path) {
+        get_unique_filename(new_path, separator)
+    } else {
+        new_path
+    }
+}
+
+/// Checks if renaming `path` to `new_path` only changes the case of the name, on a filesystem
+/// that ignores case where `new_path` already exists as `path` itself.
+pub fn is_case_change(path: &Path, new_path: &Path) -> bool {
+    let (Some(name), Some(new_name)) = (path.file_name(), new_path.file_name()) else {
+        return false;
+    };
+    if path.parent() != new_path.parent()
+        || name == new_name
+        || name.to_string_lossy().to_lowercase() != new_name.to_string_lossy().to_lowercase()
+        || !new_path.exists()
+    {
+        return false;
+    }
+    //the directory lists the file with its stored name, a file named exactly `new_name` is
+    //another one on a case-sensitive filesystem
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    read_dir(dir).is_ok_and(|entries| !entries.flatten().any(|entry| entry.file_name() == new_name))
+}
+
+/// Renames `path` to `new_path` differing only in case through a temporary name, as some
+/// case-insensitive filesystems ignore such a rename or fail on it.
+pub fn change_case(path: &Path, new_path: &Path) -> io::Result<()> {
+    let temporary = temporary_path(new_path);
+    fs::rename(path, &temporary)?;
+    fs::rename(&temporary, new_path).inspect_err(|_| {
+        let _ = fs::rename(&temporary, path);
+    })
+}
+
+//a hidden name next to `path` to write or move a file to before it gets its name
+fn temporary_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!(
+        ".{}.tr_pdf_rename.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ))
+}
+
+pub fn get_unique_filename(mut path: PathBuf, separator: char) -> PathBuf {
+    let mut counter = 1;
+    let original_path = path.clone();
+
+    while path.exists() {
+        let mut new_path = original_path.clone();
+        new_path.set_file_name(format!(
+            "{}{separator}{}.pdf",
+            original_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(""),
+            counter
+        ));
+        path = new_path;
+        counter += 1;
+    }
+
+    path
+}
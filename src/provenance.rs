@@ -0,0 +1,105 @@
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{parser::RULES_VERSION, xattr};
+
+/// Whether the provenance of renamed files is recorded in extended attributes on this platform.
+pub const PROVENANCE_SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "android"));
+
+//user attributes, readable with e.g. `getfattr -d`
+const ORIGINAL_NAME: &str = "user.tr_pdf_rename.original_name";
+const PARSED_AT: &str = "user.tr_pdf_rename.parsed_at";
+const VERSION: &str = "user.tr_pdf_rename.version";
+const TEMPLATE: &str = "user.tr_pdf_rename.template";
+const RULES: &str = "user.tr_pdf_rename.rules";
+
+/// Where a renamed file came from.
+#[derive(Debug)]
+pub struct Provenance {
+    /// The filename before the first rename.
+    pub original_name: String,
+    /// When the file was parsed for its current name.
+    pub parsed_at: Option<DateTime<Utc>>,
+    /// Version of TR_PDF_rename that renamed it.
+    pub version: Option<String>,
+    /// [Scheme](crate::naming::Template::scheme) of the template it was named with.
+    pub template: Option<String>,
+    /// Version of the parser rules it was named by.
+    pub rules: Option<String>,
+}
+
+/// The provenance recorded on `path`, None if it was never renamed or the filesystem has no
+/// extended attributes.
+pub fn read(path: &Path) -> Option<Provenance> {
+    let text = |name| {
+        xattr::get(path, name)
+            .ok()
+            .flatten()
+            .map(|value| String::from_utf8_lossy(&value).into_owned())
+    };
+    Some(Provenance {
+        original_name: text(ORIGINAL_NAME)?,
+        parsed_at: text(PARSED_AT)
+            .and_then(|parsed_at| DateTime::parse_from_rfc3339(&parsed_at).ok())
+            .map(|parsed_at| parsed_at.to_utc()),
+        version: text(VERSION),
+        template: text(TEMPLATE),
+        rules: text(RULES),
+    })
+}
+
+/// The name the file at `path` had before it was first renamed, its current name if it was not
+/// renamed yet.
+pub fn original_name(path: &Path) -> String {
+    read(path).map_or_else(
+        || {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        },
+        |provenance| provenance.original_name,
+    )
+}
+
+/// Records on the renamed file at `path` its `original_name`, the time it was parsed, the
+/// version of TR_PDF_rename and the naming scheme, the `template` scheme and the parser rules.
+/// Filesystems without extended attributes are skipped.
+pub fn record(
+    path: &Path,
+    original_name: &str,
+    parsed_at: DateTime<Utc>,
+    template: &str,
+) -> io::Result<()> {
+    let parsed_at = parsed_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+    set(
+        path,
+        &[
+            (ORIGINAL_NAME, original_name),
+            (PARSED_AT, parsed_at.as_str()),
+            (VERSION, env!("CARGO_PKG_VERSION")),
+            (TEMPLATE, template),
+            (RULES, RULES_VERSION),
+        ],
+    )
+}
+
+/// Records on the file at `path` that it is named with the `template` scheme and the current
+/// parser rules, e.g. after it was migrated to a new template.
+pub fn record_scheme(path: &Path, template: &str) -> io::Result<()> {
+    set(path, &[(TEMPLATE, template), (RULES, RULES_VERSION)])
+}
+
+fn set(path: &Path, attributes: &[(&str, &str)]) -> io::Result<()> {
+    for &(name, value) in attributes {
+        match xattr::set(path, name, value.as_bytes()) {
+            Err(err) if err.kind() == ErrorKind::Unsupported => return Ok(()),
+            result => result?,
+        }
+    }
+    Ok(())
+}
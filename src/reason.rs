@@ -0,0 +1,132 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, ErrorKind},
+};
+
+use serde::{Serialize, Serializer};
+
+/// Why a file was not renamed. The codes are stable, scripts can branch on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reason {
+    /// The name follows the naming scheme already, the file is not read.
+    AlreadyRenamed,
+    /// The name matches one of the `organized_patterns`, the file is not read.
+    Organized,
+    /// The file has the name it would get.
+    Unchanged,
+    /// The archive holds the same document already.
+    InArchive,
+    TooLarge,
+    NotModifiedRecently,
+    /// The document type is not selected or excluded.
+    TypeNotSelected,
+    /// The ISIN is not selected or the document has none.
+    IsinNotSelected,
+    /// The amount is out of the selected range or the document has none.
+    AmountOutOfRange,
+    /// The file is not downloaded from the cloud.
+    OnlineOnly,
+    ExtractionFailed,
+    NoDate,
+    UnknownDocType,
+    /// The pre-rename hook vetoed the rename.
+    HookVeto,
+    /// The pre-rename hook failed or returned an invalid decision.
+    HookFailed,
+    /// The pre-rename hook ran longer than the timeout and was killed.
+    HookTimeout,
+    /// The pre-rename hook returned a name that is empty or contains a path separator.
+    InvalidName,
+    /// The folder layout would put the file outside of the archive.
+    OutsideTargetDir,
+    /// The new path would exceed the maximum path length.
+    NameTooLong,
+    /// The file is encrypted already, so it cannot be rewritten or encrypted again.
+    AlreadyEncrypted,
+    /// Any other problem with the file, e.g. a PDF that cannot be rewritten.
+    Other,
+}
+
+impl Reason {
+    /// The code in the reports, e.g. `no_date`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Reason::AlreadyRenamed => "already_renamed",
+            Reason::Organized => "organized",
+            Reason::Unchanged => "unchanged",
+            Reason::InArchive => "in_archive",
+            Reason::TooLarge => "too_large",
+            Reason::NotModifiedRecently => "not_modified_recently",
+            Reason::TypeNotSelected => "type_not_selected",
+            Reason::IsinNotSelected => "isin_not_selected",
+            Reason::AmountOutOfRange => "amount_out_of_range",
+            Reason::OnlineOnly => "online_only",
+            Reason::ExtractionFailed => "extraction_failed",
+            Reason::NoDate => "no_date",
+            Reason::UnknownDocType => "unknown_doc_type",
+            Reason::HookVeto => "hook_veto",
+            Reason::HookFailed => "hook_failed",
+            Reason::HookTimeout => "hook_timeout",
+            Reason::InvalidName => "invalid_name",
+            Reason::OutsideTargetDir => "outside_target_dir",
+            Reason::NameTooLong => "name_too_long",
+            Reason::AlreadyEncrypted => "already_encrypted",
+            Reason::Other => "other",
+        }
+    }
+
+    /// Whether the file could not be read or recognized, so `--retry-unparsed` may rename it once
+    /// the parser knows it. Files skipped by a hook, a filter or a limit are not retried.
+    pub fn is_unparsed(self) -> bool {
+        matches!(
+            self,
+            Reason::ExtractionFailed | Reason::NoDate | Reason::UnknownDocType
+        )
+    }
+
+    /// An error of invalid data with `message`, skipping the file for this reason.
+    pub fn error(self, message: impl Into<String>) -> io::Error {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            Skip {
+                reason: self,
+                message: message.into(),
+            },
+        )
+    }
+
+    /// The reason a file was skipped with `err`, `Other` if it is not known.
+    pub fn of(err: &io::Error) -> Reason {
+        err.get_ref()
+            .and_then(|err| err.downcast_ref::<Skip>())
+            .map_or(Reason::Other, |skip| skip.reason)
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl Serialize for Reason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+//the payload of the errors of skipped files, only the message is shown
+#[derive(Debug)]
+struct Skip {
+    reason: Reason,
+    message: String,
+}
+
+impl fmt::Display for Skip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for Skip {}
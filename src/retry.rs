@@ -0,0 +1,83 @@
+use std::{
+    io::{self, ErrorKind},
+    thread,
+    time::Duration,
+};
+
+/// How often a filesystem operation failing with a transient error is tried again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// Attempts after the first one.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every further one.
+    pub delay: Duration,
+}
+
+/// Retries of renames blocked by another program having the file open.
+pub const RENAME: Retry = Retry {
+    retries: 5,
+    delay: Duration::from_millis(200),
+};
+
+/// Retries of reads and renames failing with a network error, e.g. on a network share.
+pub const IO: Retry = Retry {
+    retries: 3,
+    delay: Duration::from_millis(500),
+};
+
+impl Retry {
+    /// These retries with the number of `retries` and the first delay of `delay_ms` replaced if
+    /// given.
+    pub fn with(self, retries: Option<u32>, delay_ms: Option<u64>) -> Retry {
+        Retry {
+            retries: retries.unwrap_or(self.retries),
+            delay: delay_ms.map_or(self.delay, Duration::from_millis),
+        }
+    }
+
+    /// Runs `operation`, and again after a growing delay as long as it fails with an error
+    /// `transient` accepts and retries are left.
+    pub fn run<T>(
+        &self,
+        transient: impl Fn(&io::Error) -> bool,
+        mut operation: impl FnMut() -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut delay = self.delay;
+        for _ in 0..self.retries {
+            match operation() {
+                Err(err) if transient(&err) => {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+        operation()
+    }
+}
+
+/// Checks if `err` means another program has the file open, e.g. a previewer or virus scanner
+/// on Windows.
+pub fn is_sharing_violation(err: &io::Error) -> bool {
+    //ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    cfg!(windows) && matches!(err.raw_os_error(), Some(32 | 33))
+}
+
+/// Checks if `err` may go away by trying again, like a timeout or dropped connection of a
+/// network share.
+pub fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::ResourceBusy
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::NetworkDown
+    )
+    //ERROR_BAD_NETPATH, ERROR_UNEXP_NET_ERR, ERROR_NETNAME_DELETED and ERROR_SEM_TIMEOUT
+    || (cfg!(windows) && matches!(err.raw_os_error(), Some(53 | 59 | 64 | 121)))
+}
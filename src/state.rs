@@ -0,0 +1,51 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+//name of the state file kept in every processed directory
+pub const STATE_FILE_NAME: &str = ".tr_pdf_rename_state.json";
+
+/// Information remembered between runs on the same directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Start time of the previous run on this directory.
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+    /// Names of the files that could not be parsed in previous runs.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub unparsed: BTreeSet<String>,
+}
+
+impl State {
+    /// Loads the state file of `dir`. A missing state file yields an empty state.
+    pub fn load(dir: &Path) -> io::Result<State> {
+        match fs::read(state_path(dir)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|err| io::Error::new(ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(State::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Checks if `dir` has a state file.
+    pub fn exists(dir: &Path) -> bool {
+        state_path(dir).is_file()
+    }
+
+    /// Writes the state file of `dir`.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        fs::write(state_path(dir), json)
+    }
+}
+
+fn state_path(dir: &Path) -> PathBuf {
+    dir.join(STATE_FILE_NAME)
+}
@@ -0,0 +1,56 @@
+use std::io;
+
+/// Exports the spans of the run to an OpenTelemetry collector until it is dropped, which sends
+/// the spans not exported yet.
+#[cfg_attr(not(feature = "otlp"), allow(dead_code))]
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    provider: opentelemetry_sdk::trace::SdkTracerProvider,
+}
+
+/// Sends the spans of every processed file to the OTLP/HTTP collector at `endpoint`, the base URL
+/// like `http://localhost:4318`.
+#[cfg(feature = "otlp")]
+pub fn init(endpoint: &str) -> io::Result<Telemetry> {
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .map_err(io::Error::other)?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(env!("CARGO_PKG_NAME"))
+                .build(),
+        )
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("tr_pdf_rename"));
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))
+        .map_err(io::Error::other)?;
+
+    Ok(Telemetry { provider })
+}
+
+#[cfg(not(feature = "otlp"))]
+pub fn init(_endpoint: &str) -> io::Result<Telemetry> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "OpenTelemetry export is not available, build with --features otlp",
+    ))
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Err(err) = self.provider.shutdown() {
+            eprintln!("Exporting the traces failed: {err}");
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+/// Whether this build can render thumbnails.
+pub const THUMBNAILS_SUPPORTED: bool = cfg!(feature = "thumbnails");
+
+//width and height of the box the first page is scaled into
+#[cfg_attr(not(feature = "thumbnails"), allow(dead_code))]
+const THUMBNAIL_SIZE: i32 = 320;
+
+/// Renders the first page of the PDF at `path` (encrypted with `password`) to a PNG next to it.
+#[cfg(feature = "thumbnails")]
+pub fn write(path: &Path, password: Option<&str>) -> Result<PathBuf, String> {
+    use image::ImageFormat;
+    use pdfium_render::prelude::PdfRenderConfig;
+
+    let pdfium = pdfium()?;
+    let document = pdfium
+        .load_pdf_from_file(path, password)
+        .map_err(|err| err.to_string())?;
+    let page = document.pages().first().map_err(|err| err.to_string())?;
+    let image = page
+        .render_with_config(&PdfRenderConfig::new().thumbnail(THUMBNAIL_SIZE))
+        .map_err(|err| err.to_string())?
+        .as_image();
+
+    let target = path.with_extension("png");
+    image
+        .save_with_format(&target, ImageFormat::Png)
+        .map_err(|err| err.to_string())?;
+    Ok(target)
+}
+
+/// Loads the pdfium library thumbnails are rendered with.
+#[cfg(feature = "thumbnails")]
+pub fn probe() -> Result<(), String> {
+    pdfium().map(|_| ())
+}
+
+#[cfg(feature = "thumbnails")]
+fn pdfium() -> Result<pdfium_render::prelude::Pdfium, String> {
+    use std::env;
+
+    use pdfium_render::prelude::Pdfium;
+
+    //a library next to the executable wins over the one installed on the system
+    let local_library = env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.parent()
+                .map(Pdfium::pdfium_platform_library_name_at_path)
+        })
+        .filter(|library| library.exists());
+    let bindings = match local_library {
+        Some(library) => Pdfium::bind_to_library(library),
+        None => Pdfium::bind_to_system_library(),
+    }
+    .map_err(|_| {
+        format!(
+            "cannot load the pdfium library, put {} next to the executable or install it",
+            Pdfium::pdfium_platform_library_name().to_string_lossy()
+        )
+    })?;
+    Ok(Pdfium::new(bindings))
+}
+
+#[cfg(not(feature = "thumbnails"))]
+pub fn probe() -> Result<(), String> {
+    Err("thumbnails are not available, build with --features thumbnails".to_string())
+}
+
+#[cfg(not(feature = "thumbnails"))]
+pub fn write(_path: &Path, _password: Option<&str>) -> Result<PathBuf, String> {
+    Err("thumbnails are not available, build with --features thumbnails".to_string())
+}
@@ -0,0 +1,78 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// The phases of processing a single file.
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    /// Extracting the text from the PDF.
+    Extraction,
+    /// Finding date, order type and name in the extracted text.
+    Parsing,
+    /// Reading, checking and renaming files.
+    Filesystem,
+}
+
+/// Wall time spent per phase and per file during a run.
+#[derive(Debug, Default)]
+pub struct Timings {
+    extraction: Duration,
+    parsing: Duration,
+    filesystem: Duration,
+    files: Vec<(PathBuf, Duration)>,
+}
+
+impl Timings {
+    /// Runs `f` in a tracing span of `phase` and adds the time it took to `phase`.
+    pub fn measure<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let span = match phase {
+            Phase::Extraction => tracing::info_span!("extraction"),
+            Phase::Parsing => tracing::info_span!("parsing"),
+            Phase::Filesystem => tracing::info_span!("filesystem"),
+        };
+        let _entered = span.enter();
+        let started = Instant::now();
+        let result = f();
+        let elapsed = started.elapsed();
+
+        match phase {
+            Phase::Extraction => self.extraction += elapsed,
+            Phase::Parsing => self.parsing += elapsed,
+            Phase::Filesystem => self.filesystem += elapsed,
+        }
+
+        result
+    }
+
+    /// Records the total time spent on the file at `path`.
+    pub fn record_file(&mut self, path: &Path, elapsed: Duration) {
+        self.files.push((path.to_path_buf(), elapsed));
+    }
+
+    /// Prints the time per phase and the `slowest` files that took longest.
+    pub fn print_report(&mut self, slowest: usize) {
+        let total = self.extraction + self.parsing + self.filesystem;
+
+        println!("Timings for {} file(s):", self.files.len());
+        for (phase, elapsed) in [
+            ("extraction", self.extraction),
+            ("parsing", self.parsing),
+            ("filesystem", self.filesystem),
+            ("total", total),
+        ] {
+            println!("  {phase:<12}{:>10.3}s", elapsed.as_secs_f64());
+        }
+
+        if slowest == 0 || self.files.is_empty() {
+            return;
+        }
+
+        self.files
+            .sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+        println!("Slowest files:");
+        for (path, elapsed) in self.files.iter().take(slowest) {
+            println!("  {:>10.3}s  {:?}", elapsed.as_secs_f64(), path);
+        }
+    }
+}
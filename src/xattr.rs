@@ -0,0 +1,161 @@
+use std::{io, path::Path};
+
+/// The value of the extended attribute `name` of `path`, None if it is not set.
+pub fn get(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+    sys::get(path, name)
+}
+
+/// Sets the extended attribute `name` of `path` to `value`.
+pub fn set(path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+    sys::set(path, name, value)
+}
+
+/// Removes the extended attribute `name` of `path`, if it is set.
+pub fn remove(path: &Path, name: &str) -> io::Result<()> {
+    sys::remove(path, name)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+mod sys {
+    use std::{
+        ffi::{c_void, CString},
+        io::{self, ErrorKind},
+        os::unix::ffi::OsStrExt,
+        path::Path,
+    };
+
+    #[cfg(target_os = "macos")]
+    const NOT_SET: i32 = libc::ENOATTR;
+    #[cfg(not(target_os = "macos"))]
+    const NOT_SET: i32 = libc::ENODATA;
+
+    pub fn get(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+        let (path, name) = c_strings(path, name)?;
+        //the first call only asks for the size
+        let mut value: Vec<u8> = Vec::new();
+        loop {
+            //SAFETY: path and name are valid C strings and value has room for value.len() bytes
+            let len = unsafe { getxattr(&path, &name, value.as_mut_ptr().cast(), value.len()) };
+            if len < 0 {
+                let err = last_error();
+                return match err.raw_os_error() {
+                    Some(NOT_SET) => Ok(None),
+                    //the value grew between the calls
+                    Some(libc::ERANGE) => {
+                        value.clear();
+                        continue;
+                    }
+                    _ => Err(err),
+                };
+            }
+            let len = len as usize;
+            if value.is_empty() && len > 0 {
+                value.resize(len, 0);
+                continue;
+            }
+            value.truncate(len);
+            return Ok(Some(value));
+        }
+    }
+
+    pub fn set(path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+        let (path, name) = c_strings(path, name)?;
+        //SAFETY: path and name are valid C strings and value is valid for value.len() bytes
+        if unsafe { setxattr(&path, &name, value.as_ptr().cast(), value.len()) } != 0 {
+            return Err(last_error());
+        }
+        Ok(())
+    }
+
+    pub fn remove(path: &Path, name: &str) -> io::Result<()> {
+        let (path, name) = c_strings(path, name)?;
+        //SAFETY: path and name are valid C strings
+        if unsafe { removexattr(&path, &name) } != 0 {
+            let err = last_error();
+            if err.raw_os_error() != Some(NOT_SET) {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    //the error of the last call, with filesystems without extended attributes as Unsupported
+    fn last_error() -> io::Error {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::ENOTSUP) {
+            io::Error::new(
+                ErrorKind::Unsupported,
+                "the filesystem does not support extended attributes",
+            )
+        } else {
+            err
+        }
+    }
+
+    fn c_strings(path: &Path, name: &str) -> io::Result<(CString, CString)> {
+        let invalid =
+            |_| io::Error::new(ErrorKind::InvalidInput, "path or name contains a NUL byte");
+        Ok((
+            CString::new(path.as_os_str().as_bytes()).map_err(invalid)?,
+            CString::new(name).map_err(invalid)?,
+        ))
+    }
+
+    //macOS has extra arguments for resource forks and symlinks
+    #[cfg(target_os = "macos")]
+    unsafe fn getxattr(path: &CString, name: &CString, value: *mut c_void, size: usize) -> isize {
+        libc::getxattr(path.as_ptr(), name.as_ptr(), value, size, 0, 0)
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe fn setxattr(path: &CString, name: &CString, value: *const c_void, size: usize) -> i32 {
+        libc::setxattr(path.as_ptr(), name.as_ptr(), value, size, 0, 0)
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe fn removexattr(path: &CString, name: &CString) -> i32 {
+        libc::removexattr(path.as_ptr(), name.as_ptr(), 0)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    unsafe fn getxattr(path: &CString, name: &CString, value: *mut c_void, size: usize) -> isize {
+        libc::getxattr(path.as_ptr(), name.as_ptr(), value, size)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    unsafe fn setxattr(path: &CString, name: &CString, value: *const c_void, size: usize) -> i32 {
+        libc::setxattr(path.as_ptr(), name.as_ptr(), value, size, 0)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    unsafe fn removexattr(path: &CString, name: &CString) -> i32 {
+        libc::removexattr(path.as_ptr(), name.as_ptr())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+mod sys {
+    use std::{
+        io::{self, ErrorKind},
+        path::Path,
+    };
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            ErrorKind::Unsupported,
+            "extended attributes are not supported on this platform",
+        )
+    }
+
+    pub fn get(_path: &Path, _name: &str) -> io::Result<Option<Vec<u8>>> {
+        Err(unsupported())
+    }
+
+    pub fn set(_path: &Path, _name: &str, _value: &[u8]) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn remove(_path: &Path, _name: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+}